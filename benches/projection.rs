@@ -0,0 +1,57 @@
+// Time to project a million positions onto the screen one at a time, as
+// drawing from Point3Ds does, and eight at a time with `project_batch`, as
+// drawing from arrays does. Run with `cargo bench`.
+use std::*;
+
+use altostratus::graphics::{Camera, Point3D, PointCloud, Viewport};
+use altostratus::layout::Preset;
+use altostratus::rng::Rng;
+use altostratus::view::{VIEWPORT_DISTANCE, ViewState};
+
+const POSITIONS: usize = 1_000_000;
+const ROUNDS: usize = 20;
+
+fn milliseconds_per_round(mut project: impl FnMut()) -> f64 {
+    let start = time::Instant::now();
+    for _ in 0..ROUNDS {
+        project();
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / ROUNDS as f64
+}
+
+fn main() {
+    let mut rng = Rng::new(1);
+    let positions: Vec<[f32; 3]> = (0..POSITIONS)
+        .map(|_| [rng.unit(), rng.unit(), rng.unit()].map(|c| c * 2.0 - 1.0))
+        .collect();
+    let point_cloud: PointCloud = positions
+        .iter()
+        .map(|&[x, y, z]| Point3D::new(x, y, z))
+        .collect();
+
+    let viewport = Viewport::new(1920, 1080);
+    let (yaw, pitch) = Preset::Isometric.angles();
+    let view = ViewState::framing(&point_cloud, viewport, yaw, pitch);
+    let mut camera = Camera::new(view.center, VIEWPORT_DISTANCE, view.fov);
+    view.apply(&mut camera);
+    camera.screen.resize(viewport);
+    let projection = camera.projection();
+    println!("{} positions", POSITIONS);
+
+    let single = milliseconds_per_round(|| {
+        for point in point_cloud.points() {
+            let camera_point = projection.to_camera(point);
+            if camera_point.z >= VIEWPORT_DISTANCE {
+                hint::black_box(projection.to_screen(&camera_point));
+            }
+        }
+    });
+    println!("One at a time:    {:8.2} ms", single);
+
+    let mut projected = Vec::with_capacity(POSITIONS);
+    let batch = milliseconds_per_round(|| {
+        projection.project_batch(&positions, &mut projected);
+        hint::black_box(&projected);
+    });
+    println!("Eight at a time:  {:8.2} ms, {:.2}x", batch, single / batch);
+}
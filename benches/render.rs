@@ -0,0 +1,73 @@
+// Time per frame of a turntable of a million points, drawn from the cloud's
+// own points and from the separate arrays `ImageRenderer` keeps. Run with
+// `cargo bench`.
+use std::*;
+
+use altostratus::graphics::{Point3D, PointCloud, Viewport};
+use altostratus::layout::Preset;
+use altostratus::render::{self, ImageRenderer, Renderer};
+use altostratus::rng::Rng;
+use altostratus::view::ViewState;
+
+const POINTS: usize = 1_000_000;
+const FRAMES: usize = 30;
+const VIEWPORT: Viewport = Viewport {
+    width: 1920,
+    height: 1080,
+};
+
+// A lumpy sphere of points, the same on every run
+fn cloud() -> PointCloud {
+    let mut rng = Rng::new(1);
+    (0..POINTS)
+        .map(|_| {
+            let (yaw, pitch) = (
+                rng.unit() * f32::consts::TAU,
+                (rng.unit() - 0.5) * f32::consts::PI,
+            );
+            let radius = 1.0 + 0.1 * rng.unit();
+            Point3D::new(
+                radius * pitch.cos() * yaw.cos(),
+                radius * pitch.sin(),
+                radius * pitch.cos() * yaw.sin(),
+            )
+        })
+        .collect()
+}
+
+fn milliseconds_per_frame(views: &[ViewState], mut render: impl FnMut(&ViewState)) -> f64 {
+    let start = time::Instant::now();
+    for view in views {
+        render(view);
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / views.len() as f64
+}
+
+fn main() {
+    let point_cloud = cloud();
+    let (yaw, pitch) = Preset::Isometric.angles();
+    let view = ViewState::framing(&point_cloud, VIEWPORT, yaw, pitch);
+    let views = render::turntable(&view, 360.0, FRAMES);
+    println!(
+        "{} points, {} frames at {}x{}",
+        POINTS, FRAMES, VIEWPORT.width, VIEWPORT.height
+    );
+
+    let points = milliseconds_per_frame(&views, |view| {
+        hint::black_box(render::render_image(&point_cloud, view, VIEWPORT));
+    });
+    println!("Points (a Point3D each):  {:8.2} ms/frame", points);
+
+    let start = time::Instant::now();
+    let mut renderer = ImageRenderer::new(&point_cloud);
+    let copy = start.elapsed().as_secs_f64() * 1000.0;
+    let arrays = milliseconds_per_frame(&views, |view| {
+        hint::black_box(renderer.render(view, VIEWPORT));
+    });
+    println!(
+        "Arrays:                   {:8.2} ms/frame, {:.2}x ({:.2} ms to copy once)",
+        arrays,
+        points / arrays,
+        copy
+    );
+}
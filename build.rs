@@ -0,0 +1,25 @@
+// With the `capi` feature, writes the C header for the functions in
+// src/capi.rs to include/altostratus.h
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "capi")]
+    {
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        let config = cbindgen::Config {
+            language: cbindgen::Language::C,
+            include_guard: Some("ALTOSTRATUS_H".to_string()),
+            autogen_warning: Some(
+                "/* Generated by build.rs from src/capi.rs; don't edit */".to_string(),
+            ),
+            cpp_compat: true,
+            usize_is_size_t: true,
+            ..Default::default()
+        };
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src("src/capi.rs")
+            .generate()
+            .expect("couldn't generate the C header")
+            .write_to_file("include/altostratus.h");
+    }
+}
@@ -0,0 +1,154 @@
+#ifndef ALTOSTRATUS_H
+#define ALTOSTRATUS_H
+
+/* Generated by build.rs from src/capi.rs; don't edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Points to draw, made by `alto_cloud_new` and freed by `alto_cloud_free`.
+ */
+typedef struct AltoCloud AltoCloud;
+
+/**
+ * Image settings and camera, made by `alto_renderer_new` and freed by
+ * `alto_renderer_free`.
+ */
+typedef struct AltoRenderer AltoRenderer;
+
+/**
+ * Where the picture is taken from, with angles in degrees. A distance of 0
+ * frames the whole cloud; a field of view of 0 keeps the viewer's.
+ */
+typedef struct AltoCamera {
+  float yaw;
+  float pitch;
+  float roll;
+  float distance;
+  float fov;
+} AltoCamera;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * A new cloud without points.
+ */
+struct AltoCloud *alto_cloud_new(void);
+
+/**
+ * Frees a cloud.
+ *
+ * # Safety
+ *
+ * `cloud` must come from `alto_cloud_new` and not be used again, or be NULL.
+ */
+void alto_cloud_free(struct AltoCloud *cloud);
+
+/**
+ * Adds `count` points, z up, from `positions` as x, y, z floats one point
+ * after another. `colors`, if not NULL, holds r, g, b bytes for each point
+ * the same way. Returns false, adding nothing, if `cloud` or `positions` is
+ * NULL.
+ *
+ * # Safety
+ *
+ * `cloud` must come from `alto_cloud_new`. `positions` must hold `count * 3`
+ * floats and `colors`, unless NULL, `count * 3` bytes.
+ */
+bool alto_cloud_add_points(struct AltoCloud *cloud,
+                           const float *positions,
+                           const uint8_t *colors,
+                           size_t count);
+
+/**
+ * Number of points in a cloud, or 0 if `cloud` is NULL.
+ *
+ * # Safety
+ *
+ * `cloud` must come from `alto_cloud_new` or be NULL.
+ */
+size_t alto_cloud_len(const struct AltoCloud *cloud);
+
+/**
+ * The isometric camera the viewer starts with.
+ */
+struct AltoCamera alto_camera_default(void);
+
+/**
+ * A renderer for `width` by `height` images of one-pixel points over black,
+ * with the axes, from the default camera.
+ */
+struct AltoRenderer *alto_renderer_new(uint16_t width, uint16_t height);
+
+/**
+ * Frees a renderer.
+ *
+ * # Safety
+ *
+ * `renderer` must come from `alto_renderer_new` and not be used again, or be
+ * NULL.
+ */
+void alto_renderer_free(struct AltoRenderer *renderer);
+
+/**
+ * Sets the camera later images are drawn from.
+ *
+ * # Safety
+ *
+ * `renderer` must come from `alto_renderer_new` or be NULL.
+ */
+void alto_renderer_set_camera(struct AltoRenderer *renderer, struct AltoCamera camera);
+
+/**
+ * Sets the background color.
+ *
+ * # Safety
+ *
+ * `renderer` must come from `alto_renderer_new` or be NULL.
+ */
+void alto_renderer_set_background(struct AltoRenderer *renderer, uint8_t r, uint8_t g, uint8_t b);
+
+/**
+ * Sets the point size in pixels; 0 draws one-pixel points.
+ *
+ * # Safety
+ *
+ * `renderer` must come from `alto_renderer_new` or be NULL.
+ */
+void alto_renderer_set_point_size(struct AltoRenderer *renderer, float size);
+
+/**
+ * Sets whether the cloud's axes are drawn.
+ *
+ * # Safety
+ *
+ * `renderer` must come from `alto_renderer_new` or be NULL.
+ */
+void alto_renderer_set_axes(struct AltoRenderer *renderer, bool axes);
+
+/**
+ * Draws `cloud` into `pixels` as r, g, b bytes, row by row from the top
+ * left. Returns false, writing nothing, if a pointer is NULL, `length` is
+ * under width * height * 3 bytes or the renderer's settings are invalid.
+ *
+ * # Safety
+ *
+ * `renderer` must come from `alto_renderer_new`, `cloud` from
+ * `alto_cloud_new`, and `pixels` must hold `length` bytes.
+ */
+bool alto_render_rgb(const struct AltoRenderer *renderer,
+                     const struct AltoCloud *cloud,
+                     uint8_t *pixels,
+                     size_t length);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ALTOSTRATUS_H */
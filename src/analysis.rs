@@ -0,0 +1,156 @@
+use crate::graphics::{Color, Point3D};
+use crate::rng::Rng;
+use crate::segmentation;
+use crate::spatial::SpatialGrid;
+
+const KMEANS_MAX_ITERATIONS: usize = 100;
+
+// Density-based clustering: points with at least `min_points` neighbors within
+// `eps` are cores, and clusters grow through chains of cores. Points reachable
+// from no core are noise (None). Cluster ids are numbered from zero.
+pub fn cluster_dbscan(points: &[Point3D], eps: f32, min_points: usize) -> Vec<Option<usize>> {
+    let grid = SpatialGrid::new(points, eps);
+    let mut labels: Vec<Option<usize>> = vec![None; points.len()];
+    let mut visited = vec![false; points.len()];
+    let mut next_label = 0;
+
+    for seed in 0..points.len() {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+
+        let neighbors = grid.neighbors_within(&points[seed], eps);
+        if neighbors.len() < min_points {
+            continue; // Noise unless a later core reaches it
+        }
+
+        labels[seed] = Some(next_label);
+        let mut stack = neighbors;
+        while let Some(current) = stack.pop() {
+            if labels[current].is_none() {
+                labels[current] = Some(next_label);
+            }
+            if visited[current] {
+                continue;
+            }
+            visited[current] = true;
+
+            let neighbors = grid.neighbors_within(&points[current], eps);
+            if neighbors.len() >= min_points {
+                stack.extend(neighbors);
+            }
+        }
+
+        next_label += 1;
+    }
+
+    labels
+}
+
+// Lloyd's k-means, seeded with `k` distinct points picked by `rng`.
+// Returns the cluster id of every point.
+pub fn cluster_kmeans(points: &[Point3D], k: usize, rng: &mut Rng) -> Vec<usize> {
+    let k = k.min(points.len());
+    if k == 0 {
+        return vec![0; points.len()];
+    }
+
+    // Partial Fisher-Yates shuffle picks the initial centroids
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    for i in 0..k {
+        let j = i + rng.below(indices.len() - i);
+        indices.swap(i, j);
+    }
+    let mut centroids: Vec<[f32; 3]> = indices[..k]
+        .iter()
+        .map(|&i| [points[i].x, points[i].y, points[i].z])
+        .collect();
+
+    let mut labels = vec![0; points.len()];
+    for iteration in 0..KMEANS_MAX_ITERATIONS {
+        let mut changed = false;
+        for (point, label) in points.iter().zip(&mut labels) {
+            let nearest = (0..k)
+                .min_by(|&a, &b| {
+                    distance_squared(point, centroids[a])
+                        .total_cmp(&distance_squared(point, centroids[b]))
+                })
+                .unwrap();
+            changed |= *label != nearest;
+            *label = nearest;
+        }
+
+        if !changed && iteration > 0 {
+            break;
+        }
+
+        let mut sums = vec![[0.0f32; 3]; k];
+        let mut counts = vec![0usize; k];
+        for (point, &label) in points.iter().zip(&labels) {
+            sums[label][0] += point.x;
+            sums[label][1] += point.y;
+            sums[label][2] += point.z;
+            counts[label] += 1;
+        }
+        // Empty clusters keep their previous centroid
+        for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+            if count > 0 {
+                *centroid = sum.map(|value| value / count as f32);
+            }
+        }
+    }
+
+    labels
+}
+
+// Paint each point with its cluster's color; noise keeps the default color
+pub fn color_by_cluster(points: &mut [Point3D], labels: &[Option<usize>]) {
+    for (point, label) in points.iter_mut().zip(labels) {
+        point.color = match label {
+            Some(label) => segmentation::label_color(*label),
+            None => Color::Default,
+        };
+    }
+}
+
+fn distance_squared(point: &Point3D, centroid: [f32; 3]) -> f32 {
+    (point.x - centroid[0]).powi(2)
+        + (point.y - centroid[1]).powi(2)
+        + (point.z - centroid[2]).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two tight blobs ten apart, then one point far from both
+    fn blobs() -> Vec<Point3D> {
+        let mut rng = Rng::new(7);
+        let mut points: Vec<Point3D> = (0..40)
+            .map(|i| {
+                let x = if i < 20 { 0.0 } else { 10.0 };
+                Point3D::new(x + rng.unit(), rng.unit(), rng.unit())
+            })
+            .collect();
+        points.push(Point3D::new(5.0, 20.0, 0.0));
+        points
+    }
+
+    #[test]
+    fn dbscan_separates_blobs_from_noise() {
+        let labels = cluster_dbscan(&blobs(), 1.0, 3);
+        assert!(labels[..20].iter().all(|&label| label == Some(0)));
+        assert!(labels[20..40].iter().all(|&label| label == Some(1)));
+        assert_eq!(labels[40], None);
+    }
+
+    #[test]
+    fn kmeans_separates_blobs() {
+        let points = &blobs()[..40];
+        let labels = cluster_kmeans(points, 2, &mut Rng::new(3));
+        assert!(labels[..20].iter().all(|&label| label == labels[0]));
+        assert!(labels[20..].iter().all(|&label| label == labels[20]));
+        assert_ne!(labels[0], labels[20]);
+    }
+}
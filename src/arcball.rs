@@ -0,0 +1,41 @@
+use crate::graphics::Viewport;
+use crate::quaternion::Quaternion;
+
+// A virtual trackball filling the view: dragging turns the scene as if the
+// cursor were rolling a ball centered on the screen, so the point grabbed
+// stays under the cursor. Unlike adding drags to the yaw and pitch, a drag
+// turns the scene the same way however it's already turned, over the poles
+// too. Beyond the ball the surface flattens out (Holroyd's variant of
+// Shoemake's arcball), so drags near the edges still turn smoothly.
+pub struct Arcball {
+    center: (f32, f32), // Pixels
+    radius: f32,        // Pixels, half the shorter side of the view
+}
+
+impl Arcball {
+    pub fn new(viewport: Viewport) -> Arcball {
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        Arcball {
+            center: (width / 2.0, height / 2.0),
+            radius: (width.min(height) / 2.0).max(1.0),
+        }
+    }
+
+    // Where the pixel (x, y), from the top left, touches the ball, in camera
+    // space (x right, y up, z into the screen), for a ball of radius 1
+    fn surface(&self, (x, y): (f32, f32)) -> [f32; 3] {
+        let x = (x - self.center.0) / self.radius;
+        let y = (self.center.1 - y) / self.radius;
+        let squared = x * x + y * y;
+        let z = match squared <= 0.5 {
+            true => (1.0 - squared).sqrt(),
+            false => 0.5 / squared.sqrt(),
+        };
+        [x, y, -z] // The side facing the camera
+    }
+
+    // Rotation of the scene, in camera space, for a drag between two pixels
+    pub fn rotation(&self, from: (f32, f32), to: (f32, f32)) -> Quaternion {
+        Quaternion::from_arc(self.surface(from), self.surface(to))
+    }
+}
@@ -1,5 +1,20 @@
+use std::*;
+
 use clap::{ArgAction, Parser};
 
+use crate::ascii::Charset;
+use crate::backend;
+use crate::config::{self, ColorMode};
+use crate::csv::CsvOptions;
+use crate::graphics::{Color, CoordinateSystem};
+use crate::image::ImageFormat;
+use crate::inline;
+use crate::input::MouseSettings;
+use crate::keys::KeyBindings;
+use crate::plot::{BarStyle, Colormap};
+use crate::view::VIEWPORT_FOV;
+use crate::view::{CameraPose, Intrinsics};
+
 #[derive(Parser)]
 #[command(name = "altostratus")]
 #[command(about = "Visualize 3D point files in the terminal!", long_about = None)]
@@ -13,20 +28,626 @@ pub struct Args {
     #[arg(short = 'f', long = "files", value_name = "FILES", action = ArgAction::Append)]
     pub files: Vec<String>,
 
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Reload the files whenever they change on disk
+    #[arg(long = "watch")]
+    pub watch: bool,
+
+    /// Read points from stdin (one point file line per record) while viewing
+    #[arg(long = "stdin")]
+    pub stdin: bool,
+
+    /// Listen for newline-delimited point records over TCP
+    #[arg(long = "listen", value_name = "ADDR")]
+    pub listen: Option<String>,
+
+    /// Listen for point records in UDP datagrams
+    #[arg(long = "listen-udp", value_name = "ADDR")]
+    pub listen_udp: Option<String>,
+
+    /// Bind keys to a camera movement, e.g. orbit-left=h,left (repeatable)
+    #[arg(long = "bind", value_name = "ACTION=KEYS", action = ArgAction::Append)]
+    pub bind: Vec<String>,
+
+    /// Seed for stochastic operations such as plane detection
+    #[arg(long = "seed", value_name = "N", default_value_t = 0)]
+    pub seed: u64,
+
+    /// Terminal background color, used to keep points visible against it [default: black]
+    #[arg(long = "background", value_name = "COLOR")]
+    pub background: Option<String>,
+
+    /// Draw colors exactly as given, even if they match the background
+    #[arg(long = "no-contrast-guard")]
+    pub no_contrast_guard: bool,
+
+    /// Draw every point in the terminal's default color
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+
+    /// Print colors from the 256-color palette, for terminals without 24-bit color
+    #[arg(long = "ansi-256")]
+    pub ansi_256: bool,
+
+    /// Multiplier on how far dragging the mouse turns or pans the camera [default: 1]
+    #[arg(long = "mouse-speed", value_name = "FACTOR")]
+    pub mouse_speed: Option<f32>,
+
+    /// Multiplier on how far a scroll step zooms [default: 1]
+    #[arg(long = "scroll-speed", value_name = "FACTOR")]
+    pub scroll_speed: Option<f32>,
+
+    /// Tilt the camera up when dragging up, like flight controls
+    #[arg(long = "invert-y")]
+    pub invert_y: bool,
+
+    /// Turn the view by dragging a virtual trackball instead of orbiting
+    #[arg(long = "arcball")]
+    pub arcball: bool,
+
+    /// Keep the view gliding after a drag, scroll or key, slowing by FRICTION per second [default: 4]
+    #[arg(long = "inertia", value_name = "FRICTION", num_args = 0..=1, default_missing_value = "4")]
+    pub inertia: Option<f32>,
+
+    /// Frame the view and fit the axes to the PERCENT-th to (100-PERCENT)-th percentile of
+    /// each axis instead of every point, so outliers don't shrink the rest
+    #[arg(long = "bounds-percentile", value_name = "PERCENT")]
+    pub bounds_percentile: Option<f32>,
+
+    /// How frames are drawn: braille, ascii, halfblock, sixel, kitty or iterm2
+    #[arg(long = "backend", value_name = "NAME", default_value = "braille")]
+    pub backend: String,
+
+    /// Open the points in a native window instead of the terminal (needs a build with the
+    /// window feature)
+    #[arg(long = "window", conflicts_with_all = ["watch", "stdin", "listen", "listen_udp"])]
+    pub window: bool,
+
+    /// Play the files back one after another as the frames of an animation, at FPS frames per
+    /// second [default: 10]
+    #[arg(
+        long = "sequence",
+        value_name = "FPS",
+        num_args = 0..=1,
+        default_missing_value = "10",
+        conflicts_with_all = ["watch", "stdin", "listen", "listen_udp", "window"]
+    )]
+    pub sequence: Option<f32>,
+
+    /// Config file to read defaults from instead of ~/.config/altostratus/config.toml
+    #[arg(long = "config", value_name = "FILE")]
+    pub config: Option<String>,
+
     /// Show detailed help information
     #[arg(long = "help-detailed", hide = true)]
     pub detailed_help: bool,
 }
 
+// How point files are read, shared by the viewer and every command that
+// reads them
+#[derive(clap::Args)]
+pub struct InputArgs {
+    /// CSV/TSV columns holding x, y and z (1-based, comma separated)
+    #[arg(long = "columns", value_name = "X,Y,Z", value_delimiter = ',')]
+    pub columns: Vec<usize>,
+
+    /// CSV/TSV column holding color names (1-based)
+    #[arg(long = "color-column", value_name = "N")]
+    pub color_column: Option<usize>,
+
+    /// CSV/TSV column holding a scalar to color by (1-based)
+    #[arg(long = "scalar-column", value_name = "N")]
+    pub scalar_column: Option<usize>,
+
+    /// CSV/TSV field delimiter (defaults to ',' or tab based on extension)
+    #[arg(long = "delimiter", value_name = "CHAR")]
+    pub delimiter: Option<char>,
+
+    /// Number of CSV/TSV header rows to skip (by default a non-numeric first
+    /// row is skipped)
+    #[arg(long = "header-rows", value_name = "N")]
+    pub header_rows: Option<usize>,
+
+    /// Axis convention of the input: z-up, y-up (right-handed) or left-handed (y-up)
+    #[arg(long = "up-axis", value_name = "SYSTEM", default_value = "z-up")]
+    pub up_axis: String,
+}
+
+// Arguments of "altostratus serve"
+#[derive(Parser)]
+#[command(name = "altostratus serve")]
+#[command(about = "Serve rendered frames of point files over HTTP", long_about = None)]
+pub struct ServeArgs {
+    /// Point cloud files to serve
+    #[arg(value_name = "FILES", required = true)]
+    pub files: Vec<String>,
+
+    /// Port to listen on
+    #[arg(long = "port", value_name = "PORT", default_value_t = 8080)]
+    pub port: u16,
+
+    /// Address to listen on; 0.0.0.0 serves every network interface
+    #[arg(long = "bind", value_name = "ADDR", default_value = "127.0.0.1")]
+    pub bind: String,
+
+    /// Draw rgba and png frames in passes: a sample of the points first, then more each
+    /// time the same view is asked for again
+    #[arg(long = "progressive")]
+    pub progressive: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+// Arguments of "altostratus summary"
+#[derive(Parser)]
+#[command(name = "altostratus summary")]
+#[command(about = "Write a thumbnail PNG next to each point file", long_about = None)]
+pub struct SummaryArgs {
+    /// Point cloud files to summarize
+    #[arg(value_name = "FILES", required = true)]
+    pub files: Vec<String>,
+
+    /// Also write this many views from all around, as <file>-view-N.png
+    #[arg(long = "views", value_name = "N", default_value_t = 0)]
+    pub views: usize,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+// Arguments of "altostratus render"
+#[derive(Parser)]
+#[command(name = "altostratus render")]
+#[command(about = "Render a point file to a PNG without the interactive viewer", long_about = None)]
+pub struct RenderArgs {
+    /// Point cloud file to render
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// Image file to write
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "FILE",
+        required_unless_present = "inline"
+    )]
+    pub output: Option<String>,
+
+    /// File format: png, bmp or jpeg [default: from the output's extension, else png]
+    #[arg(long = "format", value_name = "FORMAT", conflicts_with = "inline")]
+    pub format: Option<String>,
+
+    /// Print the image to the terminal instead: kitty, iterm2 or sixel (terminal image protocols)
+    #[arg(long = "inline", value_name = "PROTOCOL", conflicts_with = "output")]
+    pub inline: Option<String>,
+
+    /// Image width in pixels
+    #[arg(long = "width", value_name = "PIXELS", default_value_t = 1920)]
+    pub width: u16,
+
+    /// Image height in pixels
+    #[arg(long = "height", value_name = "PIXELS", default_value_t = 1080)]
+    pub height: u16,
+
+    /// Background color of the image
+    #[arg(long = "background", value_name = "COLOR", default_value = "black")]
+    pub background: String,
+
+    /// Draw each point as a square this wide, in data units, instead of a single pixel
+    #[arg(long = "point-size", value_name = "SIZE")]
+    pub point_size: Option<f32>,
+
+    /// Leave out the axes
+    #[arg(long = "no-axes")]
+    pub no_axes: bool,
+
+    /// Draw at 2 or 4 times the size across and down and average down, for smooth edges
+    #[arg(long = "supersample", value_name = "FACTOR", default_value_t = 1)]
+    pub supersample: usize,
+
+    /// Camera position in data coordinates (defaults to an isometric view of every point)
+    #[arg(
+        long = "camera",
+        value_name = "X,Y,Z",
+        value_delimiter = ',',
+        allow_negative_numbers = true
+    )]
+    pub camera: Vec<f32>,
+
+    /// Point the camera looks at, in data coordinates (defaults to the center of the points)
+    #[arg(
+        long = "target",
+        value_name = "X,Y,Z",
+        value_delimiter = ',',
+        allow_negative_numbers = true
+    )]
+    pub target: Vec<f32>,
+
+    /// Direction that points up the image, in data coordinates (defaults to the up axis)
+    #[arg(
+        long = "up",
+        value_name = "X,Y,Z",
+        value_delimiter = ',',
+        allow_negative_numbers = true
+    )]
+    pub up: Vec<f32>,
+
+    /// Turn the image clockwise by this many degrees
+    #[arg(
+        long = "roll",
+        value_name = "DEGREES",
+        allow_negative_numbers = true,
+        conflicts_with = "up"
+    )]
+    pub roll: Option<f32>,
+
+    /// Camera-to-world pose matrix, 16 numbers row by row, OpenGL convention (looks down -z)
+    #[arg(
+        long = "pose",
+        value_name = "M00,M01,...,M33",
+        value_delimiter = ',',
+        allow_negative_numbers = true,
+        conflicts_with_all = ["camera", "target", "up", "roll"]
+    )]
+    pub pose: Vec<f32>,
+
+    /// World-to-camera rotation then translation, 12 numbers, OpenCV/COLMAP convention
+    #[arg(
+        long = "extrinsics",
+        value_name = "R00,...,R22,TX,TY,TZ",
+        value_delimiter = ',',
+        allow_negative_numbers = true,
+        conflicts_with_all = ["camera", "target", "up", "roll", "pose"]
+    )]
+    pub extrinsics: Vec<f32>,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Draw on the GPU (needs a build with the gpu feature)
+    #[arg(
+        long = "gpu",
+        conflicts_with_all = ["background", "point_size", "no_axes", "supersample"]
+    )]
+    pub gpu: bool,
+
+    /// PNG photo taken from --pose or --extrinsics to draw the points over, at its size
+    #[arg(
+        long = "photo",
+        value_name = "FILE",
+        requires = "intrinsics",
+        conflicts_with_all = ["width", "height", "gpu"]
+    )]
+    pub photo: Option<String>,
+
+    /// The photo's camera: focal lengths and principal point in pixels, OpenCV/COLMAP convention
+    #[arg(
+        long = "intrinsics",
+        value_name = "FX,FY,CX,CY",
+        value_delimiter = ',',
+        allow_negative_numbers = true,
+        requires = "photo"
+    )]
+    pub intrinsics: Vec<f32>,
+
+    /// Read and draw the file this many points at a time, for point files bigger than memory
+    #[arg(
+        long = "chunk-points",
+        value_name = "N",
+        conflicts_with_all = ["gpu", "photo"]
+    )]
+    pub chunk_points: Option<usize>,
+}
+
+// Arguments of "altostratus animate"
+#[derive(Parser)]
+#[command(name = "altostratus animate")]
+#[command(about = "Render a turntable animation of a point file to a GIF", long_about = None)]
+pub struct AnimateArgs {
+    /// Point cloud file to animate
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// GIF file to write
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    pub output: String,
+
+    /// Degrees to turn around the points over the animation
+    #[arg(long = "orbit", value_name = "DEGREES", num_args = 0..=1, default_value_t = 360.0, default_missing_value = "360", allow_negative_numbers = true)]
+    pub orbit: f32,
+
+    /// Field of view to end on, in degrees, moving the camera to keep the middle the same size
+    #[arg(long = "dolly-zoom", value_name = "DEGREES")]
+    pub dolly_zoom: Option<f32>,
+
+    /// Number of frames
+    #[arg(long = "frames", value_name = "N", default_value_t = 120)]
+    pub frames: usize,
+
+    /// Frames per second (GIF timing is in hundredths of a second)
+    #[arg(long = "fps", value_name = "N", default_value_t = 30.0)]
+    pub fps: f32,
+
+    /// Image width in pixels
+    #[arg(long = "width", value_name = "PIXELS", default_value_t = 640)]
+    pub width: u16,
+
+    /// Image height in pixels
+    #[arg(long = "height", value_name = "PIXELS", default_value_t = 480)]
+    pub height: u16,
+
+    /// Background color of the image
+    #[arg(long = "background", value_name = "COLOR", default_value = "black")]
+    pub background: String,
+
+    /// Draw each point as a square this wide, in data units, instead of a single pixel
+    #[arg(long = "point-size", value_name = "SIZE")]
+    pub point_size: Option<f32>,
+
+    /// Leave out the axes
+    #[arg(long = "no-axes")]
+    pub no_axes: bool,
+
+    /// Draw at 2 or 4 times the size across and down and average down, for smooth edges
+    #[arg(long = "supersample", value_name = "FACTOR", default_value_t = 1)]
+    pub supersample: usize,
+
+    /// Palette picker: median-cut, octree or xterm
+    #[arg(long = "quantizer", value_name = "NAME", default_value = "median-cut")]
+    pub quantizer: String,
+
+    /// Keep the points in compact storage while rendering (far less memory for
+    /// large clouds, with colors rounded to 16 bits)
+    #[arg(
+        long = "compact",
+        conflicts_with_all = ["gpu", "background", "point_size", "supersample"]
+    )]
+    pub compact: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Draw on the GPU (needs a build with the gpu feature)
+    #[arg(
+        long = "gpu",
+        conflicts_with_all = ["background", "point_size", "no_axes", "supersample"]
+    )]
+    pub gpu: bool,
+}
+
+pub struct AnimateOptions {
+    pub file_path: String,
+    pub output: String,
+    pub orbit: f32,              // Degrees
+    pub dolly_zoom: Option<f32>, // Radians of field of view the last frame has
+    pub frames: usize,
+    pub delay: u16, // Hundredths of a second per frame
+    pub image: ImageOptions,
+    pub quantizer: String,
+    pub compact: bool,
+    pub gpu: bool,
+    pub csv_options: CsvOptions,
+    pub coordinate_system: CoordinateSystem,
+}
+
+// Arguments of "altostratus ascii"
+#[derive(Parser)]
+#[command(name = "altostratus ascii")]
+#[command(about = "Print one text frame of a point file to stdout", long_about = None)]
+pub struct AsciiArgs {
+    /// Point cloud file to print
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// Width in characters
+    #[arg(long = "width", value_name = "COLUMNS", default_value_t = 120)]
+    pub width: u16,
+
+    /// Height in lines
+    #[arg(long = "height", value_name = "ROWS", default_value_t = 40)]
+    pub height: u16,
+
+    /// Characters to draw with: braille, blocks or ascii [default: braille]
+    #[arg(long = "charset", value_name = "NAME")]
+    pub charset: Option<String>,
+
+    /// Color the characters with 24-bit escape codes
+    #[arg(long = "color")]
+    pub color: bool,
+
+    /// Leave out the axes
+    #[arg(long = "no-axes")]
+    pub no_axes: bool,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+// Arguments of "altostratus plot"
+#[derive(Parser)]
+#[command(name = "altostratus plot")]
+#[command(about = "Chart x y z points as a colored scatter plot", long_about = None)]
+pub struct PlotArgs {
+    /// Point file to chart
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// PNG file to write instead of printing the chart as text
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// Read the file as a grid of heights, one row of numbers per line, and chart the surface
+    #[arg(long = "surface")]
+    pub surface: bool,
+
+    /// Join neighboring heights of the surface with lines
+    #[arg(long = "wireframe", requires = "surface")]
+    pub wireframe: bool,
+
+    /// Join the points with lines in file order and color them by progress along the path
+    #[arg(long = "trajectory", conflicts_with = "surface")]
+    pub trajectory: bool,
+
+    /// Colors of the trajectory's first and last points [default: blue,red]
+    #[arg(
+        long = "gradient",
+        value_name = "START,END",
+        value_delimiter = ',',
+        requires = "trajectory",
+        conflicts_with = "colormap"
+    )]
+    pub gradient: Vec<String>,
+
+    /// Stand a bar at each point's x and y, as tall as its z
+    #[arg(long = "bars", conflicts_with_all = ["surface", "trajectory"])]
+    pub bars: bool,
+
+    /// Fill the bars' sides and tops with points instead of drawing their edges
+    #[arg(long = "solid", requires = "bars")]
+    pub solid: bool,
+
+    /// Colors by height: ramp or viridis [default: ramp, except for trajectories]
+    #[arg(long = "colormap", value_name = "NAME")]
+    pub colormap: Option<String>,
+
+    /// Text written above the chart
+    #[arg(long = "title", value_name = "TEXT")]
+    pub title: Option<String>,
+
+    /// Width of the text chart in characters
+    #[arg(
+        long = "width",
+        value_name = "COLUMNS",
+        default_value_t = 80,
+        conflicts_with = "output"
+    )]
+    pub width: u16,
+
+    /// Height of the text chart in lines, the title included
+    #[arg(
+        long = "height",
+        value_name = "ROWS",
+        default_value_t = 24,
+        conflicts_with = "output"
+    )]
+    pub height: u16,
+
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+pub struct PlotOptions {
+    pub file_path: String,
+    pub output: Option<String>, // Prints text if not given
+    pub surface: bool,
+    pub wireframe: bool,
+    pub trajectory: bool,
+    pub gradient: Option<(Color, Color)>,
+    pub bars: Option<BarStyle>,
+    pub colormap: Option<Colormap>,
+    pub title: Option<String>,
+    pub columns: u16,
+    pub rows: u16,
+    pub csv_options: CsvOptions,
+    pub coordinate_system: CoordinateSystem,
+}
+
+pub struct AsciiOptions {
+    pub file_path: String,
+    pub columns: u16,
+    pub rows: u16,
+    pub charset: Charset,
+    pub color: bool,
+    pub axes: bool,
+    pub csv_options: CsvOptions,
+    pub coordinate_system: CoordinateSystem,
+}
+
+// How `render` and `animate` draw each image
+pub struct ImageOptions {
+    pub width: u16,
+    pub height: u16,
+    pub background: Color,
+    pub point_size: Option<f32>, // Data units; None draws single pixels
+    pub axes: bool,
+    pub supersampling: usize, // Pixels drawn per image pixel, across and down
+}
+
+pub struct RenderOptions {
+    pub file_path: String,
+    pub output: Option<String>,
+    pub inline: Option<String>, // Terminal image protocol to print with instead
+    pub format: ImageFormat,    // Of the output file
+    pub image: ImageOptions,
+    pub camera: Option<[f32; 3]>, // Data coordinates, like the points
+    pub target: Option<[f32; 3]>,
+    pub up: Option<[f32; 3]>, // Data coordinates; None keeps the up axis up
+    pub roll: f32,            // Radians, clockwise
+    pub pose: Option<CameraPose>, // Data coordinates; replaces camera, target, up and roll
+    pub gpu: bool,
+    pub chunk_points: Option<usize>, // Points read from the file at a time, instead of all at once
+    pub photo: Option<(String, Intrinsics)>, // To draw over, from the pose
+    pub csv_options: CsvOptions,
+    pub coordinate_system: CoordinateSystem,
+}
+
+pub struct ServeOptions {
+    pub file_paths: Vec<String>,
+    pub port: u16,
+    pub bind: String,      // Address to listen on
+    pub progressive: bool, // Refine rgba and png frames over repeated requests
+    pub csv_options: CsvOptions,
+    pub coordinate_system: CoordinateSystem,
+}
+
+pub struct SummaryOptions {
+    pub file_paths: Vec<String>,
+    pub views: usize,
+    pub csv_options: CsvOptions,
+    pub coordinate_system: CoordinateSystem,
+}
+
+// Settings for the interactive viewer collected from the command line
+pub struct RunOptions {
+    pub file_paths: Vec<String>,
+    pub csv_options: CsvOptions,
+    pub seed: u64,
+    pub watch: bool,
+    pub stdin: bool,
+    pub listen: Option<String>,
+    pub listen_udp: Option<String>,
+    pub background: Option<Color>, // None disables the contrast guard
+    pub color: bool,
+    pub ansi_256: bool, // Print colors as xterm palette indices
+    pub fov: f32,       // Radians
+    pub mouse: MouseSettings,
+    pub bounds_percentile: Option<f32>, // Outliers left out when framing the view and axes
+    pub backend: String,
+    pub window: bool,          // View in a native window instead of the terminal
+    pub sequence: Option<f32>, // Frames per second to play the files back at, one per frame
+    pub coordinate_system: CoordinateSystem,
+    pub key_bindings: KeyBindings,
+}
+
 pub enum ParseResult {
     ShowUsage,
     ShowDetailedHelp,
-    LoadFiles(Vec<String>),
+    Demo,
+    Serve(ServeOptions),
+    Summary(SummaryOptions),
+    Render(RenderOptions),
+    Animate(AnimateOptions),
+    Ascii(AsciiOptions),
+    Plot(PlotOptions),
+    LoadFiles(RunOptions),
 }
 
 pub fn parse_arguments() -> ParseResult {
     // Handle no arguments case
-    let args: Vec<String> = std::env::args().collect();
+    let args: Vec<String> = env::args().collect();
     if args.len() == 1 {
         return ParseResult::ShowUsage;
     }
@@ -36,7 +657,114 @@ pub fn parse_arguments() -> ParseResult {
         return ParseResult::ShowDetailedHelp;
     }
 
+    if args.len() == 2 && args[1] == "demo" {
+        return ParseResult::Demo;
+    }
+
+    if args[1] == "serve" {
+        // Parse the rest as if "altostratus serve" were the program name
+        let serve_args = ServeArgs::parse_from(&args[1..]);
+        return match input_options_from_args(&serve_args.input) {
+            Ok((csv_options, coordinate_system)) => ParseResult::Serve(ServeOptions {
+                file_paths: serve_args.files,
+                port: serve_args.port,
+                bind: serve_args.bind,
+                progressive: serve_args.progressive,
+                csv_options,
+                coordinate_system,
+            }),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ParseResult::ShowUsage
+            }
+        };
+    }
+
+    if args[1] == "summary" {
+        let summary_args = SummaryArgs::parse_from(&args[1..]);
+        return match input_options_from_args(&summary_args.input) {
+            Ok((csv_options, coordinate_system)) => ParseResult::Summary(SummaryOptions {
+                file_paths: summary_args.files,
+                views: summary_args.views,
+                csv_options,
+                coordinate_system,
+            }),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ParseResult::ShowUsage
+            }
+        };
+    }
+
+    if args[1] == "render" {
+        let render_args = RenderArgs::parse_from(&args[1..]);
+        return match render_options_from_args(render_args) {
+            Ok(options) => ParseResult::Render(options),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ParseResult::ShowUsage
+            }
+        };
+    }
+
+    if args[1] == "animate" {
+        let animate_args = AnimateArgs::parse_from(&args[1..]);
+        return match animate_options_from_args(animate_args) {
+            Ok(options) => ParseResult::Animate(options),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ParseResult::ShowUsage
+            }
+        };
+    }
+
+    if args[1] == "ascii" {
+        let ascii_args = AsciiArgs::parse_from(&args[1..]);
+        return match ascii_options_from_args(ascii_args) {
+            Ok(options) => ParseResult::Ascii(options),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ParseResult::ShowUsage
+            }
+        };
+    }
+
+    if args[1] == "plot" {
+        let plot_args = PlotArgs::parse_from(&args[1..]);
+        return match plot_options_from_args(plot_args) {
+            Ok(options) => ParseResult::Plot(options),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ParseResult::ShowUsage
+            }
+        };
+    }
+
     let args = Args::parse();
+    let config = match config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            return ParseResult::ShowUsage;
+        }
+    };
+    let input = input_options_from_args(&args.input);
+    let background_name = args
+        .background
+        .as_deref()
+        .or(config.background.as_deref())
+        .unwrap_or("black");
+    let background = match Color::from_string(background_name) {
+        Some(color) => Ok((!args.no_contrast_guard).then_some(color)),
+        None => Err(format!("invalid background color: {}", background_name)),
+    };
+    let color_mode = if args.no_color {
+        ColorMode::None
+    } else if args.ansi_256 {
+        ColorMode::Ansi256
+    } else {
+        config.color.unwrap_or(ColorMode::TrueColor)
+    };
 
     // Collect all files from both positional and flag arguments
     let mut all_files = Vec::new();
@@ -47,20 +775,378 @@ pub fn parse_arguments() -> ParseResult {
 
     all_files.extend(args.files);
 
-    if all_files.is_empty() {
-        ParseResult::ShowUsage
-    } else {
-        ParseResult::LoadFiles(all_files)
+    let has_stream = args.stdin || args.listen.is_some() || args.listen_udp.is_some();
+    if all_files.is_empty() && !has_stream {
+        return ParseResult::ShowUsage;
+    }
+
+    let mut key_bindings = KeyBindings::new();
+    let bound: Result<(), String> = config
+        .key_bindings
+        .iter()
+        .chain(&args.bind)
+        .try_for_each(|binding| key_bindings.bind(binding));
+
+    let speed = |flag: &str, value: Option<f32>, default: Option<f32>| match value {
+        Some(value) if !(value.is_finite() && value > 0.0) => Err(format!(
+            "invalid --{}: {} (expected a positive number)",
+            flag, value
+        )),
+        Some(value) => Ok(value),
+        None => Ok(default.unwrap_or(1.0)),
+    };
+    let mouse =
+        speed("mouse-speed", args.mouse_speed, config.mouse_sensitivity).and_then(|speed_factor| {
+            Ok(MouseSettings {
+                speed: speed_factor,
+                scroll_speed: speed("scroll-speed", args.scroll_speed, config.scroll_speed)?,
+                invert_y: args.invert_y || config.invert_y.unwrap_or(false),
+                arcball: args.arcball || config.arcball.unwrap_or(false),
+                friction: match args.inertia.or(config.inertia) {
+                    Some(friction) if !(friction.is_finite() && friction > 0.0) => {
+                        return Err(format!(
+                            "invalid --inertia: {} (expected a positive friction)",
+                            friction
+                        ));
+                    }
+                    friction => friction,
+                },
+            })
+        });
+
+    let bounds_percentile = match args.bounds_percentile {
+        Some(percent) if !(0.0..50.0).contains(&percent) => Err(format!(
+            "invalid --bounds-percentile: {} (expected at least 0 and below 50)",
+            percent
+        )),
+        percentile => Ok(percentile),
+    };
+
+    let sequence = match args.sequence {
+        Some(fps) if !(fps.is_finite() && fps > 0.0) => Err(format!(
+            "invalid --sequence: {} (expected positive frames per second)",
+            fps
+        )),
+        fps => Ok(fps),
+    };
+
+    let backend = match backend::from_string(&args.backend) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "invalid --backend: {} (expected braille, ascii, halfblock, sixel, kitty or iterm2)",
+            args.backend
+        )),
+    };
+
+    let options = input.and_then(|(csv_options, coordinate_system)| {
+        Ok((
+            csv_options,
+            background?,
+            coordinate_system,
+            bound?,
+            mouse?,
+            bounds_percentile?,
+            backend?,
+            sequence?,
+        ))
+    });
+    match options {
+        Ok((
+            csv_options,
+            background,
+            coordinate_system,
+            (),
+            mouse,
+            bounds_percentile,
+            (),
+            sequence,
+        )) => ParseResult::LoadFiles(RunOptions {
+            file_paths: all_files,
+            csv_options,
+            seed: args.seed,
+            watch: args.watch,
+            stdin: args.stdin,
+            listen: args.listen,
+            listen_udp: args.listen_udp,
+            background,
+            color: color_mode != ColorMode::None,
+            ansi_256: color_mode == ColorMode::Ansi256,
+            fov: config.fov.unwrap_or(VIEWPORT_FOV),
+            mouse,
+            bounds_percentile,
+            backend: args.backend,
+            window: args.window,
+            sequence,
+            coordinate_system,
+            key_bindings,
+        }),
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            ParseResult::ShowUsage
+        }
     }
 }
 
+// How to read point files, from the CSV flags and --up-axis
+fn input_options_from_args(args: &InputArgs) -> Result<(CsvOptions, CoordinateSystem), String> {
+    // Columns are 1-based on the command line
+    let to_index = |column: usize| {
+        column
+            .checked_sub(1)
+            .ok_or_else(|| "CSV columns are numbered from 1".to_string())
+    };
+
+    let mut options = CsvOptions::new();
+    match args.columns[..] {
+        [] => {}
+        [x, y, z] => {
+            options.x_column = to_index(x)?;
+            options.y_column = to_index(y)?;
+            options.z_column = to_index(z)?;
+        }
+        _ => return Err("--columns expects exactly three columns: X,Y,Z".to_string()),
+    }
+    options.color_column = args.color_column.map(to_index).transpose()?;
+    options.scalar_column = args.scalar_column.map(to_index).transpose()?;
+    options.delimiter = args.delimiter;
+    options.header_rows = args.header_rows;
+
+    let coordinate_system = CoordinateSystem::from_string(&args.up_axis).ok_or_else(|| {
+        format!(
+            "invalid --up-axis: {} (expected z-up, y-up or left-handed)",
+            args.up_axis
+        )
+    })?;
+    Ok((options, coordinate_system))
+}
+
+fn render_options_from_args(args: RenderArgs) -> Result<RenderOptions, String> {
+    let to_point = |values: &[f32], flag: &str| match *values {
+        [] => Ok(None),
+        [x, y, z] => Ok(Some([x, y, z])),
+        _ => Err(format!(
+            "--{} expects exactly three coordinates: X,Y,Z",
+            flag
+        )),
+    };
+    if args.width == 0 || args.height == 0 {
+        return Err("--width and --height must be at least 1".to_string());
+    }
+    if args.chunk_points == Some(0) {
+        return Err("--chunk-points must be at least 1".to_string());
+    }
+    if let Some(protocol) = &args.inline
+        && inline::from_string(protocol).is_none()
+    {
+        return Err(format!(
+            "invalid --inline: {} (expected kitty, iterm2 or sixel)",
+            protocol
+        ));
+    }
+    let (csv_options, coordinate_system) = input_options_from_args(&args.input)?;
+    let background = Color::from_string(&args.background)
+        .ok_or_else(|| format!("invalid background color: {}", args.background))?;
+    let (matrix, extrinsics) = (&args.pose, &args.extrinsics);
+    let pose = match (matrix.len(), extrinsics.len()) {
+        (0, 0) => None,
+        (16, _) => Some(CameraPose::from_matrix(array::from_fn(|i| {
+            array::from_fn(|j| matrix[i * 4 + j])
+        }))),
+        (_, 12) => Some(CameraPose::from_extrinsics(
+            array::from_fn(|i| array::from_fn(|j| extrinsics[i * 3 + j])),
+            [extrinsics[9], extrinsics[10], extrinsics[11]],
+        )),
+        (0, _) => {
+            return Err(
+                "--extrinsics expects 12 numbers: a 3x3 rotation, row by row, then X,Y,Z"
+                    .to_string(),
+            );
+        }
+        _ => return Err("--pose expects 16 numbers: a 4x4 matrix, row by row".to_string()),
+    };
+    if let Some(pose) = pose
+        && (pose.forward == [0.0; 3] || pose.forward.iter().any(|v| !v.is_finite()))
+    {
+        return Err("the camera pose has no view direction".to_string());
+    }
+    let photo = match (args.photo, &args.intrinsics[..]) {
+        (None, _) => None,
+        (Some(_), _) if pose.is_none() => {
+            return Err("--photo needs the camera's --pose or --extrinsics".to_string());
+        }
+        (Some(photo), &[fx, fy, cx, cy]) => Some((photo, Intrinsics { fx, fy, cx, cy })),
+        (Some(_), _) => {
+            return Err("--intrinsics expects 4 numbers: FX,FY,CX,CY".to_string());
+        }
+    };
+    let format = match &args.format {
+        Some(format) => ImageFormat::from_string(format)
+            .ok_or_else(|| format!("invalid --format: {} (expected png, bmp or jpeg)", format))?,
+        None => args
+            .output
+            .as_deref()
+            .and_then(ImageFormat::from_path)
+            .unwrap_or(ImageFormat::Png),
+    };
+
+    Ok(RenderOptions {
+        camera: to_point(&args.camera, "camera")?,
+        target: to_point(&args.target, "target")?,
+        up: to_point(&args.up, "up")?,
+        roll: args.roll.unwrap_or(0.0).to_radians(),
+        pose,
+        file_path: args.file,
+        output: args.output,
+        inline: args.inline,
+        format,
+        image: ImageOptions {
+            width: args.width,
+            height: args.height,
+            background,
+            point_size: args.point_size,
+            axes: !args.no_axes,
+            supersampling: args.supersample,
+        },
+        gpu: args.gpu,
+        chunk_points: args.chunk_points,
+        photo,
+        csv_options,
+        coordinate_system,
+    })
+}
+
+fn animate_options_from_args(args: AnimateArgs) -> Result<AnimateOptions, String> {
+    if args.width == 0 || args.height == 0 {
+        return Err("--width and --height must be at least 1".to_string());
+    }
+    if args.frames == 0 {
+        return Err("--frames must be at least 1".to_string());
+    }
+    if args.fps.is_nan() || args.fps <= 0.0 {
+        return Err("--fps must be a positive number".to_string());
+    }
+    if let Some(fov) = args.dolly_zoom
+        && !(fov > 0.0 && fov < 180.0)
+    {
+        return Err("--dolly-zoom must be between 0 and 180 degrees".to_string());
+    }
+    if !["median-cut", "octree", "xterm"].contains(&args.quantizer.as_str()) {
+        return Err(format!(
+            "invalid --quantizer: {} (expected median-cut, octree or xterm)",
+            args.quantizer
+        ));
+    }
+    let (csv_options, coordinate_system) = input_options_from_args(&args.input)?;
+    let background = Color::from_string(&args.background)
+        .ok_or_else(|| format!("invalid background color: {}", args.background))?;
+
+    Ok(AnimateOptions {
+        file_path: args.file,
+        output: args.output,
+        orbit: args.orbit,
+        dolly_zoom: args.dolly_zoom.map(f32::to_radians),
+        frames: args.frames,
+        delay: (100.0 / args.fps).round().clamp(1.0, u16::MAX as f32) as u16,
+        image: ImageOptions {
+            width: args.width,
+            height: args.height,
+            background,
+            point_size: args.point_size,
+            axes: !args.no_axes,
+            supersampling: args.supersample,
+        },
+        quantizer: args.quantizer,
+        compact: args.compact,
+        gpu: args.gpu,
+        csv_options,
+        coordinate_system,
+    })
+}
+
+fn ascii_options_from_args(args: AsciiArgs) -> Result<AsciiOptions, String> {
+    if args.width == 0 || args.height == 0 {
+        return Err("--width and --height must be at least 1".to_string());
+    }
+    let charset = match &args.charset {
+        Some(name) => Charset::from_string(name).ok_or_else(|| {
+            format!(
+                "invalid --charset: {} (expected braille, blocks or ascii)",
+                name
+            )
+        })?,
+        None => config::load(None)?.charset.unwrap_or(Charset::Braille),
+    };
+    let (csv_options, coordinate_system) = input_options_from_args(&args.input)?;
+
+    Ok(AsciiOptions {
+        file_path: args.file,
+        columns: args.width,
+        rows: args.height,
+        charset,
+        color: args.color,
+        axes: !args.no_axes,
+        csv_options,
+        coordinate_system,
+    })
+}
+
+fn plot_options_from_args(args: PlotArgs) -> Result<PlotOptions, String> {
+    let colormap =
+        match &args.colormap {
+            Some(name) => Some(Colormap::from_string(name).ok_or_else(|| {
+                format!("invalid --colormap: {} (expected ramp or viridis)", name)
+            })?),
+            None => None,
+        };
+    let to_color = |name: &String| {
+        Color::from_string(name).ok_or_else(|| format!("invalid --gradient color: {}", name))
+    };
+    let gradient = match args.gradient.as_slice() {
+        [] => None,
+        [start, end] => Some((to_color(start)?, to_color(end)?)),
+        _ => return Err("--gradient expects exactly two colors: START,END".to_string()),
+    };
+    let (csv_options, coordinate_system) = input_options_from_args(&args.input)?;
+
+    Ok(PlotOptions {
+        file_path: args.file,
+        output: args.output,
+        surface: args.surface,
+        wireframe: args.wireframe,
+        trajectory: args.trajectory,
+        gradient,
+        bars: args.bars.then_some(match args.solid {
+            true => BarStyle::Solid,
+            false => BarStyle::Frame,
+        }),
+        colormap,
+        title: args.title,
+        columns: args.width,
+        rows: args.height,
+        csv_options,
+        coordinate_system,
+    })
+}
+
 pub fn print_usage() {
     println!("Usage: altostratus [FILE] | altostratus -f [FILES...]");
     println!("       altostratus --help | -h for detailed help");
+    println!("       altostratus demo to render a built-in scene once");
+    println!(
+        "       altostratus serve [--port PORT] [--bind ADDR] [--progressive] FILES... to serve frames over HTTP"
+    );
+    println!("       altostratus summary FILES... to write a thumbnail PNG of each file");
+    println!("       altostratus render FILE -o OUT.png to render a file without the viewer");
+    println!("       altostratus animate FILE -o OUT.gif to render a turntable animation");
+    println!("       altostratus ascii FILE to print one text frame to stdout");
+    println!("       altostratus plot FILE [-o OUT.png] to chart x y z points");
     println!();
     println!("Examples:");
     println!("  altostratus points.txt              # Load single file");
     println!("  altostratus -f file1.txt file2.txt  # Load multiple files");
+    println!("  altostratus data.csv --columns 2,3,4  # Load CSV with column mapping");
+    println!("  tail -f points.txt | altostratus --stdin  # Stream points from stdin");
+    println!("  altostratus --listen 0.0.0.0:9000  # Stream points over TCP");
 }
 
 pub fn print_detailed_help() {
@@ -70,28 +1156,225 @@ pub fn print_detailed_help() {
 \x1b[1mUsage\x1b[0m:
     \"altostratus <filepath.txt>\": Interactively view the provided point file.
     \"altostratus -f <file1.txt> <file2.txt> ...\": Load multiple point files.
+    \"altostratus --watch <filepath.txt>\": Reload the file whenever it changes on disk.
+    \"altostratus --stdin\": Keep reading point lines from stdin while viewing.
+    \"altostratus --listen <addr>\": Accept point lines from TCP clients while viewing.
+    \"altostratus --listen-udp <addr>\": Accept point lines in UDP datagrams while viewing.
+    \"altostratus --sequence [fps] -f <frame1.txt> -f <frame2.txt> ...\": Play the files back as
+        the frames of an animation, e.g. the steps of a simulation or a tracker's output, looping
+        at the given frames per second (default 10). Press [space] to pause or play, and [.] or
+        [,] to step a frame forward or back. Each frame shows its file's points as loaded, so
+        commands that change the points (/load, /overlay, /open, /clear, /remove, /hide, /show,
+        /unhide, /color, /filter, /brightness, /segment, /planes, /ground, /voxel, /sample, /flip,
+        /swap, /crop, /cluster and /smooth) are refused while a sequence plays.
+    \"altostratus --window <filepath.txt>\": View the file in a native window with the same
+        keys and mouse controls (builds with the window feature; drawn on the GPU in builds
+        that also have the gpu feature).
     \"altostratus --help\", \"altostratus -h\": Show this help message.
+    \"altostratus demo\": Render a built-in scene once to check terminal support.
+    \"altostratus serve [--port 8080] [--bind 127.0.0.1] [--progressive] <files...>\": Serve
+        rendered frames over HTTP, on this machine only unless --bind gives another address
+        (0.0.0.0 for every interface). The camera comes from the query string:
+        ?yaw=45&pitch=20&roll=0&distance=10&cols=120&rows=40&format=html (angles in degrees;
+        format is ansi, html, png, or rgba for raw RGBA bytes to draw on a web canvas; png and
+        rgba have one pixel per braille dot). --progressive draws rgba and png frames of big
+        clouds in passes: a random sample of the points comes back first, and asking for the
+        same view again adds more until all are in.
+    \"altostratus summary [--views N] <files...>\": Write <file>.png next to each file: an
+        isometric view colored by height, with axes, the point count, the bounding box size and
+        a legend. --views also writes <file>-view-1.png to -N.png, looking from directions
+        spread evenly all around the points.
+    \"altostratus render <file> -o <out.png> [--width 1920] [--height 1080] [--camera x,y,z]
+        [--target x,y,z] [--up x,y,z | --roll degrees] [--pose m00,...,m33 | --extrinsics
+        r00,...,r22,tx,ty,tz] [--photo <photo.png> --intrinsics fx,fy,cx,cy] [--up-axis z-up]
+        [--format png|bmp|jpeg] [--chunk-points N]\":
+        Render the file to a PNG, one pixel per braille dot, without the interactive viewer.
+        PNGs record the camera, the point count and the altostratus version as text metadata;
+        --format bmp (or an output ending in .bmp) writes an uncompressed BMP instead, and
+        --format jpeg (or .jpg, .jpeg) a smaller JPEG with the same text as comments.
+        Coordinates are in the file's own axes; by default the camera frames every point from
+        the isometric direction, with the up axis pointing up. --up turns the image so another
+        direction points up, as for a camera pose from elsewhere, and --roll turns it clockwise.
+        --pose places the camera with a 4x4 camera-to-world matrix as OpenGL and /pose write it
+        (looking down -z, y up), and --extrinsics with the world-to-camera rotation and
+        translation of OpenCV and COLMAP (looking down +z, y down). --photo draws the points
+        over a PNG taken from that pose, at its size, through the lens --intrinsics gives as
+        OpenCV and COLMAP do (focal lengths and principal point in pixels; pixels must be
+        square), to check how well a scan and a camera line up. --inline kitty|iterm2|sixel
+        instead prints the image to terminals that show pixels, at --width x --height pixels
+        (sixel images are reduced to 256 colors). --background sets the image's color,
+        --point-size draws each point as a square that wide in data units and --no-axes leaves
+        out the axes. --supersample 2|4 draws at that many times the size across and down and
+        averages each block of pixels into one, for smooth edges (single-pixel points come out
+        fainter). --gpu draws the points on the GPU instead, in builds with the gpu feature.
+        --chunk-points N reads a point file N points at a time, once to find its bounds and
+        again to draw it, so files bigger than memory can be rendered (CSV and other formats
+        read by a loader are always read whole).
+    \"altostratus animate <file> -o <out.gif> [--orbit [360]] [--frames 120] [--fps 30]
+        [--width 640] [--height 480] [--quantizer median-cut] [--compact]\": Write a looping GIF
+        of the camera turning around the points by the given degrees, starting from the isometric
+        view. --dolly-zoom DEGREES also widens or narrows the field of view to that angle over the
+        frames while the camera moves to keep the middle of the points the same size (add
+        --orbit 0 for the zoom alone). --compact keeps 8 bytes per point while rendering, for clouds too large for memory.
+        --background, --point-size, --no-axes and --supersample work as for render. --gpu uploads the points
+        once and draws every frame on the GPU (gpu feature builds).
+    \"altostratus ascii <file> [--width 120] [--height 40] [--charset braille|blocks|ascii]
+        [--color] [--no-axes]\": Print the isometric view as text, for scripts, READMEs and CI
+        logs. Plain text unless --color is given.
+    \"altostratus plot <file> [-o <out.png>] [--surface [--wireframe]] [--trajectory
+        [--gradient blue,red]] [--bars [--solid]] [--colormap ramp|viridis] [--title TEXT] [--width 80]
+        [--height 24]\": Chart the file's points with the up axis as height, colored by
+        height and framed from the isometric direction with axes, without choosing a camera.
+        Prints colored braille text unless -o writes a PNG. --surface reads the file as a grid of heights
+        instead, one row of numbers per line, at x = column and y = row; --wireframe joins
+        neighboring heights with lines. --trajectory joins the points with lines in file order,
+        colored from the first --gradient color to the second along the way. --bars stands a
+        bar at each point's x and y up to its z, as a 3D histogram; --solid fills them in.
     \"altostratus\": Show usage examples.
 
 \x1b[1mFile Format\x1b[0m:
     Mixed format supporting points and lines:
-    p x y z                    - Point at coordinates (x, y, z)
+    p x y z [color]            - Point at coordinates (x, y, z)
+    p x y z r g b              - Point with a color given as 0-255 channels
     l x1 y1 z1 x2 y2 z2        - Line from (x1, y1, z1) to (x2, y2, z2)
     x y z                      - Legacy point format (backwards compatible)
     
     Lines are rendered as dense point sequences for smooth visualization.
+    Colors are names (red, green, blue, ...) or 24-bit hex values like #ff8000.
+    Colors too close to the background are lightened (or darkened) to stay visible:
+    --background COLOR         - Terminal background color (default black)
+    --no-contrast-guard        - Draw colors exactly as given
+    --no-color                 - Draw everything in the terminal's default color
+    --ansi-256                 - Print the nearest of the 256 palette colors instead of 24-bit color
+    --backend NAME             - How the viewer draws: braille (default), ascii, halfblock (two
+                                 colored pixels per cell), or real pixels with sixel, kitty or
+                                 iterm2 in terminals supporting those image protocols
     Comments (lines starting with #) and empty lines are ignored.
 
+    Coordinates are read as right-handed z-up by default. Use --up-axis y-up for
+    right-handed y-up data or --up-axis left-handed for left-handed y-up data.
+    serve, summary, render, animate, ascii and plot read files the same way and take
+    --up-axis and the CSV options below too.
+
+    Files ending in .csv or .tsv are read as delimited columns:
+    --columns X,Y,Z            - Columns holding coordinates (default 1,2,3)
+    --color-column N           - Column holding color names
+    --scalar-column N          - Column mapped onto a blue-to-red color ramp
+    --delimiter CHAR           - Field delimiter (default ',' or tab)
+    --header-rows N            - Rows to skip (default: a non-numeric first row)
+
 \x1b[1mControls\x1b[0m:
     Scroll down to zoom out, scroll up to zoom in.
     Click and drag the mouse to rotate around the data.
     Click and drag the mouse while holding [ctrl] to pan.
+    Adjust these with --mouse-speed FACTOR, --scroll-speed FACTOR and --invert-y, or /mouse.
+    With --arcball (or /mouse arcball on), dragging rolls a trackball instead: the point
+        grabbed follows the cursor, and the view turns over the top and bottom freely.
+    With --inertia [FRICTION] (or /mouse inertia), the view glides on after a flick of the
+        mouse, scrolling or keys, slowing to a stop; higher friction stops it sooner.
     Press [/] to enter command mode and load new datasets.
+    Press [v] to cycle split-screen layouts (single, with top view, with top/front/side).
+    Press [l] to link or unlink the split-screen views from the main view.
+    Press [w/a/s/d] or the arrow keys to orbit, [+]/[-] to zoom and [shift+arrows] to pan.
+    Press [p] to pick points: move the crosshair with the mouse or arrow keys to show the
+        coordinates, index and file of the point under it.
+    Press [r] to reset the view, or [7], [1], [3], [0] for top, front, side and isometric views.
+    Press [x] to show or hide the axes.
+    Press [space] to pause or play a --sequence, and [.] or [,] to step a frame forward or back.
+    Rebind these with --bind ACTION=KEYS (repeatable), e.g. --bind orbit-left=h,left.
+        Actions: orbit-left, orbit-right, orbit-up, orbit-down, zoom-in, zoom-out,
+        pan-left, pan-right, pan-up, pan-down, reset-view, view-top, view-front,
+        view-side, view-isometric, toggle-axes, play-pause, step-forward, step-back.
+        Keys are characters or names such as left, pageup, space, comma, with optional
+        shift+/ctrl+/alt+ prefixes.
     Press [Ctrl+C] to exit.
 
+\x1b[1mConfig File\x1b[0m:
+    Defaults are read from ~/.config/altostratus/config.toml (or $XDG_CONFIG_HOME), or from
+    the file given with --config PATH. Command line flags take precedence. For example:
+        fov = 97                   # Field of view in degrees
+        mouse_sensitivity = 1.5    # Multiplier on how far dragging turns the camera
+        scroll_speed = 0.5         # Multiplier on how far a scroll step zooms
+        invert_y = true            # Drag up to tilt the camera up
+        arcball = true             # Drag a trackball to turn the view
+        inertia = 4                # Glide on after input, with this friction
+        charset = \"blocks\"         # Default for altostratus ascii
+        color = \"256\"              # truecolor, 256 or none
+        background = \"white\"
+        [keys]
+        orbit-left = \"h,left\"      # Same as --bind orbit-left=h,left
+
 \x1b[1mCommands\x1b[0m:
     /load <filepath>: Load additional point cloud file
+    /overlay <file.obj> [count] [color]: Add points spread evenly over the surface of an OBJ
+        mesh (default 10000, cyan), e.g. to compare a scan against its CAD model
     /clear: Remove all loaded points from the visualization
+    /clear lines: Remove the lines drawn over the points (plane outlines, contours, splines)
+    /list: Show the loaded datasets (files) with their point counts
+    /remove <name>: Remove a dataset's points (name is the file name, or the path if ambiguous)
+    /hide <name>, /show <name>: Hide or show again the points of a dataset
+    /color <name> <r> <g> <b>: Draw a dataset in one color (0-255 channels, or a color name)
+    /segment [angle] [distance]: Color smooth connected regions (angle up to 180, default 15 degrees)
+    /planes [count] [threshold] [outline]: Detect planes with RANSAC and color their inliers;
+        outline also draws each plane's extent over the points (see /clear lines)
+    /cluster dbscan <eps> [min_points]: Color density-connected clusters (noise stays uncolored)
+    /cluster kmeans <k>: Color the points by k-means cluster (uses /seed)
+    /ground ground|nonground|off [cell] [height]: Show only ground or non-ground points
+    /filter scalar|height <min> <max>: Show only points whose CSV scalar column (see
+        --scalar-column) or height lies in the range
+    /brightness scalar|height [min max]: Darken points towards the bottom of the range while
+        keeping their colors' hues (the range defaults to the attribute's extent)
+    /unhide: Show the points hidden by /ground or /filter again
+    /contours <interval>: Draw iso-elevation lines every <interval> units of height (at most
+        1000 of them), over the points
+    /spline [smoothing]: Draw a smooth curve through the points in file order, averaging
+        over a window of about <smoothing> points first (default 2, 0 passes through them)
+    /voxel <size>: Keep one averaged point per voxel of the given size and dataset; hidden
+        points are averaged apart from the visible ones and stay hidden
+    /sample random <count>: Keep a random subset of the points (uses /seed)
+    /sample every <step>: Keep every <step>-th point
+    /flip x|y|z: Mirror the points along an axis (axes as in the file, see --up-axis)
+    /swap <axis> <axis>: Exchange two coordinates of every point, e.g. /swap y z
+    /crop box <x1> <y1> <z1> <x2> <y2> <z2>: Keep only the points inside a box
+    /crop sphere <x> <y> <z> <radius>: Keep only the points inside a sphere
+    /axes on|off: Show or hide the axes
+    /axes length <n>|auto: Draw the axes <n> units long, or long enough to reach every point
+    /bounds percentile [p] | all: Frame the view and fit the axes to the p-th to (100-p)-th
+        percentile of each axis (default 1), so far-away outliers don't shrink the rest to a
+        dot, or to every point again (same as --bounds-percentile p)
+    /grid on|off: Draw a grid on the horizontal plane, spaced like the axis ticks
+    /adaptive on|off: While the view moves, draw fewer points if a frame would take too long
+    /mouse [speed <factor> | scroll <factor> | invert on|off | arcball on|off |
+        inertia [friction] | inertia off]: Change how far dragging and scrolling move the
+        camera, whether dragging up tilts it up, whether dragging rolls a trackball, or whether
+        the view glides on after input (default friction 4); shows the settings
+    /fps [n]: Play a --sequence at n frames per second; shows the frame count and rate
+    /spin [deg/s]|off: Keep turning the view about the vertical (default 30 deg/s; negative turns the other way)
+    /edges on [ratio] | off: Darken silhouettes where depth jumps by more than ratio (default 0.1)
+    /smooth [radius]: Reduce noise by projecting points onto surfaces fitted to their neighbors
+    /shade [x y z] | off: Estimate normals and light the points from the given direction
+    /style dot | splat [radius] | square [size] | gaussian [sigma]: Draw points as dots,
+        disks facing along their normals, squares, or soft additive blobs for density
+        renders (sizes default to the typical point spacing)
+    /density linear|log|off: Color pixels by how many points overlap them (heat map)
+    /depthmap on|off: Shade points by distance from the camera (near is bright)
+    /exposure <stops> [clip|reinhard|filmic] | off: Brighten (or darken, for negative stops) the
+        summed light of /aggregate sum and /style gaussian, and fit it into range with the given
+        curve (default reinhard) rather than cutting it off at white
+    /aggregate nearest|mean|max|sum: Combine the colors of points sharing a pixel (default nearest)
+    /view reset|top|front|side|iso: Reset the camera or look from a standard direction
+    /seed <n>: Set the seed used by stochastic commands (default 0, or --seed)
+    /export <filepath.html>: Write the current view to an HTML page with colors
+    /heat on [half-life]: Show where points arrived lately, fading with the half-life in seconds (default 5)
+    /heat off: Show the points again
+    /record start <file.gif> [quantizer]: Capture what the main view shows (up to 20 frames per second).
+        The GIF's 256 colors are picked by median-cut (default, most faithful), octree (faster)
+        or xterm (fixed palette, fastest)
+    /record stop: Stop capturing and write the frames as an animated GIF
+    /pose: Show the camera's pose as the 16 numbers of a camera-to-world matrix, for render --pose
+    /save <filepath>: Save the loaded points, their datasets and hidden flags, and the camera
+        to a session file
+    /open <filepath>: Restore the points, datasets and camera from a session file
 ";
 
     print!("{}", HELP_MSG);
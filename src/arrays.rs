@@ -0,0 +1,70 @@
+use crate::graphics::{Color, Point3D, PointCloud};
+use crate::rng::Rng;
+
+// A cloud's visible points as separate contiguous arrays, one per attribute
+// drawing reads, instead of one `Point3D` after another. Projecting only has
+// to stream through the positions, 12 bytes a point instead of a whole
+// `Point3D`, so more of them fit in cache. Read-only, like `CompactCloud`:
+// build a new one after the cloud changes.
+pub struct PointArrays {
+    pub positions: Vec<[f32; 3]>,
+    pub colors: Vec<Color>,
+    pub normals: Vec<Option<[f32; 3]>>,
+}
+
+impl PointArrays {
+    pub fn from_cloud(point_cloud: &PointCloud) -> PointArrays {
+        let points = point_cloud.points();
+        PointArrays {
+            positions: point_cloud
+                .visible()
+                .map(|index| [points[index].x, points[index].y, points[index].z])
+                .collect(),
+            colors: point_cloud
+                .visible()
+                .map(|index| points[index].color)
+                .collect(),
+            normals: point_cloud
+                .visible()
+                .map(|index| point_cloud.normal(index))
+                .collect(),
+        }
+    }
+
+    // Points with no attributes but their colors, such as a chunk of a file
+    pub fn from_points(points: &[Point3D]) -> PointArrays {
+        PointArrays {
+            positions: points
+                .iter()
+                .map(|point| [point.x, point.y, point.z])
+                .collect(),
+            colors: points.iter().map(|point| point.color).collect(),
+            normals: vec![None; points.len()],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    // Put the points in a random order, so any run of them is spread over
+    // the whole cloud
+    pub fn shuffle(&mut self, rng: &mut Rng) {
+        for i in (1..self.len()).rev() {
+            let j = rng.below(i + 1);
+            self.positions.swap(i, j);
+            self.colors.swap(i, j);
+            self.normals.swap(i, j);
+        }
+    }
+
+    // The point at `index` put back together, without its attributes
+    pub fn point(&self, index: usize) -> Point3D {
+        let [x, y, z] = self.positions[index];
+        Point3D::new_with_color(x, y, z, self.colors[index])
+    }
+}
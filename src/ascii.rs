@@ -0,0 +1,202 @@
+use std::*;
+
+use fmt::Write;
+
+use crate::graphics::{Color, PointCloud, PointStyle, Screen, Viewport};
+use crate::render;
+use crate::view::ViewState;
+
+const DEFAULT_COLUMNS: u16 = 120;
+const DEFAULT_ROWS: u16 = 40;
+const CELL_WIDTH: usize = 2; // Screen dots per character, as in the viewer's braille
+const CELL_HEIGHT: usize = 4;
+const ASCII_RAMP: &[u8] = b" .:-=+*#%"; // By number of lit dots, 0 to 8
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+const BRAILLE_BITS: [[u32; CELL_WIDTH]; CELL_HEIGHT] =
+    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// Characters a frame is printed with outside the viewer
+#[derive(Copy, Clone, PartialEq)]
+pub enum Charset {
+    Braille, // One character per 2x4 dots, as in the viewer
+    Blocks,  // Quadrant blocks; each one covers a 1x2 pair of dots
+    Ascii,   // Plain ASCII, darker characters for more lit dots
+}
+
+impl Charset {
+    pub fn from_string(s: &str) -> Option<Charset> {
+        match s {
+            "braille" => Some(Charset::Braille),
+            "blocks" => Some(Charset::Blocks),
+            "ascii" => Some(Charset::Ascii),
+            _ => None,
+        }
+    }
+}
+
+// Frames of a cloud as text, drawn at braille resolution whatever the
+// charset so the proportions match the viewer's
+pub struct AsciiRenderer<'a> {
+    point_cloud: &'a PointCloud,
+    columns: u16,
+    rows: u16,
+    charset: Charset,
+    color: bool,
+    axes: bool,
+}
+
+impl<'a> AsciiRenderer<'a> {
+    // Defaults: 120x40 braille characters without color, with the axes
+    pub fn builder(point_cloud: &'a PointCloud) -> AsciiRendererBuilder<'a> {
+        AsciiRendererBuilder {
+            renderer: AsciiRenderer {
+                point_cloud,
+                columns: DEFAULT_COLUMNS,
+                rows: DEFAULT_ROWS,
+                charset: Charset::Braille,
+                color: false,
+                axes: true,
+            },
+        }
+    }
+
+    // The dots the text is drawn from
+    pub fn viewport(&self) -> Viewport {
+        Viewport::new(
+            self.columns.saturating_mul(CELL_WIDTH as u16),
+            self.rows.saturating_mul(CELL_HEIGHT as u16),
+        )
+    }
+
+    pub fn render(&self, view: &ViewState) -> String {
+        let screen = render::draw_screen(
+            self.point_cloud,
+            view,
+            self.viewport(),
+            PointStyle::Dot,
+            self.axes,
+        );
+        screen_to_text(&screen, self.charset, self.color)
+    }
+}
+
+// Settings for an `AsciiRenderer`, checked all at once by `build`
+pub struct AsciiRendererBuilder<'a> {
+    renderer: AsciiRenderer<'a>,
+}
+
+impl<'a> AsciiRendererBuilder<'a> {
+    // Width in characters and height in lines
+    pub fn size(mut self, columns: u16, rows: u16) -> Self {
+        self.renderer.columns = columns;
+        self.renderer.rows = rows;
+        self
+    }
+
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.renderer.charset = charset;
+        self
+    }
+
+    // Whether characters are colored with 24-bit escape codes
+    pub fn color(mut self, color: bool) -> Self {
+        self.renderer.color = color;
+        self
+    }
+
+    // Whether the cloud's axes are drawn
+    pub fn axes(mut self, axes: bool) -> Self {
+        self.renderer.axes = axes;
+        self
+    }
+
+    pub fn build(self) -> Result<AsciiRenderer<'a>, String> {
+        if self.renderer.columns == 0 || self.renderer.rows == 0 {
+            return Err("width and height must be at least one character".to_string());
+        }
+        Ok(self.renderer)
+    }
+}
+
+// The screen as lines of text, one character per 2x4 dots. With `color`,
+// each character takes the color of its first lit dot as a 24-bit escape;
+// without, the text is plain. Trailing blanks are left off every line.
+pub fn screen_to_text(screen: &Screen, charset: Charset, color: bool) -> String {
+    let columns = screen.viewport.width as usize / CELL_WIDTH;
+    let rows = screen.viewport.height as usize / CELL_HEIGHT;
+    let mut text = String::new();
+
+    for row in 0..rows {
+        let mut line = String::new();
+        let mut current_color = None;
+        let mut blank_tail = 0; // Bytes of blanks at the end of `line`
+        for column in 0..columns {
+            let mut lit = [[false; CELL_WIDTH]; CELL_HEIGHT];
+            let mut cell_color = None;
+            for (dy, lit_row) in lit.iter_mut().enumerate() {
+                for (dx, dot) in lit_row.iter_mut().enumerate() {
+                    let found = screen.dot(column * CELL_WIDTH + dx, row * CELL_HEIGHT + dy);
+                    *dot = found.is_some();
+                    cell_color = cell_color.or(found);
+                }
+            }
+
+            let character = cell_character(&lit, charset);
+            if character == ' ' {
+                line.push(' ');
+                blank_tail += 1;
+                continue;
+            }
+            blank_tail = 0;
+            if color && cell_color != current_color {
+                let (r, g, b) = cell_color.unwrap_or(Color::Default).to_rgb();
+                let _ = write!(line, "\x1b[38;2;{};{};{}m", r, g, b);
+                current_color = cell_color;
+            }
+            line.push(character);
+        }
+
+        line.truncate(line.len() - blank_tail);
+        if current_color.is_some() {
+            line.push_str("\x1b[39m");
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+fn cell_character(lit: &[[bool; CELL_WIDTH]; CELL_HEIGHT], charset: Charset) -> char {
+    match charset {
+        Charset::Braille => {
+            let mut bits = 0;
+            for (dy, lit_row) in lit.iter().enumerate() {
+                for (dx, &dot) in lit_row.iter().enumerate() {
+                    if dot {
+                        bits |= BRAILLE_BITS[dy][dx];
+                    }
+                }
+            }
+            match bits {
+                0 => ' ',
+                _ => char::from_u32(0x2800 + bits).unwrap_or(' '),
+            }
+        }
+        Charset::Blocks => {
+            // Top and bottom halves of the cell, each a quadrant row
+            let mut index = 0;
+            for (quadrant, (dx, dy)) in [(0, 0), (1, 0), (0, 2), (1, 2)].into_iter().enumerate() {
+                if lit[dy][dx] || lit[dy + 1][dx] {
+                    index |= 1 << quadrant;
+                }
+            }
+            QUADRANTS[index]
+        }
+        Charset::Ascii => {
+            let count = lit.iter().flatten().filter(|&&dot| dot).count();
+            ASCII_RAMP[count.min(ASCII_RAMP.len() - 1)] as char
+        }
+    }
+}
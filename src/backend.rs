@@ -0,0 +1,149 @@
+use std::*;
+
+use crossterm::{execute, style, terminal};
+
+use crate::ascii::{self, Charset};
+use crate::graphics::{self, BRAILLE_CELL, Color, Screen};
+use crate::inline::{self, Renderer};
+
+const FALLBACK_CELL_PIXELS: (u16, u16) = (8, 16); // If the terminal doesn't report pixel sizes
+
+// Puts the viewer's frames on the terminal. The screen is drawn with
+// `cell_size` pixels per terminal cell, and `present` prints it from the top
+// left, leaving the bottom row for the status line.
+pub trait Backend {
+    fn cell_size(&self) -> (u16, u16);
+    fn present(&mut self, screen: &mut Screen);
+}
+
+pub fn from_string(s: &str) -> Option<Box<dyn Backend>> {
+    match s {
+        "braille" => Some(Box::new(BrailleBackend)),
+        "ascii" => Some(Box::new(AsciiBackend)),
+        "halfblock" => Some(Box::new(HalfBlockBackend)),
+        "sixel" | "kitty" | "iterm2" => Some(Box::new(ImageBackend::new(inline::from_string(s)?))),
+        _ => None,
+    }
+}
+
+// One braille character per 2x4 pixels, reprinting only the cells that changed
+pub struct BrailleBackend;
+
+impl Backend for BrailleBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        BRAILLE_CELL
+    }
+
+    fn present(&mut self, screen: &mut Screen) {
+        screen.render();
+    }
+}
+
+// Plain ASCII for terminals and fonts without braille, as `altostratus ascii
+// --charset ascii` prints it
+pub struct AsciiBackend;
+
+impl Backend for AsciiBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        BRAILLE_CELL
+    }
+
+    fn present(&mut self, screen: &mut Screen) {
+        let text = ascii::screen_to_text(screen, Charset::Ascii, screen.color);
+        // Lines leave off trailing blanks, so the rest of each row is erased
+        let mut output = String::from("\x1b[H");
+        for line in text.lines() {
+            output.push_str(line);
+            output.push_str("\x1b[K\r\n");
+        }
+        print(&output);
+    }
+}
+
+// Two pixels per cell, stacked: the upper half block in the top pixel's color
+// over the bottom pixel's color, so neighboring pixels keep their own colors
+pub struct HalfBlockBackend;
+
+impl Backend for HalfBlockBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        (1, 2)
+    }
+
+    fn present(&mut self, screen: &mut Screen) {
+        let (width, height) = (
+            screen.viewport.width as usize,
+            screen.viewport.height as usize,
+        );
+        let dot = |x, y| {
+            let color = screen.dot(x, y)?;
+            Some(if screen.color { color } else { Color::Default })
+        };
+
+        let mut output = String::from("\x1b[H");
+        for row in 0..height / 2 {
+            let (mut foreground, mut background) = (None, None); // As last printed
+            for x in 0..width {
+                let (character, top, bottom) = match (dot(x, row * 2), dot(x, row * 2 + 1)) {
+                    (None, None) => (' ', None, None),
+                    (Some(top), None) => ('▀', Some(top), None),
+                    (None, Some(bottom)) => ('▄', Some(bottom), None),
+                    (Some(top), Some(bottom)) if top == bottom => ('█', Some(top), None),
+                    (Some(top), Some(bottom)) => ('▀', Some(top), Some(bottom)),
+                };
+                if let Some(color) = top
+                    && top != foreground
+                {
+                    graphics::write_color(&mut output, color, screen.ansi_256, false);
+                    foreground = top;
+                }
+                if bottom != background {
+                    let color = bottom.unwrap_or(Color::Default);
+                    graphics::write_color(&mut output, color, screen.ansi_256, true);
+                    background = bottom;
+                }
+                output.push(character);
+            }
+            output.push_str("\x1b[39;49m\r\n");
+        }
+        print(&output);
+    }
+}
+
+// Real pixels through a terminal image protocol, one per screen pixel
+pub struct ImageBackend {
+    renderer: Box<dyn Renderer>,
+    cell_pixels: (u16, u16),
+}
+
+impl ImageBackend {
+    pub fn new(renderer: Box<dyn Renderer>) -> ImageBackend {
+        let cell_pixels = match terminal::window_size() {
+            Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+                (size.width / size.columns, size.height / size.rows)
+            }
+            _ => FALLBACK_CELL_PIXELS,
+        };
+        ImageBackend {
+            renderer,
+            cell_pixels,
+        }
+    }
+}
+
+impl Backend for ImageBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        self.cell_pixels
+    }
+
+    fn present(&mut self, screen: &mut Screen) {
+        let mut output = String::from("\x1b[H");
+        output.push_str(self.renderer.erase());
+        output.push_str(&self.renderer.encode(&screen.to_image()));
+        print(&output);
+    }
+}
+
+// Everything at once instead of many small writes
+fn print(output: &str) {
+    execute!(io::stdout(), style::Print(output)).unwrap();
+}
@@ -0,0 +1,129 @@
+// Growable set of flags, one bit each, packed 64 to a word. Bits past the
+// length are always clear, so whole words can be counted and compared.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Bitset {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitset {
+    // `len` clear bits
+    pub fn new(len: usize) -> Bitset {
+        Bitset {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "bit {} of {}", index, self.len);
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "bit {} of {}", index, self.len);
+        let mask = 1 << (index % 64);
+        if value {
+            self.words[index / 64] |= mask;
+        } else {
+            self.words[index / 64] &= !mask;
+        }
+    }
+
+    // Set or clear every bit
+    pub fn fill(&mut self, value: bool) {
+        self.words.fill(if value { u64::MAX } else { 0 });
+        self.clear_tail();
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    // Grow with clear bits, or drop bits off the end
+    pub fn resize(&mut self, len: usize) {
+        self.words.resize(len.div_ceil(64), 0);
+        self.len = len;
+        self.clear_tail();
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn extend<I: IntoIterator<Item = bool>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.get(index))
+    }
+
+    // Keep the bits whose entry in `keep` is true, in order
+    pub fn retain(&mut self, keep: &[bool]) {
+        let kept = self
+            .iter()
+            .zip(keep)
+            .filter(|&(_, &keep)| keep)
+            .map(|(value, _)| value);
+        let mut retained = Bitset::new(0);
+        retained.extend(kept);
+        *self = retained;
+    }
+
+    fn clear_tail(&mut self) {
+        let used = self.len % 64;
+        if let Some(last) = self.words.last_mut()
+            && used > 0
+        {
+            *last &= (1 << used) - 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_survive_growing_across_words() {
+        let mut bits = Bitset::new(63);
+        bits.set(62, true);
+        bits.push(true);
+        bits.push(false);
+        bits.resize(130);
+        bits.set(129, true);
+        assert_eq!(bits.len(), 130);
+        assert!(bits.get(62) && bits.get(63) && !bits.get(64) && bits.get(129));
+        assert_eq!(bits.count_ones(), 3);
+
+        bits.resize(64);
+        bits.fill(true);
+        assert_eq!(bits.count_ones(), 64);
+    }
+
+    #[test]
+    fn retain_keeps_bits_in_order() {
+        let mut bits = Bitset::new(0);
+        bits.extend([true, false, true, true]);
+        bits.retain(&[false, true, true, false]);
+        assert_eq!(bits.iter().collect::<Vec<_>>(), [false, true]);
+    }
+}
@@ -0,0 +1,268 @@
+use std::*;
+
+use crate::graphics::{Color, CoordinateSystem, Point3D, PointCloud};
+use crate::layout::Preset;
+use crate::render::{ImageRenderer, Renderer};
+use crate::view::ViewState;
+
+// C bindings, built into libaltostratus with the `capi` feature. build.rs
+// writes the header, include/altostratus.h, from the `///` comments here.
+//
+//     AltoCloud *cloud = alto_cloud_new();
+//     alto_cloud_add_points(cloud, xyz, NULL, count);
+//     AltoRenderer *renderer = alto_renderer_new(800, 600);
+//     alto_render_rgb(renderer, cloud, pixels, 800 * 600 * 3);
+
+/// Points to draw, made by `alto_cloud_new` and freed by `alto_cloud_free`.
+pub struct AltoCloud {
+    cloud: PointCloud,
+}
+
+/// Image settings and camera, made by `alto_renderer_new` and freed by
+/// `alto_renderer_free`.
+pub struct AltoRenderer {
+    width: u16,
+    height: u16,
+    background: Color,
+    point_size: Option<f32>,
+    axes: bool,
+    camera: AltoCamera,
+}
+
+/// Where the picture is taken from, with angles in degrees. A distance of 0
+/// frames the whole cloud; a field of view of 0 keeps the viewer's.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct AltoCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    pub distance: f32,
+    pub fov: f32,
+}
+
+impl AltoCamera {
+    // A view of what `renderer` draws, framed from the camera's angles, then
+    // turned, zoomed and moved back as set
+    fn view(&self, renderer: &ImageRenderer) -> ViewState {
+        let mut view = renderer.auto_frame(self.yaw.to_radians(), self.pitch.to_radians());
+        view.roll_by(self.roll.to_radians());
+        if self.fov > 0.0 {
+            view = view.dolly_zoom(self.fov.to_radians());
+        }
+        if self.distance > 0.0 {
+            view.distance = self.distance;
+        }
+        view
+    }
+}
+
+/// A new cloud without points.
+#[unsafe(no_mangle)]
+pub extern "C" fn alto_cloud_new() -> *mut AltoCloud {
+    let cloud = Box::new(AltoCloud {
+        cloud: PointCloud::new(),
+    });
+    Box::into_raw(cloud)
+}
+
+/// Frees a cloud.
+///
+/// # Safety
+///
+/// `cloud` must come from `alto_cloud_new` and not be used again, or be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_cloud_free(cloud: *mut AltoCloud) {
+    if !cloud.is_null() {
+        drop(unsafe { Box::from_raw(cloud) });
+    }
+}
+
+/// Adds `count` points, z up, from `positions` as x, y, z floats one point
+/// after another. `colors`, if not NULL, holds r, g, b bytes for each point
+/// the same way. Returns false, adding nothing, if `cloud` or `positions` is
+/// NULL.
+///
+/// # Safety
+///
+/// `cloud` must come from `alto_cloud_new`. `positions` must hold `count * 3`
+/// floats and `colors`, unless NULL, `count * 3` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_cloud_add_points(
+    cloud: *mut AltoCloud,
+    positions: *const f32,
+    colors: *const u8,
+    count: usize,
+) -> bool {
+    let Some(cloud) = (unsafe { cloud.as_mut() }) else {
+        return false;
+    };
+    let Some(length) = count.checked_mul(3) else {
+        return false;
+    };
+    if positions.is_null() {
+        return false;
+    }
+    let positions = unsafe { slice::from_raw_parts(positions, length) };
+    let colors = match colors.is_null() {
+        true => None,
+        false => Some(unsafe { slice::from_raw_parts(colors, length) }),
+    };
+
+    cloud.cloud.extend((0..count).map(|index| {
+        let [x, y, z] = [0, 1, 2].map(|axis| positions[index * 3 + axis]);
+        let (x, y, z) = CoordinateSystem::ZUp.to_viewer(x, y, z);
+        match colors {
+            Some(colors) => {
+                let [r, g, b] = [0, 1, 2].map(|channel| colors[index * 3 + channel]);
+                Point3D::new_with_color(x, y, z, Color::Rgb(r, g, b))
+            }
+            None => Point3D::new(x, y, z),
+        }
+    }));
+    true
+}
+
+/// Number of points in a cloud, or 0 if `cloud` is NULL.
+///
+/// # Safety
+///
+/// `cloud` must come from `alto_cloud_new` or be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_cloud_len(cloud: *const AltoCloud) -> usize {
+    unsafe { cloud.as_ref() }.map_or(0, |cloud| cloud.cloud.len())
+}
+
+/// The isometric camera the viewer starts with.
+#[unsafe(no_mangle)]
+pub extern "C" fn alto_camera_default() -> AltoCamera {
+    let (yaw, pitch) = Preset::Isometric.angles();
+    AltoCamera {
+        yaw: yaw.to_degrees(),
+        pitch: pitch.to_degrees(),
+        roll: 0.0,
+        distance: 0.0,
+        fov: 0.0,
+    }
+}
+
+/// A renderer for `width` by `height` images of one-pixel points over black,
+/// with the axes, from the default camera.
+#[unsafe(no_mangle)]
+pub extern "C" fn alto_renderer_new(width: u16, height: u16) -> *mut AltoRenderer {
+    let renderer = Box::new(AltoRenderer {
+        width,
+        height,
+        background: Color::Black,
+        point_size: None,
+        axes: true,
+        camera: alto_camera_default(),
+    });
+    Box::into_raw(renderer)
+}
+
+/// Frees a renderer.
+///
+/// # Safety
+///
+/// `renderer` must come from `alto_renderer_new` and not be used again, or be
+/// NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_renderer_free(renderer: *mut AltoRenderer) {
+    if !renderer.is_null() {
+        drop(unsafe { Box::from_raw(renderer) });
+    }
+}
+
+/// Sets the camera later images are drawn from.
+///
+/// # Safety
+///
+/// `renderer` must come from `alto_renderer_new` or be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_renderer_set_camera(renderer: *mut AltoRenderer, camera: AltoCamera) {
+    if let Some(renderer) = unsafe { renderer.as_mut() } {
+        renderer.camera = camera;
+    }
+}
+
+/// Sets the background color.
+///
+/// # Safety
+///
+/// `renderer` must come from `alto_renderer_new` or be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_renderer_set_background(
+    renderer: *mut AltoRenderer,
+    r: u8,
+    g: u8,
+    b: u8,
+) {
+    if let Some(renderer) = unsafe { renderer.as_mut() } {
+        renderer.background = Color::Rgb(r, g, b);
+    }
+}
+
+/// Sets the point size in pixels; 0 draws one-pixel points.
+///
+/// # Safety
+///
+/// `renderer` must come from `alto_renderer_new` or be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_renderer_set_point_size(renderer: *mut AltoRenderer, size: f32) {
+    if let Some(renderer) = unsafe { renderer.as_mut() } {
+        renderer.point_size = (size > 0.0).then_some(size);
+    }
+}
+
+/// Sets whether the cloud's axes are drawn.
+///
+/// # Safety
+///
+/// `renderer` must come from `alto_renderer_new` or be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_renderer_set_axes(renderer: *mut AltoRenderer, axes: bool) {
+    if let Some(renderer) = unsafe { renderer.as_mut() } {
+        renderer.axes = axes;
+    }
+}
+
+/// Draws `cloud` into `pixels` as r, g, b bytes, row by row from the top
+/// left. Returns false, writing nothing, if a pointer is NULL, `length` is
+/// under width * height * 3 bytes or the renderer's settings are invalid.
+///
+/// # Safety
+///
+/// `renderer` must come from `alto_renderer_new`, `cloud` from
+/// `alto_cloud_new`, and `pixels` must hold `length` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn alto_render_rgb(
+    renderer: *const AltoRenderer,
+    cloud: *const AltoCloud,
+    pixels: *mut u8,
+    length: usize,
+) -> bool {
+    let (Some(settings), Some(cloud)) = (unsafe { renderer.as_ref() }, unsafe { cloud.as_ref() })
+    else {
+        return false;
+    };
+    if pixels.is_null() || length < settings.width as usize * settings.height as usize * 3 {
+        return false;
+    }
+
+    let mut builder = ImageRenderer::builder(&cloud.cloud)
+        .size(settings.width, settings.height)
+        .background(settings.background)
+        .axes(settings.axes);
+    if let Some(size) = settings.point_size {
+        builder = builder.point_size(size);
+    }
+    let Ok(mut renderer) = builder.build() else {
+        return false;
+    };
+    let view = settings.camera.view(&renderer);
+    let image = renderer.render(&view, renderer.viewport());
+    let bytes = image.pixels().as_flattened();
+    unsafe { slice::from_raw_parts_mut(pixels, bytes.len()) }.copy_from_slice(bytes);
+    true
+}
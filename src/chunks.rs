@@ -0,0 +1,150 @@
+use std::io::BufRead;
+use std::*;
+
+use crate::graphics::{CoordinateSystem, Point3D, PointCloud};
+use crate::loaders;
+
+// A point file read a chunk of points at a time, for files too big to load
+// whole. Every pass reads the file from disk again, so only one chunk is in
+// memory however big the file is. Only the native point file format can be
+// split up like this; formats read by a loader come whole.
+pub struct PointChunks {
+    path: String,
+    chunk_points: usize,
+    coordinate_system: CoordinateSystem,
+}
+
+impl PointChunks {
+    pub fn new(
+        path: &str,
+        chunk_points: usize,
+        coordinate_system: CoordinateSystem,
+    ) -> Result<PointChunks, String> {
+        if chunk_points == 0 {
+            return Err("chunks must hold at least one point".to_string());
+        }
+        if loaders::find_loader(path).is_some() {
+            return Err(format!(
+                "{} isn't a point file, so it can't be read in chunks",
+                path
+            ));
+        }
+        Ok(PointChunks {
+            path: path.to_string(),
+            chunk_points,
+            coordinate_system,
+        })
+    }
+
+    // The file's points in order and in viewer coordinates, about
+    // `chunk_points` at a time (a line may add a few more, the last chunk
+    // fewer)
+    pub fn iter(&self) -> io::Result<Chunks> {
+        let file = fs::File::open(&self.path)?;
+        Ok(Chunks {
+            lines: io::BufReader::new(file).lines().enumerate(),
+            chunk_points: self.chunk_points,
+            coordinate_system: self.coordinate_system,
+        })
+    }
+
+    // A stand-in for the whole file: the corners of the box around every
+    // point, hidden, which frame views and fit axes as the file would without
+    // drawing anything. Also returns how many points the file holds.
+    pub fn bounds(&self) -> Result<(PointCloud, usize), Box<dyn error::Error>> {
+        let mut bounds: Option<(Point3D, Point3D)> = None;
+        let mut count = 0;
+        for chunk in self.iter()? {
+            for point in chunk? {
+                let (min, max) = bounds.get_or_insert((point, point));
+                (min.x, min.y, min.z) =
+                    (min.x.min(point.x), min.y.min(point.y), min.z.min(point.z));
+                (max.x, max.y, max.z) =
+                    (max.x.max(point.x), max.y.max(point.y), max.z.max(point.z));
+                count += 1;
+            }
+        }
+        let (min, max) = bounds.ok_or("no points found")?;
+
+        let mut corners: PointCloud = (0..8)
+            .map(|corner| {
+                let pick = |bit: usize, min: f32, max: f32| match corner >> bit & 1 {
+                    0 => min,
+                    _ => max,
+                };
+                Point3D::new(
+                    pick(0, min.x, max.x),
+                    pick(1, min.y, max.y),
+                    pick(2, min.z, max.z),
+                )
+            })
+            .collect();
+        corners.hide_where(|_| true);
+        corners.set_file(&self.path);
+        Ok((corners, count))
+    }
+}
+
+pub struct Chunks {
+    lines: iter::Enumerate<io::Lines<io::BufReader<fs::File>>>,
+    chunk_points: usize,
+    coordinate_system: CoordinateSystem,
+}
+
+impl Iterator for Chunks {
+    type Item = Result<Vec<Point3D>, Box<dyn error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vec::new();
+        while chunk.len() < self.chunk_points {
+            let Some((line_num, line)) = self.lines.next() else {
+                break;
+            };
+            let points = line
+                .map_err(Into::into)
+                .and_then(|line| PointCloud::parse_line(&line, line_num));
+            match points {
+                Ok(points) => chunk.extend(points),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        if chunk.is_empty() {
+            return None;
+        }
+        self.coordinate_system.convert(&mut chunk);
+        Some(Ok(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_hold_every_point_in_order() {
+        let path = env::temp_dir().join(format!("chunks-{}.txt", process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(
+            path,
+            "p 1 0 0\n# comment\np 2 0 0\np 3 0 0\n\np 4 5 -1\np 5 0 0\n",
+        )
+        .unwrap();
+        let chunks = PointChunks::new(path, 2, CoordinateSystem::LeftHanded).unwrap();
+        let sizes: Vec<usize> = chunks
+            .iter()
+            .unwrap()
+            .map(|chunk| chunk.unwrap().len())
+            .collect();
+        let (bounds, count) = chunks.bounds().unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(sizes, [2, 2, 1]);
+        assert_eq!(count, 5);
+        assert_eq!(bounds.visible_count(), 0);
+        let (min, max) = bounds.get_bounds();
+        assert_eq!(
+            (min.x, min.y, min.z, max.x, max.y, max.z),
+            (1.0, 0.0, -1.0, 5.0, 5.0, 0.0)
+        );
+    }
+}
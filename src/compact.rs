@@ -0,0 +1,105 @@
+use std::*;
+
+use crate::graphics::{Color, Point3D, PointCloud};
+
+const CHUNK_POINTS: usize = 4096;
+const QUANTIZATION_STEPS: f32 = 65535.0;
+
+// Read-only copy of a cloud's visible points in about an eighth of the memory:
+// positions are 16-bit steps across the bounding box of their chunk and colors
+// are RGB565. Only what drawing needs is kept (no normals, scalars or sources).
+// Consecutive points in a file tend to lie close together, so chunks are runs
+// of points in file order.
+pub struct CompactCloud {
+    chunks: Vec<Chunk>,
+}
+
+struct Chunk {
+    origin: [f32; 3],
+    step: [f32; 3], // Size of one quantization step along each axis
+    positions: Vec<[u16; 3]>,
+    colors: Vec<u16>,
+}
+
+impl CompactCloud {
+    pub fn from_cloud(point_cloud: &PointCloud) -> CompactCloud {
+        let visible: Vec<&Point3D> = point_cloud.visible_points().collect();
+        let chunks = visible
+            .chunks(CHUNK_POINTS)
+            .map(|chunk| {
+                let coordinates = |point: &Point3D| [point.x, point.y, point.z];
+                let mut min = [f32::INFINITY; 3];
+                let mut max = [f32::NEG_INFINITY; 3];
+                for point in chunk {
+                    for (axis, value) in coordinates(point).into_iter().enumerate() {
+                        min[axis] = min[axis].min(value);
+                        max[axis] = max[axis].max(value);
+                    }
+                }
+                let step = array::from_fn(|axis| (max[axis] - min[axis]) / QUANTIZATION_STEPS);
+
+                Chunk {
+                    origin: min,
+                    step,
+                    positions: chunk
+                        .iter()
+                        .map(|point| {
+                            let values = coordinates(point);
+                            array::from_fn(|axis| match step[axis] {
+                                0.0 => 0,
+                                step => ((values[axis] - min[axis]) / step).round() as u16,
+                            })
+                        })
+                        .collect(),
+                    colors: chunk.iter().map(|point| to_rgb565(point.color)).collect(),
+                }
+            })
+            .collect();
+        CompactCloud { chunks }
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.positions.len()).sum()
+    }
+
+    // Bytes held by the points
+    pub fn memory_size(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                mem::size_of::<Chunk>()
+                    + chunk.positions.capacity() * mem::size_of::<[u16; 3]>()
+                    + chunk.colors.capacity() * mem::size_of::<u16>()
+            })
+            .sum()
+    }
+
+    // The points, decoded as they are visited
+    pub fn iter(&self) -> impl Iterator<Item = Point3D> + '_ {
+        self.chunks.iter().flat_map(|chunk| {
+            chunk
+                .positions
+                .iter()
+                .zip(&chunk.colors)
+                .map(|(position, &color)| {
+                    let [x, y, z] = array::from_fn(|axis| {
+                        chunk.origin[axis] + position[axis] as f32 * chunk.step[axis]
+                    });
+                    Point3D::new_with_color(x, y, z, from_rgb565(color))
+                })
+        })
+    }
+}
+
+fn to_rgb565(color: Color) -> u16 {
+    let (r, g, b) = color.to_rgb();
+    (r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3
+}
+
+// Low bits are filled from the high ones so white stays white
+fn from_rgb565(color: u16) -> Color {
+    let r = (color >> 11) as u8 & 0x1F;
+    let g = (color >> 5) as u8 & 0x3F;
+    let b = color as u8 & 0x1F;
+    Color::Rgb(r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2)
+}
@@ -0,0 +1,157 @@
+use std::*;
+
+use crate::ascii::Charset;
+
+// How colors are printed
+#[derive(Copy, Clone, PartialEq)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+    None, // Everything in the terminal's default color
+}
+
+// Defaults read from the config file; anything left out keeps the built-in
+// default, and command line flags override both. Example:
+//
+//   fov = 97.4               # Field of view in degrees
+//   mouse_sensitivity = 1.5  # Multiplier on how far a drag turns the camera
+//   scroll_speed = 0.5       # Multiplier on how far a scroll step zooms
+//   invert_y = true          # Drag up to tilt the camera up
+//   arcball = true           # Drag a trackball to turn the view
+//   inertia = 4              # Glide on after input, slowing by this friction
+//   charset = "blocks"       # Default for "altostratus ascii"
+//   color = "256"            # truecolor, 256 or none
+//   background = "white"
+//
+//   [keys]
+//   orbit-left = "h,left"
+//
+// Only that much of TOML is understood: one `key = value` per line, strings
+// in double quotes, numbers, booleans, `[keys]` and `#` comments.
+#[derive(Default)]
+pub struct Config {
+    pub fov: Option<f32>, // Radians
+    pub mouse_sensitivity: Option<f32>,
+    pub scroll_speed: Option<f32>,
+    pub invert_y: Option<bool>,
+    pub arcball: Option<bool>,
+    pub inertia: Option<f32>, // Friction per second
+    pub charset: Option<Charset>,
+    pub color: Option<ColorMode>,
+    pub background: Option<String>,
+    pub key_bindings: Vec<String>, // "action=keys", as given to --bind
+}
+
+// $XDG_CONFIG_HOME/altostratus/config.toml, or under ~/.config
+pub fn default_path() -> Option<path::PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => path::PathBuf::from(dir),
+        _ => path::PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("altostratus").join("config.toml"))
+}
+
+// Read the given config file, or the default one if it exists
+pub fn load(path: Option<&str>) -> Result<Config, String> {
+    let (path, content) = match path {
+        Some(path) => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("failed to read config {}: {}", path, e))?;
+            (path::PathBuf::from(path), content)
+        }
+        None => match default_path() {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(content) => (path, content),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+                Err(e) => return Err(format!("failed to read config {}: {}", path.display(), e)),
+            },
+            None => return Ok(Config::default()),
+        },
+    };
+    parse(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse(content: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut in_keys = false;
+
+    for (line_num, line) in content.lines().enumerate() {
+        let error = |msg: &str| format!("line {}: {}", line_num + 1, msg);
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            match section.trim() {
+                "keys" => in_keys = true,
+                other => return Err(error(&format!("unknown section [{}]", other))),
+            }
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error("expected key = value"))?;
+        let (key, value) = (key.trim().trim_matches('"'), value.trim());
+        let string = || {
+            value
+                .strip_prefix('"')
+                .and_then(|s| s.strip_suffix('"'))
+                .ok_or_else(|| error(&format!("{} must be a quoted string", key)))
+        };
+        let number = || {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|n| n.is_finite() && *n > 0.0)
+                .ok_or_else(|| error(&format!("{} must be a positive number", key)))
+        };
+        let boolean = || match value {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(error(&format!("{} must be true or false", key))),
+        };
+
+        if in_keys {
+            config.key_bindings.push(format!("{}={}", key, string()?));
+            continue;
+        }
+        match key {
+            "fov" => config.fov = Some(number()?.to_radians()),
+            "mouse_sensitivity" => config.mouse_sensitivity = Some(number()?),
+            "scroll_speed" => config.scroll_speed = Some(number()?),
+            "invert_y" => config.invert_y = Some(boolean()?),
+            "arcball" => config.arcball = Some(boolean()?),
+            "inertia" => config.inertia = Some(number()?),
+            "charset" => {
+                let charset = Charset::from_string(string()?)
+                    .ok_or_else(|| error("charset must be braille, blocks or ascii"))?;
+                config.charset = Some(charset);
+            }
+            "color" => {
+                config.color = Some(match string()? {
+                    "truecolor" => ColorMode::TrueColor,
+                    "256" => ColorMode::Ansi256,
+                    "none" => ColorMode::None,
+                    _ => return Err(error("color must be truecolor, 256 or none")),
+                });
+            }
+            "background" => config.background = Some(string()?.to_string()),
+            _ => return Err(error(&format!("unknown setting {}", key))),
+        }
+    }
+    Ok(config)
+}
+
+// Everything from a `#` outside double quotes on
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
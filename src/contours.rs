@@ -0,0 +1,118 @@
+use crate::graphics::{Color, Point3D};
+
+const MAX_GRID_CELLS: usize = 200; // Grid resolution cap along each horizontal axis
+const MAX_LEVELS: f32 = 1000.0; // Most contours one call draws; each one walks the whole grid
+
+// Extract iso-elevation lines from 2.5D data with marching squares.
+// Heights (viewer y) are averaged onto a regular grid over the horizontal
+// x/z plane, and every multiple of `interval` between the lowest and highest
+// grid value becomes a contour, colored along the height ramp. Intervals
+// giving more than `MAX_LEVELS` contours are refused.
+pub fn extract(points: &[Point3D], interval: f32) -> Result<Vec<(Point3D, Point3D)>, String> {
+    if points.is_empty() || interval <= 0.0 {
+        return Ok(Vec::new());
+    }
+
+    let (mut min_x, mut max_x) = (f32::INFINITY, f32::NEG_INFINITY);
+    let (mut min_z, mut max_z) = (f32::INFINITY, f32::NEG_INFINITY);
+    for point in points {
+        min_x = min_x.min(point.x);
+        max_x = max_x.max(point.x);
+        min_z = min_z.min(point.z);
+        max_z = max_z.max(point.z);
+    }
+
+    // Roughly one point per cell on average
+    let cells = ((points.len() as f32).sqrt() as usize).clamp(2, MAX_GRID_CELLS);
+    let step_x = ((max_x - min_x) / cells as f32).max(f32::EPSILON);
+    let step_z = ((max_z - min_z) / cells as f32).max(f32::EPSILON);
+
+    let mut sums = vec![vec![0.0f32; cells + 1]; cells + 1];
+    let mut counts = vec![vec![0u32; cells + 1]; cells + 1];
+    for point in points {
+        let i = (((point.x - min_x) / step_x).round() as usize).min(cells);
+        let j = (((point.z - min_z) / step_z).round() as usize).min(cells);
+        sums[i][j] += point.y;
+        counts[i][j] += 1;
+    }
+
+    let heights: Vec<Vec<Option<f32>>> = sums
+        .iter()
+        .zip(&counts)
+        .map(|(sum_row, count_row)| {
+            sum_row
+                .iter()
+                .zip(count_row)
+                .map(|(&sum, &count)| (count > 0).then(|| sum / count as f32))
+                .collect()
+        })
+        .collect();
+
+    let (min_height, max_height) = heights
+        .iter()
+        .flatten()
+        .flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &h| {
+            (lo.min(h), hi.max(h))
+        });
+
+    // Counted rather than stepped to, since adding a small interval to a
+    // large level may not change it
+    let start = (min_height / interval).ceil() * interval;
+    let levels = ((max_height - start) / interval).floor() + 1.0;
+    if levels > MAX_LEVELS {
+        return Err(format!(
+            "An interval of {} gives more than {} contours",
+            interval, MAX_LEVELS
+        ));
+    }
+
+    let mut segments = Vec::new();
+    for index in 0..levels.max(0.0) as usize {
+        let level = start + index as f32 * interval;
+        let color = Color::ramp((level - min_height) / (max_height - min_height).max(f32::EPSILON));
+
+        for i in 0..cells {
+            for j in 0..cells {
+                // Corners in order around the square; skip squares with gaps
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let Some(values) = corners
+                    .iter()
+                    .map(|&(ci, cj)| heights[ci][cj])
+                    .collect::<Option<Vec<f32>>>()
+                else {
+                    continue;
+                };
+
+                // Points where the level crosses each edge of the square
+                let mut crossings = Vec::with_capacity(4);
+                for edge in 0..4 {
+                    let (a, b) = (edge, (edge + 1) % 4);
+                    let (height_a, height_b) = (values[a], values[b]);
+                    if (height_a < level) == (height_b < level) {
+                        continue;
+                    }
+
+                    let t = (level - height_a) / (height_b - height_a);
+                    let (ai, aj) = corners[a];
+                    let (bi, bj) = corners[b];
+                    let grid_i = ai as f32 + t * (bi as f32 - ai as f32);
+                    let grid_j = aj as f32 + t * (bj as f32 - aj as f32);
+                    crossings.push(Point3D::new_with_color(
+                        min_x + grid_i * step_x,
+                        level,
+                        min_z + grid_j * step_z,
+                        color,
+                    ));
+                }
+
+                // Two crossings form one segment; saddles (four) form two
+                for pair in crossings.chunks_exact(2) {
+                    segments.push((pair[0], pair[1]));
+                }
+            }
+        }
+    }
+
+    Ok(segments)
+}
@@ -0,0 +1,218 @@
+use std::*;
+
+use crate::graphics::{Color, Point3D, PointCloud};
+
+// Column mapping for delimited text files (CSV/TSV spreadsheet exports).
+// Column indices are zero-based.
+#[derive(Clone)]
+pub struct CsvOptions {
+    pub delimiter: Option<char>, // None = infer from the file extension
+    pub x_column: usize,
+    pub y_column: usize,
+    pub z_column: usize,
+    pub color_column: Option<usize>,  // Column holding color names
+    pub scalar_column: Option<usize>, // Column mapped onto a color ramp
+    pub header_rows: Option<usize>,   // Rows to skip (None = skip a first row that isn't numbers)
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        CsvOptions {
+            delimiter: None,
+            x_column: 0,
+            y_column: 1,
+            z_column: 2,
+            color_column: None,
+            scalar_column: None,
+            header_rows: None,
+        }
+    }
+
+    fn delimiter_for(&self, path: &str) -> char {
+        if let Some(delimiter) = self.delimiter {
+            return delimiter;
+        }
+
+        if path.to_lowercase().ends_with(".tsv") {
+            '\t'
+        } else {
+            ','
+        }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions::new()
+    }
+}
+
+pub fn load_csv(path: &str, options: &CsvOptions) -> Result<PointCloud, Box<dyn error::Error>> {
+    let content = fs::read_to_string(path)?;
+    let delimiter = options.delimiter_for(path);
+
+    let mut points = Vec::new();
+    let mut scalars = Vec::new();
+    let mut first_data_row = true;
+
+    for (line_num, line) in content
+        .lines()
+        .enumerate()
+        .skip(options.header_rows.unwrap_or(0))
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(delimiter).map(|field| field.trim()).collect();
+        let column = |index: usize, name: &str| -> Result<&str, String> {
+            fields.get(index).copied().ok_or_else(|| {
+                format!(
+                    "Missing {} column ({}) on line {}: {}",
+                    name,
+                    index + 1,
+                    line_num + 1,
+                    line
+                )
+            })
+        };
+
+        let coordinates = (
+            column(options.x_column, "x")?.parse::<f32>(),
+            column(options.y_column, "y")?.parse::<f32>(),
+            column(options.z_column, "z")?.parse::<f32>(),
+        );
+
+        let (file_x, file_y, file_z) = match coordinates {
+            (Ok(x), Ok(y), Ok(z)) => (x, y, z),
+            // Without a header row count, an unparseable first row is taken
+            // as a header line
+            _ if first_data_row && options.header_rows.is_none() => {
+                first_data_row = false;
+                continue;
+            }
+            _ => {
+                return Err(
+                    format!("Invalid coordinates on line {}: {}", line_num + 1, line).into(),
+                );
+            }
+        };
+        first_data_row = false;
+
+        let color = match options.color_column {
+            Some(index) => {
+                let name = column(index, "color")?;
+                Color::from_string(name)
+                    .ok_or_else(|| format!("Invalid color '{}' on line {}", name, line_num + 1))?
+            }
+            None => Color::Default,
+        };
+
+        if let Some(index) = options.scalar_column {
+            let value = column(index, "scalar")?;
+            let scalar: f32 = value
+                .parse()
+                .map_err(|_| format!("Invalid scalar value on line {}: {}", line_num + 1, value))?;
+            scalars.push(scalar);
+        }
+
+        // File coordinates; the caller converts them for the viewer
+        points.push(Point3D::new_with_color(file_x, file_y, file_z, color));
+    }
+
+    // Scalar columns override named colors, normalized over the whole file
+    if !scalars.is_empty() {
+        let min = scalars.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = scalars.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let range = (max - min).max(f32::EPSILON);
+
+        for (point, scalar) in points.iter_mut().zip(&scalars) {
+            point.color = Color::ramp((scalar - min) / range);
+        }
+    }
+
+    let mut point_cloud: PointCloud = points.into_iter().collect();
+    if !scalars.is_empty() {
+        point_cloud.set_scalars(Some(scalars));
+    }
+    Ok(point_cloud)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Loads `content` as a file named `name`
+    fn load(name: &str, content: &str, options: &CsvOptions) -> Result<PointCloud, String> {
+        let path = env::temp_dir().join(format!("csv-{}-{}", process::id(), name));
+        fs::write(&path, content).unwrap();
+        let points = load_csv(path.to_str().unwrap(), options).map_err(|e| e.to_string());
+        fs::remove_file(&path).unwrap();
+        points
+    }
+
+    fn load_error(name: &str, content: &str, options: &CsvOptions) -> String {
+        load(name, content, options)
+            .err()
+            .expect("the file should not load")
+    }
+
+    fn positions(cloud: &PointCloud) -> Vec<(f32, f32, f32)> {
+        cloud.points().iter().map(|p| (p.x, p.y, p.z)).collect()
+    }
+
+    #[test]
+    fn text_first_row_is_a_header_by_default() {
+        let points = load("auto.csv", "x,y,z\n1,2,3\n4,5,6\n", &CsvOptions::new()).unwrap();
+        assert_eq!(positions(&points), [(1.0, 2.0, 3.0), (4.0, 5.0, 6.0)]);
+    }
+
+    #[test]
+    fn header_rows_are_skipped_and_later_text_is_an_error() {
+        let mut options = CsvOptions::new();
+        options.header_rows = Some(2);
+        let content = "exported by a scanner\nx,y,z\n1,2,3\n";
+        let points = load("skip.csv", content, &options).unwrap();
+        assert_eq!(positions(&points), [(1.0, 2.0, 3.0)]);
+
+        options.header_rows = Some(1);
+        let error = load_error("short.csv", content, &options);
+        assert_eq!(error, "Invalid coordinates on line 2: x,y,z");
+
+        options.header_rows = Some(0);
+        let error = load_error("none.csv", "x,y,z\n1,2,3\n", &options);
+        assert_eq!(error, "Invalid coordinates on line 1: x,y,z");
+    }
+
+    #[test]
+    fn columns_are_mapped() {
+        let options = CsvOptions {
+            x_column: 3,
+            y_column: 1,
+            z_column: 0,
+            color_column: Some(2),
+            ..CsvOptions::new()
+        };
+        let cloud = load("columns.tsv", "3\t2\tred\t1\n6\t5\tblue\t4\n", &options).unwrap();
+        assert_eq!(positions(&cloud), [(1.0, 2.0, 3.0), (4.0, 5.0, 6.0)]);
+        assert_eq!(cloud.points()[0].color, Color::Red);
+        assert_eq!(cloud.points()[1].color, Color::Blue);
+
+        let error = load_error("missing.csv", "1,2\n", &CsvOptions::new());
+        assert_eq!(error, "Missing z column (3) on line 1: 1,2");
+    }
+
+    #[test]
+    fn scalars_are_kept_and_colored_on_a_ramp() {
+        let options = CsvOptions {
+            delimiter: Some(';'),
+            scalar_column: Some(3),
+            ..CsvOptions::new()
+        };
+        let cloud = load("scalar.txt", "0;0;0;10\n1;1;1;20\n", &options).unwrap();
+        assert_eq!([cloud.scalar(0), cloud.scalar(1)], [Some(10.0), Some(20.0)]);
+        assert_eq!(cloud.points()[0].color, Color::ramp(0.0));
+        assert_eq!(cloud.points()[1].color, Color::ramp(1.0));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::graphics::Point3D;
+
+const SAMPLES_PER_SEGMENT: usize = 8; // Curve points between two consecutive samples
+
+// Smooth polyline through ordered samples, e.g. a noisy trajectory. The samples
+// are first averaged with a Gaussian window `smoothing` samples wide (0 keeps
+// them as they are; the end points never move), then joined with a centripetal
+// Catmull-Rom spline, which doesn't overshoot or loop on uneven spacing.
+// Curve points take the color of the sample they start from.
+pub fn fit_spline(points: &[Point3D], smoothing: f32) -> Vec<Point3D> {
+    // Repeated samples would give zero-length segments
+    let mut samples: Vec<Point3D> = Vec::with_capacity(points.len());
+    for point in points {
+        if samples
+            .last()
+            .is_none_or(|last| position(last) != position(point))
+        {
+            samples.push(*point);
+        }
+    }
+    if samples.len() < 2 {
+        return samples;
+    }
+
+    let controls = smooth(&samples, smoothing);
+    let last = controls.len() - 1;
+    let mut curve = Vec::with_capacity(last * SAMPLES_PER_SEGMENT + 1);
+    for i in 0..last {
+        // Mirror the neighbors past the ends
+        let p1 = controls[i];
+        let p2 = controls[i + 1];
+        let p0 = if i > 0 {
+            controls[i - 1]
+        } else {
+            reflect(p2, p1)
+        };
+        let p3 = if i + 1 < last {
+            controls[i + 2]
+        } else {
+            reflect(p1, p2)
+        };
+
+        for step in 0..SAMPLES_PER_SEGMENT {
+            let t = step as f32 / SAMPLES_PER_SEGMENT as f32;
+            let [x, y, z] = catmull_rom([p0, p1, p2, p3], t);
+            curve.push(Point3D::new_with_color(x, y, z, samples[i].color));
+        }
+    }
+    let [x, y, z] = controls[last];
+    curve.push(Point3D::new_with_color(x, y, z, samples[last].color));
+
+    curve
+}
+
+// Gaussian-weighted moving average with a standard deviation of `sigma` samples
+fn smooth(samples: &[Point3D], sigma: f32) -> Vec<[f32; 3]> {
+    let positions: Vec<[f32; 3]> = samples.iter().map(position).collect();
+    if sigma <= 0.0 {
+        return positions;
+    }
+
+    let reach = (3.0 * sigma).ceil() as usize;
+    let last = positions.len() - 1;
+    (0..positions.len())
+        .map(|i| {
+            if i == 0 || i == last {
+                return positions[i];
+            }
+            let mut sum = [0.0; 3];
+            let mut total = 0.0;
+            for (j, position) in positions
+                .iter()
+                .enumerate()
+                .take((i + reach).min(last) + 1)
+                .skip(i.saturating_sub(reach))
+            {
+                let offset = j as f32 - i as f32;
+                let weight = (-offset * offset / (2.0 * sigma * sigma)).exp();
+                for axis in 0..3 {
+                    sum[axis] += position[axis] * weight;
+                }
+                total += weight;
+            }
+            sum.map(|value| value / total)
+        })
+        .collect()
+}
+
+// Point on the centripetal Catmull-Rom segment from p[1] (t = 0) to p[2]
+// (t = 1), using the Barry-Goldman recursion
+fn catmull_rom(p: [[f32; 3]; 4], t: f32) -> [f32; 3] {
+    // Knots spaced by the square root of the chord lengths
+    let mut knots = [0.0f32; 4];
+    for i in 1..4 {
+        knots[i] = knots[i - 1] + distance(p[i - 1], p[i]).sqrt().max(f32::EPSILON);
+    }
+    let t = knots[1] + (knots[2] - knots[1]) * t;
+
+    let lerp = |a: [f32; 3], b: [f32; 3], t0: f32, t1: f32| {
+        let w = (t - t0) / (t1 - t0);
+        [0, 1, 2].map(|axis| a[axis] + (b[axis] - a[axis]) * w)
+    };
+    let a1 = lerp(p[0], p[1], knots[0], knots[1]);
+    let a2 = lerp(p[1], p[2], knots[1], knots[2]);
+    let a3 = lerp(p[2], p[3], knots[2], knots[3]);
+    let b1 = lerp(a1, a2, knots[0], knots[2]);
+    let b2 = lerp(a2, a3, knots[1], knots[3]);
+    lerp(b1, b2, knots[1], knots[2])
+}
+
+fn position(point: &Point3D) -> [f32; 3] {
+    [point.x, point.y, point.z]
+}
+
+// `point` mirrored through `center`
+fn reflect(point: [f32; 3], center: [f32; 3]) -> [f32; 3] {
+    [0, 1, 2].map(|axis| 2.0 * center[axis] - point[axis])
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
@@ -0,0 +1,70 @@
+use crate::graphics::{Camera, Color, PointCloud};
+use crate::view::ViewState;
+
+const DEMO_POINTS: usize = 600;
+const DEMO_TURNS: f32 = 4.0;
+const DEMO_RADIUS: f32 = 3.0;
+const DEMO_HEIGHT: f32 = 6.0;
+
+// Render a built-in scene once to stdout, without entering the interactive
+// viewer. Useful for checking that a terminal shows braille and colors.
+pub fn run_demo(viewport_fov: f32, viewport_distance: f32) {
+    let point_cloud = demo_scene();
+
+    let mut camera = Camera::new(point_cloud.get_center(), viewport_distance, viewport_fov);
+    let view = ViewState {
+        orientation: ViewState::orientation_at(std::f32::consts::PI / 4.0, 0.4, 0.0),
+        distance: point_cloud.get_diagonal() * 1.5,
+        center: point_cloud.get_center(),
+        fov: viewport_fov,
+    };
+    view.apply(&mut camera);
+
+    camera.screen.fit_to_terminal();
+    camera.screen.clear();
+    camera.draw_cloud(&point_cloud);
+
+    print!("{}", camera.screen.frame());
+
+    // One swatch per supported color so missing color support is obvious
+    let swatches: Vec<String> = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ]
+    .iter()
+    .map(|color| {
+        let (r, g, b) = color.to_rgb();
+        format!("\x1b[38;2;{};{};{}m{}\x1b[39m", r, g, b, color)
+    })
+    .collect();
+    println!("colors: {}", swatches.join(" "));
+    println!(
+        "{} points, {} x {} braille pixels",
+        point_cloud.len(),
+        camera.screen.viewport.width,
+        camera.screen.viewport.height
+    );
+}
+
+// A rising spiral colored along its length
+fn demo_scene() -> PointCloud {
+    let steps = (0..DEMO_POINTS).map(|i| i as f32 / (DEMO_POINTS - 1) as f32);
+    let positions: Vec<[f32; 3]> = steps
+        .clone()
+        .map(|t| {
+            let angle = t * DEMO_TURNS * std::f32::consts::TAU;
+            [
+                DEMO_RADIUS * angle.cos(),
+                t * DEMO_HEIGHT,
+                DEMO_RADIUS * angle.sin(),
+            ]
+        })
+        .collect();
+    let colors: Vec<Color> = steps.map(Color::ramp).collect();
+    PointCloud::from_positions_and_colors(&positions, &colors)
+}
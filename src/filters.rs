@@ -0,0 +1,74 @@
+use crate::graphics::Point3D;
+use crate::segmentation;
+use crate::spatial::SpatialGrid;
+
+// Moving-least-squares smoothing: every point is projected onto a plane fitted
+// to its neighbors within `radius`, weighted by a Gaussian of their distance so
+// the fit follows the local surface. Noise across the surface is removed while
+// edges between surfaces are only slightly rounded. Points with fewer than
+// three neighbors are left where they are.
+pub fn mls_smooth(points: &mut [Point3D], radius: f32) {
+    let sigma = radius / 2.0;
+    let smoothed: Vec<Option<[f32; 3]>> = {
+        let grid = SpatialGrid::new(points, radius);
+        points
+            .iter()
+            .map(|point| {
+                let neighbors = grid.neighbors_within(point, radius);
+                if neighbors.len() < 3 {
+                    return None;
+                }
+
+                // Weighted centroid
+                let weights: Vec<f32> = neighbors
+                    .iter()
+                    .map(|&index| {
+                        let distance_squared = (points[index].x - point.x).powi(2)
+                            + (points[index].y - point.y).powi(2)
+                            + (points[index].z - point.z).powi(2);
+                        (-distance_squared / (2.0 * sigma * sigma)).exp()
+                    })
+                    .collect();
+                let total: f32 = weights.iter().sum();
+                let mut mean = [0.0f32; 3];
+                for (&index, &weight) in neighbors.iter().zip(&weights) {
+                    mean[0] += points[index].x * weight / total;
+                    mean[1] += points[index].y * weight / total;
+                    mean[2] += points[index].z * weight / total;
+                }
+
+                // Weighted covariance; the plane normal is its least-variance direction
+                let mut covariance = [[0.0f32; 3]; 3];
+                for (&index, &weight) in neighbors.iter().zip(&weights) {
+                    let delta = [
+                        points[index].x - mean[0],
+                        points[index].y - mean[1],
+                        points[index].z - mean[2],
+                    ];
+                    for row in 0..3 {
+                        for col in 0..3 {
+                            covariance[row][col] += weight * delta[row] * delta[col];
+                        }
+                    }
+                }
+                let normal = segmentation::smallest_eigenvector(covariance);
+
+                // Drop the point's offset from the plane along the normal
+                let offset = (point.x - mean[0]) * normal[0]
+                    + (point.y - mean[1]) * normal[1]
+                    + (point.z - mean[2]) * normal[2];
+                Some([
+                    point.x - offset * normal[0],
+                    point.y - offset * normal[1],
+                    point.z - offset * normal[2],
+                ])
+            })
+            .collect()
+    };
+
+    for (point, position) in points.iter_mut().zip(smoothed) {
+        if let Some([x, y, z]) = position {
+            (point.x, point.y, point.z) = (x, y, z);
+        }
+    }
+}
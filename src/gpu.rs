@@ -0,0 +1,410 @@
+use std::*;
+
+use wgpu::util::DeviceExt;
+
+use crate::graphics::{Camera, Color, Point3D, PointCloud, Viewport};
+use crate::image::RgbImage;
+use crate::render::Renderer;
+use crate::view::VIEWPORT_DISTANCE;
+use crate::view::ViewState;
+
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+const VERTEX_SIZE: u64 = 16; // Position as three floats, then RGBA bytes
+const UNIFORM_SIZE: u64 = 96; // World to camera matrix, projection and pixel offset
+
+// Points go through the same projection as `Camera::projection`. Depth
+// runs from the near plane at the viewport distance (nothing closer is drawn,
+// as on the CPU) to a far plane behind the whole cloud.
+const SHADER: &str = r#"
+struct Uniforms {
+    world_to_camera: mat4x4<f32>,
+    projection: vec4<f32>, // x and y scale, near, far
+    offset: vec4<f32>,     // Half a pixel, so pixels round like the CPU's
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vertex_main(@location(0) position: vec3<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    let camera = uniforms.world_to_camera * vec4<f32>(position, 1.0);
+    let near = uniforms.projection.z;
+    let far = uniforms.projection.w;
+    var output: VertexOutput;
+    output.position = vec4<f32>(
+        camera.xy * uniforms.projection.xy + uniforms.offset.xy * camera.z,
+        (camera.z - near) * far / (far - near),
+        camera.z,
+    );
+    output.color = color;
+    return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    return input.color;
+}
+"#;
+
+// Draws clouds on the GPU: the points are uploaded once as a vertex buffer,
+// then every frame is one draw call into an off-screen texture that is read
+// back as an image. Points are single pixels in their own color (no point
+// styles or lighting), with the axes behind them as on the CPU.
+pub struct WgpuRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    point_pipeline: wgpu::RenderPipeline,
+    line_pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    points: wgpu::Buffer,
+    point_count: u32,
+    lines: wgpu::Buffer,
+    line_vertex_count: u32,
+    target: Option<Target>, // Kept while the viewport size stays the same
+    center: Point3D,        // Of the cloud, to place the far plane
+    diagonal: f32,
+}
+
+// Textures a frame is drawn into and the buffer it is read back through
+struct Target {
+    viewport: Viewport,
+    color: wgpu::Texture,
+    depth: wgpu::TextureView,
+    readback: wgpu::Buffer,
+    row_bytes: u32, // Padded to the alignment texture copies need
+}
+
+impl WgpuRenderer {
+    pub fn new(point_cloud: &PointCloud) -> Result<WgpuRenderer, Box<dyn error::Error>> {
+        // WGPU_BACKEND picks the API (vulkan, metal, dx12 or gl) if set
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))?;
+        // Whatever the adapter allows, since large clouds need large buffers
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+                label: Some("altostratus"),
+                required_limits: adapter.limits(),
+                ..Default::default()
+            }))?;
+
+        let point_data: Vec<u8> = point_cloud
+            .visible_points()
+            .flat_map(|point| vertex(point, point.color))
+            .collect();
+        let line_data: Vec<u8> = point_cloud
+            .axes
+            .iter()
+            .flat_map(|axis| {
+                iter::once(&axis.axis_line)
+                    .chain(&axis.arrowhead_lines)
+                    .chain(&axis.tick_lines)
+            })
+            .flat_map(|(start, end)| [vertex(start, Color::Default), vertex(end, Color::Default)])
+            .flatten()
+            .collect();
+        let vertex_buffer = |label, contents| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        };
+        let points = vertex_buffer("points", &point_data);
+        let lines = vertex_buffer("axes", &line_data);
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uniforms"),
+            size: UNIFORM_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+
+        // Axis lines are drawn first and never hide anything; points hide
+        // points behind them, and the last of equally near ones wins
+        let pipeline = |topology, depth_write_enabled, depth_compare| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vertex_main"),
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: VERTEX_SIZE,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Unorm8x4],
+                    }],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology,
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled,
+                    depth_compare,
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }),
+                multisample: Default::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fragment_main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(COLOR_FORMAT.into())],
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let line_pipeline = pipeline(
+            wgpu::PrimitiveTopology::LineList,
+            false,
+            wgpu::CompareFunction::Always,
+        );
+        let point_pipeline = pipeline(
+            wgpu::PrimitiveTopology::PointList,
+            true,
+            wgpu::CompareFunction::LessEqual,
+        );
+
+        Ok(WgpuRenderer {
+            point_count: (point_data.len() as u64 / VERTEX_SIZE) as u32,
+            line_vertex_count: (line_data.len() as u64 / VERTEX_SIZE) as u32,
+            center: point_cloud.get_center(),
+            diagonal: point_cloud.get_diagonal(),
+            device,
+            queue,
+            point_pipeline,
+            line_pipeline,
+            uniforms,
+            bind_group,
+            points,
+            lines,
+            target: None,
+        })
+    }
+
+    // Make the textures match the viewport's size
+    fn resize_target(&mut self, viewport: Viewport) {
+        if self
+            .target
+            .as_ref()
+            .is_none_or(|target| target.viewport != viewport)
+        {
+            let size = wgpu::Extent3d {
+                width: viewport.width as u32,
+                height: viewport.height as u32,
+                depth_or_array_layers: 1,
+            };
+            let texture = |label, format, usage| {
+                self.device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+            };
+            let row_bytes = (size.width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+            self.target = Some(Target {
+                viewport,
+                color: texture(
+                    "color",
+                    COLOR_FORMAT,
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                ),
+                depth: texture(
+                    "depth",
+                    DEPTH_FORMAT,
+                    wgpu::TextureUsages::RENDER_ATTACHMENT,
+                )
+                .create_view(&Default::default()),
+                readback: self.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("readback"),
+                    size: row_bytes as u64 * size.height as u64,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                row_bytes,
+            });
+        }
+    }
+
+    fn write_uniforms(&self, view: &ViewState, viewport: Viewport) {
+        let mut camera = Camera::new(view.center, VIEWPORT_DISTANCE, view.fov);
+        view.apply(&mut camera);
+
+        // Columns of the matrix are where the world's origin and unit axes
+        // end up, which keeps it in step with the CPU's transform
+        let transform = camera.projection();
+        let origin = transform.to_camera(&Point3D::new(0., 0., 0.));
+        let axis = |x, y, z| {
+            let end = transform.to_camera(&Point3D::new(x, y, z));
+            [end.x - origin.x, end.y - origin.y, end.z - origin.z, 0.0]
+        };
+        let columns = [
+            axis(1., 0., 0.),
+            axis(0., 1., 0.),
+            axis(0., 0., 1.),
+            [origin.x, origin.y, origin.z, 1.0],
+        ];
+
+        let half_width = (view.fov / 2.0).tan();
+        let center = transform.to_camera(&self.center);
+        let far = (center.z + self.diagonal).max(VIEWPORT_DISTANCE * 2.0);
+        let projection = [
+            1.0 / half_width,
+            1.0 / (half_width * viewport.aspect_ratio()),
+            VIEWPORT_DISTANCE,
+            far,
+        ];
+        let offset = [
+            1.0 / viewport.width as f32,
+            -1.0 / viewport.height as f32,
+            0.0,
+            0.0,
+        ];
+
+        let data: Vec<u8> = columns
+            .iter()
+            .flatten()
+            .chain(&projection)
+            .chain(&offset)
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        self.queue.write_buffer(&self.uniforms, 0, &data);
+    }
+}
+
+impl Renderer for WgpuRenderer {
+    fn render(&mut self, view: &ViewState, viewport: Viewport) -> RgbImage {
+        self.write_uniforms(view, viewport);
+        self.resize_target(viewport);
+        let target = self.target.as_ref().unwrap();
+
+        let color_view = target.color.create_view(&Default::default());
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let (r, g, b) = Color::Black.to_rgb(); // As `Screen::to_image`
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: r as f64 / 255.0,
+                            g: g as f64 / 255.0,
+                            b: b as f64 / 255.0,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &target.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            for (pipeline, buffer, count) in [
+                (&self.line_pipeline, &self.lines, self.line_vertex_count),
+                (&self.point_pipeline, &self.points, self.point_count),
+            ] {
+                if count > 0 {
+                    pass.set_pipeline(pipeline);
+                    pass.set_vertex_buffer(0, buffer.slice(..));
+                    pass.draw(0..count, 0..1);
+                }
+            }
+        }
+        encoder.copy_texture_to_buffer(
+            target.color.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &target.readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(target.row_bytes),
+                    rows_per_image: None,
+                },
+            },
+            target.color.size(),
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = target.readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        self.device.poll(wgpu::PollType::Wait).unwrap();
+
+        let (width, height) = (viewport.width as usize, viewport.height as usize);
+        let mut image = RgbImage::new(width, height, Color::Black);
+        {
+            let data = slice.get_mapped_range();
+            for (y, row) in data.chunks(target.row_bytes as usize).enumerate() {
+                for (x, pixel) in row[..width * 4].chunks(4).enumerate() {
+                    image.put(x, y, Color::Rgb(pixel[0], pixel[1], pixel[2]));
+                }
+            }
+        }
+        target.readback.unmap();
+        image
+    }
+}
+
+// Position and color of one vertex as the shader reads them
+fn vertex(point: &Point3D, color: Color) -> impl Iterator<Item = u8> {
+    let (r, g, b) = color.to_rgb();
+    [point.x, point.y, point.z]
+        .into_iter()
+        .flat_map(f32::to_le_bytes)
+        .chain([r, g, b, u8::MAX])
+}
@@ -1,8 +1,21 @@
-use crossterm::{cursor, execute, style, terminal};
+use crossterm::{execute, style, terminal};
+use std::collections::HashMap;
+use std::fmt::Write;
 use std::ops;
 use std::*;
 
-// Color definitions for ANSI 8-color support
+use crate::arrays::PointArrays;
+use crate::bitset::Bitset;
+use crate::csv::CsvOptions;
+use crate::image::RgbImage;
+use crate::loaders;
+use crate::quantize;
+use crate::quaternion::Quaternion;
+use crate::rng::Rng;
+use crate::segmentation;
+use crate::spatial::SpatialGrid;
+
+// Color definitions for ANSI 8-color support, plus 24-bit colors
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Color {
     Default, // No color specified (white/default terminal color)
@@ -14,6 +27,7 @@ pub enum Color {
     Magenta,
     Cyan,
     White,
+    Rgb(u8, u8, u8),
 }
 
 impl Color {
@@ -28,10 +42,19 @@ impl Color {
             Color::Magenta => style::Color::Magenta,
             Color::Cyan => style::Color::Cyan,
             Color::White => style::Color::White,
+            Color::Rgb(r, g, b) => style::Color::Rgb {
+                r: *r,
+                g: *g,
+                b: *b,
+            },
         }
     }
 
     pub fn from_string(s: &str) -> Option<Color> {
+        if let Some(hex) = s.strip_prefix('#') {
+            return Self::from_hex(hex);
+        }
+
         match s.to_lowercase().as_str() {
             "default" | "white" => Some(Color::Default),
             "black" => Some(Color::Black),
@@ -44,14 +67,277 @@ impl Color {
             _ => None,
         }
     }
+
+    // Parse "rrggbb"
+    fn from_hex(hex: &str) -> Option<Color> {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    // 8-bit channels, using the xterm palette for the named colors
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Default | Color::White => (229, 229, 229),
+            Color::Black => (0, 0, 0),
+            Color::Red => (205, 0, 0),
+            Color::Green => (0, 205, 0),
+            Color::Yellow => (205, 205, 0),
+            Color::Blue => (0, 0, 238),
+            Color::Magenta => (205, 0, 205),
+            Color::Cyan => (0, 205, 205),
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    // Interpolate from `a` (t = 0) to `b` (t = 1) in linear light
+    pub fn blend(a: Color, b: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let (from, to): ([f32; 3], [f32; 3]) = (a.into(), b.into());
+        Color::from(array::from_fn::<f32, 3, _>(|i| {
+            from[i] + (to[i] - from[i]) * t
+        }))
+    }
+
+    // Add the light of both colors per channel, saturating at white
+    pub fn saturating_add(self, other: Color) -> Color {
+        let (a, b): ([f32; 3], [f32; 3]) = (self.into(), other.into());
+        Color::from(array::from_fn::<f32, 3, _>(|i| a[i] + b[i]))
+    }
+
+    // Scale the intensity in linear light, saturating at white
+    pub fn scale(self, factor: f32) -> Color {
+        let linear: [f32; 3] = self.into();
+        Color::from(linear.map(|channel| channel * factor.max(0.0)))
+    }
+
+    // Relative luminance (0..1) of the color in linear light
+    fn luminance(self) -> f32 {
+        let [r, g, b]: [f32; 3] = self.into();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    // Move the color towards white (or black, on light backgrounds) just far
+    // enough that its luminance differs from the background by `min_contrast`.
+    // The terminal's default color is assumed to be readable already.
+    pub fn with_contrast(self, background: Color, min_contrast: f32) -> Color {
+        if self == Color::Default {
+            return self;
+        }
+
+        let (own, back) = (self.luminance(), background.luminance());
+        if (own - back).abs() >= min_contrast {
+            return self;
+        }
+
+        // Luminance is linear in linear light, so the blend amount is exact
+        let (target, wanted) = if back < 0.5 {
+            (Color::Rgb(255, 255, 255), (back + min_contrast).min(1.0))
+        } else {
+            (Color::Rgb(0, 0, 0), (back - min_contrast).max(0.0))
+        };
+        let target_luminance = target.luminance();
+        let t = (wanted - own) / (target_luminance - own);
+        Self::blend(self, target, t)
+    }
+
+    // Map a normalized value (0..1) onto a blue -> cyan -> green -> yellow -> red ramp
+    pub fn ramp(t: f32) -> Color {
+        const RAMP: [Color; 5] = [
+            Color::Blue,
+            Color::Cyan,
+            Color::Green,
+            Color::Yellow,
+            Color::Red,
+        ];
+
+        let position = t.clamp(0.0, 1.0) * (RAMP.len() - 1) as f32;
+        let index = (position.floor() as usize).min(RAMP.len() - 2);
+        Self::blend(RAMP[index], RAMP[index + 1], position - index as f32)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Color::Default => write!(f, "default"),
+            Color::Black => write!(f, "black"),
+            Color::Red => write!(f, "red"),
+            Color::Green => write!(f, "green"),
+            Color::Yellow => write!(f, "yellow"),
+            Color::Blue => write!(f, "blue"),
+            Color::Magenta => write!(f, "magenta"),
+            Color::Cyan => write!(f, "cyan"),
+            Color::White => write!(f, "white"),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+// Linear-light channels in 0..1 (sRGB transfer function removed)
+impl From<Color> for [f32; 3] {
+    fn from(color: Color) -> [f32; 3] {
+        let to_linear = |channel: u8| {
+            let c = channel as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let (r, g, b) = color.to_rgb();
+        [to_linear(r), to_linear(g), to_linear(b)]
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from(linear: [f32; 3]) -> Color {
+        let to_srgb = |channel: f32| {
+            let c = channel.clamp(0.0, 1.0);
+            let encoded = if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            };
+            (encoded * 255.0).round() as u8
+        };
+
+        Color::Rgb(to_srgb(linear[0]), to_srgb(linear[1]), to_srgb(linear[2]))
+    }
+}
+
+impl ops::Add for Color {
+    type Output = Color;
+
+    fn add(self, other: Color) -> Color {
+        self.saturating_add(other)
+    }
+}
+
+impl ops::Mul<f32> for Color {
+    type Output = Color;
+
+    fn mul(self, factor: f32) -> Color {
+        self.scale(factor)
+    }
 }
 
 // Graphics rendering constants
 const DEFAULT_TERMINAL_DIMENSIONS: (u16, u16) = (80, 24);
+pub const BRAILLE_CELL: (u16, u16) = (2, 4); // Dots per braille character
 const MIN_AXIS_LENGTH: f32 = 5.0;
+const AXIS_MARGIN: f32 = 1.1; // Axes reach this much past the furthest point
+const PERCENTILE_SAMPLES: usize = 100_000; // Points looked at to estimate percentiles
+const AMBIENT_LIGHT: f32 = 0.25; // Brightness of surfaces facing away from the light
+// Minimum luminance difference from the background. Kept below pure blue's
+// luminance of 0.0722, so pure blue on black is never lifted.
+const MIN_CONTRAST: f32 = 0.05;
+const TARGET_TICK_COUNT: f32 = 5.0; // Roughly how many ticks each axis gets
+const MIN_BRIGHTNESS: f32 = 0.2; // Brightness factor at the bottom of a modulation range
+const PICK_RADIUS: f32 = 6.0; // How far from the target (in pixels) a point can be picked
+const CROSSHAIR_ARM: i32 = 3; // Crosshair arm length in pixels
+const PROJECTION_LANES: usize = 8; // Positions `project_batch` works on at once
+const BATCH_POINTS: usize = 4096; // Points `draw_arrays` projects at a time, per thread
+
+// Axis conventions of input data. The viewer works in a left-handed Y-up
+// frame (x right, y up, z into the screen); loaded points are converted into
+// it so that "up" in the data is up on screen.
+#[derive(Copy, Clone, PartialEq)]
+pub enum CoordinateSystem {
+    ZUp,        // Right-handed, z up (GIS, CAD, robotics); the default
+    YUp,        // Right-handed, y up (OpenGL, most mesh tools)
+    LeftHanded, // Left-handed, y up (DirectX, Unity); same as the viewer
+}
+
+impl CoordinateSystem {
+    pub fn from_string(s: &str) -> Option<CoordinateSystem> {
+        match s.to_lowercase().as_str() {
+            "z" | "z-up" => Some(CoordinateSystem::ZUp),
+            "y" | "y-up" => Some(CoordinateSystem::YUp),
+            "left" | "left-handed" => Some(CoordinateSystem::LeftHanded),
+            _ => None,
+        }
+    }
+
+    // Viewer coordinates of a point given in this system
+    pub fn to_viewer(self, x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+        match self {
+            CoordinateSystem::ZUp => (x, z, y),
+            CoordinateSystem::YUp => (x, y, -z),
+            CoordinateSystem::LeftHanded => (x, y, z),
+        }
+    }
+
+    // Coordinates in this system of a point given in viewer coordinates
+    pub fn to_data(self, x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+        match self {
+            CoordinateSystem::ZUp => (x, z, y),
+            CoordinateSystem::YUp => (x, y, -z),
+            CoordinateSystem::LeftHanded => (x, y, z),
+        }
+    }
+
+    // Move points given in this system into viewer coordinates
+    pub fn convert(self, points: &mut [Point3D]) {
+        for point in points {
+            (point.x, point.y, point.z) = self.to_viewer(point.x, point.y, point.z);
+        }
+    }
+
+    // Move points in viewer coordinates back into this system
+    pub fn convert_back(self, points: &mut [Point3D]) {
+        for point in points {
+            (point.x, point.y, point.z) = self.to_data(point.x, point.y, point.z);
+        }
+    }
+
+    // Move directions (such as normals) given in this system into viewer
+    // coordinates
+    pub fn convert_directions(self, directions: &mut [[f32; 3]]) {
+        for [x, y, z] in directions {
+            (*x, *y, *z) = self.to_viewer(*x, *y, *z);
+        }
+    }
+
+    pub fn convert_directions_back(self, directions: &mut [[f32; 3]]) {
+        for [x, y, z] in directions {
+            (*x, *y, *z) = self.to_data(*x, *y, *z);
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub fn from_string(s: &str) -> Option<Axis> {
+        match s.to_lowercase().as_str() {
+            "x" => Some(Axis::X),
+            "y" => Some(Axis::Y),
+            "z" => Some(Axis::Z),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
 
 // Simple 3d point wrapper with color support.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct Point3D {
     pub x: f32,
     pub y: f32,
@@ -75,7 +361,7 @@ impl Point3D {
 }
 
 // Simple 2d point wrapper.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Point2D {
     pub x: i32,
     pub y: i32,
@@ -88,7 +374,7 @@ impl Point2D {
 }
 
 // Braille pixel struct
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct BraillePixel {
     data: [[bool; 2]; 4],
 }
@@ -100,7 +386,12 @@ impl BraillePixel {
         }
     }
 
-    pub fn to_char(&self) -> char {
+    // No dots raised, so the foreground color doesn't matter
+    pub fn is_blank(self) -> bool {
+        self.data.iter().flatten().all(|dot| !dot)
+    }
+
+    pub fn to_char(self) -> char {
         let mut unicode: u32 = 0;
         if self.data[0][0] {
             unicode |= 1 << 0
@@ -135,6 +426,12 @@ impl BraillePixel {
     }
 }
 
+impl Default for BraillePixel {
+    fn default() -> BraillePixel {
+        BraillePixel::new()
+    }
+}
+
 impl ops::Index<usize> for BraillePixel {
     type Output = [bool; 2];
 
@@ -149,30 +446,187 @@ impl ops::IndexMut<usize> for BraillePixel {
     }
 }
 
-// Screen wrapper with color support and performance optimizations
-pub struct Screen {
+// Pixel dimensions of a drawing surface, shared by the screen buffers and
+// the camera projection so they can't disagree about the size
+#[derive(Copy, Clone, PartialEq)]
+pub struct Viewport {
     pub width: u16,
     pub height: u16,
+}
+
+impl Viewport {
+    pub fn new(width: u16, height: u16) -> Viewport {
+        Viewport { width, height }
+    }
+
+    // Pixels per terminal cell come from the backend (2x4 for braille); the
+    // bottom row is left for the status line
+    pub fn from_terminal(
+        columns: u16,
+        rows: u16,
+        (cell_width, cell_height): (u16, u16),
+    ) -> Viewport {
+        Viewport::new(
+            columns.saturating_mul(cell_width),
+            rows.saturating_sub(1).saturating_mul(cell_height),
+        )
+    }
+
+    pub fn contains(&self, point: &Point2D) -> bool {
+        let x_in_bounds = 0 < point.x && point.x < self.width as i32;
+        let y_in_bounds = 0 < point.y && point.y < self.height as i32;
+        x_in_bounds && y_in_bounds
+    }
+
+    // Height over width
+    pub fn aspect_ratio(&self) -> f32 {
+        self.height as f32 / self.width as f32
+    }
+}
+
+// How the colors of several points landing on one pixel are combined
+#[derive(Copy, Clone, PartialEq)]
+pub enum AggregationMode {
+    Nearest, // Closest point wins (depth test)
+    Mean,    // Average color
+    Max,     // Per-channel maximum
+    Sum,     // Accumulated light, saturating at white (density-style data)
+}
+
+impl AggregationMode {
+    pub fn from_string(s: &str) -> Option<AggregationMode> {
+        match s.to_lowercase().as_str() {
+            "nearest" => Some(AggregationMode::Nearest),
+            "mean" => Some(AggregationMode::Mean),
+            "max" => Some(AggregationMode::Max),
+            "sum" => Some(AggregationMode::Sum),
+            _ => None,
+        }
+    }
+}
+
+// How accumulated light is brought into displayable range, after exposure
+#[derive(Copy, Clone, PartialEq)]
+pub enum ToneMapping {
+    Clip,     // Cut off at full brightness, as without tone mapping
+    Reinhard, // x / (1 + x): never saturates, so bright regions keep their detail
+    Filmic,   // ACES-like S-curve: more contrast than Reinhard, highlights roll off
+}
+
+impl ToneMapping {
+    pub fn from_string(s: &str) -> Option<ToneMapping> {
+        match s.to_lowercase().as_str() {
+            "clip" => Some(ToneMapping::Clip),
+            "reinhard" => Some(ToneMapping::Reinhard),
+            "filmic" => Some(ToneMapping::Filmic),
+            _ => None,
+        }
+    }
+
+    // One linear channel into 0..1
+    fn map(&self, light: f32) -> f32 {
+        let mapped = match self {
+            ToneMapping::Clip => light,
+            ToneMapping::Reinhard => light / (1.0 + light),
+            // Krzysztof Narkowicz's fit of the ACES curve
+            ToneMapping::Filmic => {
+                (light * (2.51 * light + 0.03)) / (light * (2.43 * light + 0.59) + 0.14)
+            }
+        };
+        mapped.clamp(0.0, 1.0)
+    }
+}
+
+// How per-pixel point counts are mapped onto the color ramp
+#[derive(Copy, Clone, PartialEq)]
+pub enum DensityScale {
+    Linear,
+    Log, // Keeps sparse regions visible next to heavily overplotted ones
+}
+
+// A band of whole rows of a screen, which can be drawn on without touching
+// the rest of it, so that bands can be drawn on at the same time by
+// different threads. Pixels outside the band are left alone.
+pub struct ScreenTile<'a> {
+    viewport: Viewport, // Of the whole screen
+    first_row: usize,
+    content: &'a mut [Vec<bool>],
+    colors: &'a mut [Vec<Color>],
+    depth: &'a mut [Vec<f32>],
+    samples: &'a mut [Vec<([f32; 3], u32)>],
+    aggregation: AggregationMode,
+}
+
+impl ScreenTile<'_> {
+    // Like `Screen::write_with_depth`, in screen coordinates
+    pub fn write_with_depth(&mut self, point: &Point2D, color: Color, depth: f32) {
+        let rows = self.first_row..self.first_row + self.content.len();
+        if !self.viewport.contains(point) || !rows.contains(&(point.y as usize)) {
+            return;
+        }
+
+        let (x, y) = (point.x as usize, point.y as usize - self.first_row);
+        let (sum, count) = &mut self.samples[y][x];
+        let linear: [f32; 3] = color.into();
+        for (total, channel) in sum.iter_mut().zip(linear) {
+            *total += channel;
+        }
+        *count += 1;
+
+        let current = self.colors[y][x];
+        self.colors[y][x] = match self.aggregation {
+            AggregationMode::Nearest if depth <= self.depth[y][x] => color,
+            AggregationMode::Nearest => current,
+            // Uncolored points stay uncolored until something colored joins them
+            _ if *count == 1 || (color == Color::Default && current == Color::Default) => color,
+            AggregationMode::Mean => Color::from(sum.map(|total| total / *count as f32)),
+            AggregationMode::Sum => Color::from(*sum),
+            AggregationMode::Max => {
+                let current: [f32; 3] = current.into();
+                Color::from(array::from_fn::<f32, 3, _>(|i| current[i].max(linear[i])))
+            }
+        };
+
+        self.content[y][x] = true;
+        self.depth[y][x] = self.depth[y][x].min(depth);
+    }
+}
+
+// Screen wrapper with color support and performance optimizations
+pub struct Screen {
+    pub viewport: Viewport,
     content: Vec<Vec<bool>>,
-    colors: Vec<Vec<Color>>,                  // Store color for each pixel
-    cached_terminal_size: Option<(u16, u16)>, // Cache terminal size to avoid unnecessary checks
+    colors: Vec<Vec<Color>>,            // Store color for each pixel
+    depth: Vec<Vec<f32>>,               // Nearest point depth per pixel (infinite if unknown)
+    samples: Vec<Vec<([f32; 3], u32)>>, // Linear color sum and point count per pixel
+    coverage: Vec<Vec<f32>>,            // How much of each pixel smooth lines cover
+    pub aggregation: AggregationMode,
+    pub background: Option<Color>, // Colors too close to this are lifted (None = off)
+    pub smooth_lines: bool, // Anti-alias lines, for images; the terminal shows pixels on or off
+    pub color: bool,        // Print point colors (off prints everything in the default color)
+    pub ansi_256: bool,     // Print colors from the xterm palette instead of as 24-bit values
+    pub cell_size: (u16, u16), // Pixels per terminal cell when fitting the terminal
+    output: String,         // Reused by `render`
+    previous: Vec<Vec<(BraillePixel, Color)>>, // Cells on the terminal after the last `render`
 }
 
 impl Screen {
     pub fn new() -> Screen {
-        execute!(
-            io::stdout(),
-            cursor::MoveTo(0, 0),
-            terminal::Clear(terminal::ClearType::All)
-        )
-        .unwrap();
-
         Screen {
             content: Vec::new(),
             colors: Vec::new(),
-            width: 0,
-            height: 0,
-            cached_terminal_size: None,
+            depth: Vec::new(),
+            samples: Vec::new(),
+            coverage: Vec::new(),
+            aggregation: AggregationMode::Nearest,
+            background: Some(Color::Black),
+            smooth_lines: false,
+            color: true,
+            ansi_256: false,
+            cell_size: BRAILLE_CELL,
+            output: String::new(),
+            previous: Vec::new(),
+            viewport: Viewport::new(0, 0),
         }
     }
 
@@ -182,32 +636,194 @@ impl Screen {
             Err(_) => DEFAULT_TERMINAL_DIMENSIONS,
         };
 
-        // Only resize if terminal size has actually changed
-        if let Some((cached_width, cached_height)) = self.cached_terminal_size {
-            if cached_width == terminal_width && cached_height == terminal_height {
-                return; // No change, skip resize
+        // Resizing is a no-op when the terminal size hasn't changed
+        self.resize(Viewport::from_terminal(
+            terminal_width,
+            terminal_height,
+            self.cell_size,
+        ));
+    }
+
+    pub fn write(&mut self, val: bool, point: &Point2D) {
+        if self.viewport.contains(point) {
+            self.content[point.y as usize][point.x as usize] = val;
+        }
+    }
+
+    // Plot a pixel, combining its color with earlier points on the same pixel
+    // according to the aggregation mode, and remember the nearest depth
+    pub fn write_with_depth(&mut self, point: &Point2D, color: Color, depth: f32) {
+        self.tile().write_with_depth(point, color, depth);
+    }
+
+    // The whole screen as a single tile
+    fn tile(&mut self) -> ScreenTile<'_> {
+        ScreenTile {
+            viewport: self.viewport,
+            first_row: 0,
+            content: &mut self.content,
+            colors: &mut self.colors,
+            depth: &mut self.depth,
+            samples: &mut self.samples,
+            aggregation: self.aggregation,
+        }
+    }
+
+    // The screen cut into bands of `rows` rows each, top to bottom
+    pub fn tiles(&mut self, rows: usize) -> Vec<ScreenTile<'_>> {
+        let (viewport, aggregation, rows) = (self.viewport, self.aggregation, rows.max(1));
+        self.content
+            .chunks_mut(rows)
+            .zip(self.colors.chunks_mut(rows))
+            .zip(self.depth.chunks_mut(rows))
+            .zip(self.samples.chunks_mut(rows))
+            .enumerate()
+            .map(
+                |(index, (((content, colors), depth), samples))| ScreenTile {
+                    viewport,
+                    first_row: index * rows,
+                    content,
+                    colors,
+                    depth,
+                    samples,
+                    aggregation,
+                },
+            )
+            .collect()
+    }
+
+    // Draw a plus-shaped marker over everything, leaving the center pixel free
+    // so a point under it stays visible
+    pub fn draw_crosshair(&mut self, center: &Point2D, color: Color) {
+        for offset in (-CROSSHAIR_ARM..=CROSSHAIR_ARM).filter(|&offset| offset != 0) {
+            for pixel in [
+                Point2D::new(center.x + offset, center.y),
+                Point2D::new(center.x, center.y + offset),
+            ] {
+                if self.viewport.contains(&pixel) {
+                    let (x, y) = (pixel.x as usize, pixel.y as usize);
+                    self.content[y][x] = true;
+                    self.colors[y][x] = color;
+                    self.depth[y][x] = 0.0;
+                }
             }
         }
+    }
+
+    // Add light to a pixel regardless of the aggregation mode, for soft points
+    // whose overlaps should build up brightness
+    pub fn add_light(&mut self, point: &Point2D, light: [f32; 3], depth: f32) {
+        if !self.viewport.contains(point) {
+            return;
+        }
 
-        // Update cache and resize
-        self.cached_terminal_size = Some((terminal_width, terminal_height));
-        self.resize(terminal_width * 2, (terminal_height - 1) * 4);
+        let (x, y) = (point.x as usize, point.y as usize);
+        let (sum, count) = &mut self.samples[y][x];
+        for (total, channel) in sum.iter_mut().zip(light) {
+            *total += channel;
+        }
+        *count += 1;
+
+        self.colors[y][x] = Color::from(*sum);
+        self.content[y][x] = true;
+        self.depth[y][x] = self.depth[y][x].min(depth);
     }
 
-    pub fn write(&mut self, val: bool, point: &Point2D) {
-        let x_in_bounds = 0 < point.x && point.x < self.width as i32;
-        let y_in_bounds = 0 < point.y && point.y < self.height as i32;
-        if x_in_bounds && y_in_bounds {
-            self.content[point.y as usize][point.x as usize] = val;
+    // Density pass: recolor every lit pixel by how many points landed on it,
+    // relative to the busiest pixel in the frame
+    pub fn color_by_density(&mut self, scale: DensityScale) {
+        let max_count = self.samples.iter().flatten().map(|&(_, count)| count).max();
+        let Some(max_count) = max_count.filter(|&count| count > 0) else {
+            return;
+        };
+
+        let normalize = |count: u32| match scale {
+            DensityScale::Linear => count as f32 / max_count as f32,
+            DensityScale::Log => (count as f32).ln_1p() / (max_count as f32).ln_1p(),
+        };
+        for (color_row, sample_row) in self.colors.iter_mut().zip(&self.samples) {
+            for (color, &(_, count)) in color_row.iter_mut().zip(sample_row) {
+                if count > 0 {
+                    *color = Color::ramp(normalize(count));
+                }
+            }
         }
     }
 
-    pub fn write_colored(&mut self, val: bool, point: &Point2D, color: Color) {
-        let x_in_bounds = 0 < point.x && point.x < self.width as i32;
-        let y_in_bounds = 0 < point.y && point.y < self.height as i32;
-        if x_in_bounds && y_in_bounds {
-            self.content[point.y as usize][point.x as usize] = val;
-            self.colors[point.y as usize][point.x as usize] = color;
+    // Tone mapping pass, for additive drawing: recolor every lit pixel from the
+    // light summed on it, in full float precision, scaled by `exposure` and
+    // brought into range by `mapping` instead of clipping at full brightness
+    pub fn tone_map(&mut self, exposure: f32, mapping: ToneMapping) {
+        for (samples, colors) in self.samples.iter().zip(&mut self.colors) {
+            for (&(sum, count), color) in samples.iter().zip(colors) {
+                if count > 0 {
+                    *color = Color::from(sum.map(|light| mapping.map(light * exposure)));
+                }
+            }
+        }
+    }
+
+    // Depth map pass: shade every pixel with a known depth in grayscale, from
+    // white at the nearest point to dark gray at the farthest
+    pub fn color_by_depth(&mut self) {
+        let finite = || {
+            self.depth
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|d| d.is_finite())
+        };
+        let (Some(near), Some(far)) = (
+            finite().min_by(f32::total_cmp),
+            finite().max_by(f32::total_cmp),
+        ) else {
+            return;
+        };
+        let range = (far - near).max(f32::EPSILON);
+
+        for (color_row, depth_row) in self.colors.iter_mut().zip(&self.depth) {
+            for (color, &depth) in color_row.iter_mut().zip(depth_row) {
+                if depth.is_finite() {
+                    // Even steps in sRGB read as even steps in depth
+                    let level = 255.0 - (depth - near) / range * 215.0;
+                    let level = level.round() as u8;
+                    *color = Color::Rgb(level, level, level);
+                }
+            }
+        }
+    }
+
+    // Silhouette pass: switch off pixels lying just behind a nearer surface
+    // (more than `ratio` deeper than a neighbor), leaving a dark gap that
+    // separates overlapping structures
+    pub fn outline_edges(&mut self, ratio: f32) {
+        const RADIUS: i32 = 2; // Point clouds are sparse, so look past direct neighbors
+
+        let (width, height) = (self.viewport.width as i32, self.viewport.height as i32);
+        let mut edges = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let depth = self.depth[y as usize][x as usize];
+                if !self.content[y as usize][x as usize] || depth.is_infinite() {
+                    continue;
+                }
+
+                let behind = (-RADIUS..=RADIUS).any(|dy| {
+                    (-RADIUS..=RADIUS).any(|dx| {
+                        let (nx, ny) = (x + dx, y + dy);
+                        (0..width).contains(&nx)
+                            && (0..height).contains(&ny)
+                            && depth > self.depth[ny as usize][nx as usize] * (1.0 + ratio)
+                    })
+                });
+                if behind {
+                    edges.push((x as usize, y as usize));
+                }
+            }
+        }
+
+        for (x, y) in edges {
+            self.content[y][x] = false;
         }
     }
 
@@ -219,45 +835,48 @@ impl Screen {
         for row in &mut self.colors {
             row.fill(Color::Default);
         }
+        for row in &mut self.depth {
+            row.fill(f32::INFINITY);
+        }
+        for row in &mut self.samples {
+            row.fill(([0.0; 3], 0));
+        }
+        for row in &mut self.coverage {
+            row.fill(0.0);
+        }
     }
 
-    pub fn resize(&mut self, width: u16, height: u16) {
+    pub fn resize(&mut self, viewport: Viewport) {
         // Early return if size hasn't changed to avoid unnecessary work
-        if width == self.width && height == self.height {
+        if viewport == self.viewport {
             return;
         }
 
-        if height > self.height {
-            self.content.extend(vec![
-                vec![false; width as usize];
-                (height - self.height) as usize
-            ]);
-            self.colors.extend(vec![
-                vec![Color::Default; width as usize];
-                (height - self.height) as usize
-            ]);
-        } else {
-            self.content.truncate(height as usize);
-            self.colors.truncate(height as usize);
-        }
-        self.height = height;
+        let (width, height) = (viewport.width as usize, viewport.height as usize);
 
-        if width > self.width {
-            for row in self.content.iter_mut() {
-                row.extend(vec![false; (width - self.width) as usize]);
-            }
-            for row in self.colors.iter_mut() {
-                row.extend(vec![Color::Default; (width - self.width) as usize]);
-            }
-        } else {
-            for row in self.content.iter_mut() {
-                row.truncate(width as usize);
-            }
-            for row in self.colors.iter_mut() {
-                row.truncate(width as usize);
-            }
+        // Rows keep their contents; new cells start blank
+        self.content.resize(height, Vec::new());
+        self.colors.resize(height, Vec::new());
+        self.depth.resize(height, Vec::new());
+        self.samples.resize(height, Vec::new());
+        self.coverage.resize(height, Vec::new());
+        for row in &mut self.content {
+            row.resize(width, false);
+        }
+        for row in &mut self.colors {
+            row.resize(width, Color::Default);
         }
-        self.width = width;
+        for row in &mut self.depth {
+            row.resize(width, f32::INFINITY);
+        }
+        for row in &mut self.samples {
+            row.resize(width, ([0.0; 3], 0));
+        }
+        for row in &mut self.coverage {
+            row.resize(width, 0.0);
+        }
+
+        self.viewport = viewport;
     }
 
     pub fn line(&mut self, start: &Point2D, end: &Point2D) {
@@ -288,24 +907,134 @@ impl Screen {
         }
     }
 
-    pub fn render(&self) {
-        // Calculate approximate output size to pre-allocate string buffer
-        let num_rows = self.content.len().div_ceil(4);
-        let chars_per_row = self.width.div_ceil(2) + 2; // +2 for \r\n
-        let estimated_size = (num_rows * chars_per_row as usize) + 20; // +20 for cursor movement
+    // Anti-aliased line between two points in fractional pixels, with Wu's
+    // algorithm: at every step along the longer axis, the two pixels the line
+    // passes between are covered in proportion to how close it runs to them.
+    // Points drawn afterwards cover it as they do lines drawn with `line`.
+    pub fn smooth_line(&mut self, start: (f32, f32), end: (f32, f32)) {
+        let steep = (end.1 - start.1).abs() > (end.0 - start.0).abs();
+        // Walk along x, with x and y swapped for steep lines
+        let (mut start, mut end) = match steep {
+            true => ((start.1, start.0), (end.1, end.0)),
+            false => (start, end),
+        };
+        if start.0 > end.0 {
+            mem::swap(&mut start, &mut end);
+        }
+        let run = end.0 - start.0;
+        let gradient = if run > f32::EPSILON {
+            (end.1 - start.1) / run
+        } else {
+            0.0
+        };
+
+        for x in start.0.round() as i32..=end.0.round() as i32 {
+            let y = start.1 + gradient * (x as f32 - start.0);
+            let (row, fraction) = (y.floor(), y - y.floor());
+            for (y, amount) in [(row as i32, 1.0 - fraction), (row as i32 + 1, fraction)] {
+                let pixel = match steep {
+                    true => Point2D::new(y, x),
+                    false => Point2D::new(x, y),
+                };
+                if self.viewport.contains(&pixel) {
+                    let coverage = &mut self.coverage[pixel.y as usize][pixel.x as usize];
+                    *coverage = coverage.max(amount);
+                }
+            }
+        }
+    }
+
+    pub fn render(&mut self) {
+        // The output buffer is kept between frames so its capacity is reused
+        let mut output = mem::take(&mut self.output);
+        output.clear();
+
+        // Only changed cells are printed, unless the terminal may no longer
+        // show the last frame (first frame, resize or `invalidate`)
+        let cells = self.cells();
+        let same_size = self.previous.len() == cells.len()
+            && self.previous.first().map(Vec::len) == cells.first().map(Vec::len);
+        if same_size {
+            self.write_frame_diff(&cells, &mut output);
+        } else {
+            // Add cursor movement to beginning
+            output.push_str("\x1b[H"); // ANSI escape for cursor to home position
+            self.write_frame(&cells, &mut output);
+        }
+
+        // Output everything at once instead of many small writes
+        execute!(io::stdout(), style::Print(&output)).unwrap();
+        self.output = output;
+        self.previous = cells;
+    }
+
+    // Forget the last rendered frame so the next `render` redraws every cell,
+    // for when something else has drawn over the terminal
+    pub fn invalidate(&mut self) {
+        self.previous.clear();
+    }
+
+    // Color of the dot at (x, y), if anything was drawn there
+    pub fn dot(&self, x: usize, y: usize) -> Option<Color> {
+        let drawn = *self.content.get(y)?.get(x)?;
+        drawn.then(|| self.colors[y][x])
+    }
+
+    // The frame as an image with one pixel per braille dot, drawn over the
+    // background (black if unknown)
+    pub fn to_image(&self) -> RgbImage {
+        let (width, height) = (self.viewport.width as usize, self.viewport.height as usize);
+        let mut image = RgbImage::new(width, height, self.background.unwrap_or(Color::Black));
+        self.draw_onto(&mut image);
+        image
+    }
+
+    // Draw what's on the screen over an image, such as a photo, leaving its
+    // pixels where nothing was drawn
+    pub fn draw_onto(&self, image: &mut RgbImage) {
+        // Smooth lines blend into what's below, in linear light
+        let line: [f32; 3] = Color::Default.into();
+        for (y, (row, colors)) in self.content.iter().zip(&self.colors).enumerate() {
+            for (x, (&drawn, &color)) in row.iter().zip(colors).enumerate() {
+                let coverage = self.coverage[y][x];
+                if drawn {
+                    image.put(x, y, if self.color { color } else { Color::Default });
+                } else if coverage > 0.0
+                    && let Some(below) = image.get(x, y)
+                {
+                    let below: [f32; 3] = below.into();
+                    let blend = array::from_fn(|i| below[i] + (line[i] - below[i]) * coverage);
+                    image.put(x, y, Color::from(blend));
+                }
+            }
+        }
+    }
 
-        let mut output = String::with_capacity(estimated_size);
+    // The braille rows of the current frame, for printing outside the viewer
+    pub fn frame(&self) -> String {
+        let mut output = String::new();
+        self.frame_into(&mut output);
+        output
+    }
 
-        // Add cursor movement to beginning
-        output.push_str("\x1b[H"); // ANSI escape for cursor to home position
+    // Like `frame`, but writes into a caller-provided buffer (cleared first)
+    // so repeated renders don't allocate
+    pub fn frame_into(&self, output: &mut String) {
+        output.clear();
+        self.write_frame(&self.cells(), output);
+    }
 
+    // Terminal cells of the current frame, row by row, with the color each is
+    // printed in. Blank cells are given the default color so they compare
+    // equal regardless of what was under them.
+    fn cells(&self) -> Vec<Vec<(BraillePixel, Color)>> {
         let chunked_rows = self.content.chunks(4);
         let chunked_color_rows = self.colors.chunks(4);
 
-        let mut current_color = Color::Default;
+        let mut cells = Vec::with_capacity(self.content.len().div_ceil(4));
 
         for (subrows, color_subrows) in chunked_rows.zip(chunked_color_rows) {
-            let real_row_width = self.width.div_ceil(2) as usize;
+            let real_row_width = self.viewport.width.div_ceil(2) as usize;
             let mut real_row = vec![BraillePixel::new(); real_row_width];
             let mut real_row_colors = vec![Color::Default; real_row_width];
 
@@ -356,121 +1085,424 @@ impl Screen {
                 }
             }
 
-            // Render the row with color changes
-            for (pixel, &pixel_color) in real_row.iter().zip(real_row_colors.iter()) {
-                // Only change color if it's different from current
-                if pixel_color != current_color {
-                    let color_code = match pixel_color {
-                        Color::Default => "\x1b[39m".to_string(),
-                        Color::Black => "\x1b[30m".to_string(),
-                        Color::Red => "\x1b[31m".to_string(),
-                        Color::Green => "\x1b[32m".to_string(),
-                        Color::Yellow => "\x1b[33m".to_string(),
-                        Color::Blue => "\x1b[34m".to_string(),
-                        Color::Magenta => "\x1b[35m".to_string(),
-                        Color::Cyan => "\x1b[36m".to_string(),
-                        Color::White => "\x1b[37m".to_string(),
-                    };
-                    output.push_str(&color_code);
-                    current_color = pixel_color;
-                }
+            let row = real_row
+                .into_iter()
+                .zip(real_row_colors)
+                .map(|(pixel, color)| match self.background {
+                    _ if pixel.is_blank() || !self.color => (pixel, Color::Default),
+                    Some(background) => (pixel, color.with_contrast(background, MIN_CONTRAST)),
+                    None => (pixel, color),
+                })
+                .collect();
+            cells.push(row);
+        }
 
+        cells
+    }
+
+    fn write_frame(&self, cells: &[Vec<(BraillePixel, Color)>], output: &mut String) {
+        let mut current_color = Color::Default;
+
+        for row in cells {
+            for &(pixel, color) in row {
+                // Blank cells print in whatever color is active
+                if color != current_color && !pixel.is_blank() {
+                    write_color(output, color, self.ansi_256, false);
+                    current_color = color;
+                }
                 output.push(pixel.to_char());
             }
+
+            // Reset color at the end of every row so rows can be placed next
+            // to other frames' rows (split-screen layouts)
+            if current_color != Color::Default {
+                output.push_str("\x1b[39m");
+                current_color = Color::Default;
+            }
             output.push_str("\r\n");
         }
+    }
+
+    // Print only the cells that changed since the last frame `render` drew
+    fn write_frame_diff(&self, cells: &[Vec<(BraillePixel, Color)>], output: &mut String) {
+        let mut current_color = Color::Default;
+
+        for (y, (row, previous_row)) in cells.iter().zip(&self.previous).enumerate() {
+            // Column the cursor is at, if it's on this row
+            let mut cursor_x = None;
+            for (x, (cell, previous)) in row.iter().zip(previous_row).enumerate() {
+                if cell == previous {
+                    continue;
+                }
+                if cursor_x != Some(x) {
+                    let _ = write!(output, "\x1b[{};{}H", y + 1, x + 1);
+                }
+                let (pixel, color) = *cell;
+                if color != current_color && !pixel.is_blank() {
+                    write_color(output, color, self.ansi_256, false);
+                    current_color = color;
+                }
+                output.push(pixel.to_char());
+                cursor_x = Some(x + 1);
+            }
+        }
 
-        // Reset color at the end
         if current_color != Color::Default {
-            output.push_str("\x1b[39m"); // Reset to default color
+            output.push_str("\x1b[39m");
         }
+        // Leave the cursor below the frame, where a full redraw would
+        let _ = write!(output, "\x1b[{};1H", cells.len() + 1);
+    }
+}
 
-        // Output everything at once instead of many small writes
-        execute!(io::stdout(), style::Print(output)).unwrap();
+impl Default for Screen {
+    fn default() -> Screen {
+        Screen::new()
+    }
+}
+
+// Escape sequence switching the text color, or with `background` the color
+// behind the text
+pub fn write_color(output: &mut String, color: Color, ansi_256: bool, background: bool) {
+    let base = if background { 40 } else { 30 };
+    // Formatted in place to avoid an allocation per change
+    let _ = match color {
+        Color::Default => write!(output, "\x1b[{}m", base + 9),
+        Color::Black => write!(output, "\x1b[{}m", base),
+        Color::Red => write!(output, "\x1b[{}m", base + 1),
+        Color::Green => write!(output, "\x1b[{}m", base + 2),
+        Color::Yellow => write!(output, "\x1b[{}m", base + 3),
+        Color::Blue => write!(output, "\x1b[{}m", base + 4),
+        Color::Magenta => write!(output, "\x1b[{}m", base + 5),
+        Color::Cyan => write!(output, "\x1b[{}m", base + 6),
+        Color::White => write!(output, "\x1b[{}m", base + 7),
+        Color::Rgb(r, g, b) if ansi_256 => write!(
+            output,
+            "\x1b[{};5;{}m",
+            base + 8,
+            16 + quantize::xterm_index([r, g, b])
+        ),
+        Color::Rgb(r, g, b) => write!(output, "\x1b[{};2;{};{};{}m", base + 8, r, g, b),
+    };
+}
+
+// How a single point is drawn
+#[derive(Copy, Clone, PartialEq)]
+pub enum PointStyle {
+    Dot,                     // One pixel per point
+    Splat { radius: f32 }, // Filled disk of the given world radius, facing along the normal if known
+    Square { size: f32 },  // Screen-aligned filled square of the given world width
+    Gaussian { sigma: f32 }, // Soft blob with Gaussian falloff, added to the pixels' light
+}
+
+impl PointStyle {
+    // How far the drawn shape reaches from the point, in world units
+    pub fn reach(&self) -> f32 {
+        match *self {
+            PointStyle::Dot => 0.0,
+            PointStyle::Splat { radius } => radius,
+            PointStyle::Square { size } => size / 2.0,
+            PointStyle::Gaussian { sigma } => 2.0 * sigma, // Where it's cut off
+        }
     }
 }
 
 pub struct Camera {
     pub coordinates: Point3D,
-    pub yaw: f32,
-    pub pitch: f32,
-    pub roll: f32,
+    pub orientation: Quaternion, // Turn from world to camera space
     pub viewport_distance: f32,
     pub viewport_fov: f32,
     pub screen: Screen,
+    pub light: Option<[f32; 3]>, // Unit direction towards a directional light
+    pub point_style: PointStyle,
 }
 
 impl Camera {
-    pub fn new(
-        coordinates: Point3D,
-        yaw: f32,
-        pitch: f32,
-        roll: f32,
-        viewport_distance: f32,
-        viewport_fov: f32,
-    ) -> Camera {
+    // Facing into the screen (+z) with y up, until a view is applied
+    pub fn new(coordinates: Point3D, viewport_distance: f32, viewport_fov: f32) -> Camera {
         Camera {
             coordinates,
-            yaw,
-            pitch,
-            roll,
+            orientation: Quaternion::IDENTITY,
             viewport_distance,
             viewport_fov,
             screen: Screen::new(),
+            light: None,
+            point_style: PointStyle::Dot,
         }
     }
 
-    fn world_to_camera(&self, point: &Point3D) -> Point3D {
-        let (s_yaw, s_pitch, s_roll) = (self.yaw.sin(), self.pitch.sin(), self.roll.sin());
-        let (c_yaw, c_pitch, c_roll) = (self.yaw.cos(), self.pitch.cos(), self.roll.cos());
+    // What projecting depends on, worked out for the camera as it is now.
+    // Drawing takes this once and reuses it for every point, rather than
+    // recomputing the same sines, cosines and viewport size per point.
+    pub fn projection(&self) -> Projection {
+        let viewport_width = 2. * self.viewport_distance * (self.viewport_fov / 2.).tan();
+        let screen = self.screen.viewport;
+        Projection {
+            origin: [self.coordinates.x, self.coordinates.y, self.coordinates.z],
+            rotation: self.orientation.to_matrix(),
+            viewport_distance: self.viewport_distance,
+            viewport_size: (viewport_width, screen.aspect_ratio() * viewport_width),
+            screen_size: (screen.width as f32, screen.height as f32),
+        }
+    }
 
-        let delta_x = point.x - self.coordinates.x;
-        let delta_y = point.y - self.coordinates.y;
-        let delta_z = point.z - self.coordinates.z;
+    // Draw the cloud's axes and points onto the screen
+    pub fn draw_cloud(&mut self, point_cloud: &PointCloud) {
+        self.draw_axes(&point_cloud.axes);
+        self.draw_points(point_cloud, 1);
+    }
 
-        // Undo yaw
-        let unyawed_x = delta_x * c_yaw - delta_z * s_yaw;
-        let unyawed_y = delta_y;
-        let unyawed_z = delta_x * s_yaw + delta_z * c_yaw;
+    // Render axes with arrowheads and ticks
+    pub fn draw_axes(&mut self, axes: &[AxisDecoration]) {
+        let projection = self.projection();
+        for axis in axes {
+            // Draw main axis line
+            self.plot_projected_line(&projection, &axis.axis_line.0, &axis.axis_line.1);
 
-        // Undo pitch
-        let unpitched_x = unyawed_x;
-        let unpitched_y = unyawed_y * c_pitch - unyawed_z * s_pitch;
-        let unpitched_z = unyawed_y * s_pitch + unyawed_z * c_pitch;
+            // Draw arrowhead lines
+            for (start, end) in &axis.arrowhead_lines {
+                self.plot_projected_line(&projection, start, end);
+            }
 
-        // Undo roll
-        let unrolled_x = unpitched_x * c_roll - unpitched_y * s_roll;
-        let unrolled_y = unpitched_x * s_roll + unpitched_y * c_roll;
-        let unrolled_z = unpitched_z;
+            for (start, end) in &axis.tick_lines {
+                self.plot_projected_line(&projection, start, end);
+            }
+        }
+    }
 
-        Point3D::new_with_color(unrolled_x, unrolled_y, unrolled_z, point.color)
+    // Render every `stride`th visible point as a vertex (all of them for 1)
+    pub fn draw_points(&mut self, point_cloud: &PointCloud, stride: usize) {
+        let projection = self.projection();
+        for index in point_cloud.visible().step_by(stride.max(1)) {
+            let normal = point_cloud.normal(index);
+            self.plot_projected(&projection, &point_cloud.points[index], normal);
+        }
     }
 
-    fn camera_to_screen(&self, point: &Point3D) -> Point2D {
-        let viewport_x = point.x * self.viewport_distance / point.z;
-        let viewport_y = point.y * self.viewport_distance / point.z;
+    // Render the points in `range` from separate arrays. Single unshaded
+    // pixels only need the positions and colors; other styles get whole points
+    // back.
+    //
+    // Unshaded pixels are drawn by a thread per core in two steps: each thread
+    // projects a share of the points and sorts the pixels they land on by band
+    // of the screen, then each band is drawn by a thread of its own. Bands take
+    // the pixels in the points' order, so the result is the same as drawing
+    // them one after another.
+    pub fn draw_arrays(&mut self, arrays: &PointArrays, range: ops::Range<usize>) {
+        if self.point_style != PointStyle::Dot || self.light.is_some() {
+            let projection = self.projection();
+            for index in range {
+                let normal = arrays.normals[index];
+                self.plot_projected(&projection, &arrays.point(index), normal);
+            }
+            return;
+        }
 
-        let viewport_width = 2. * self.viewport_distance * (self.viewport_fov / 2.).tan();
-        let viewport_height =
-            (self.screen.height as f32 / self.screen.width as f32) * viewport_width;
+        let threads = thread::available_parallelism().map_or(1, num::NonZero::get);
+        let viewport = self.screen.viewport;
+        let band_rows = (viewport.height as usize).div_ceil(threads).max(1);
+        let bands = (viewport.height as usize).div_ceil(band_rows);
+        let share = range.len().div_ceil(threads).max(1);
+
+        let projection = self.projection();
+        let sorted: Vec<Vec<Vec<(Point2D, Color, f32)>>> = thread::scope(|scope| {
+            let workers: Vec<_> = arrays.positions[range.clone()]
+                .chunks(share)
+                .zip(arrays.colors[range].chunks(share))
+                .map(|(positions, colors)| {
+                    let projection = &projection;
+                    scope.spawn(move || {
+                        let mut by_band = vec![Vec::new(); bands];
+                        let mut projected = Vec::with_capacity(BATCH_POINTS);
+                        for (positions, colors) in positions
+                            .chunks(BATCH_POINTS)
+                            .zip(colors.chunks(BATCH_POINTS))
+                        {
+                            projection.project_batch(positions, &mut projected);
+                            for (&projected, &color) in projected.iter().zip(colors) {
+                                if let Some((pixel, depth)) = projected
+                                    && viewport.contains(&pixel)
+                                {
+                                    by_band[pixel.y as usize / band_rows]
+                                        .push((pixel, color, depth));
+                                }
+                            }
+                        }
+                        by_band
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        thread::scope(|scope| {
+            for (band, mut tile) in self.screen.tiles(band_rows).into_iter().enumerate() {
+                let sorted = &sorted;
+                scope.spawn(move || {
+                    for by_band in sorted {
+                        for (pixel, color, depth) in &by_band[band] {
+                            tile.write_with_depth(pixel, *color, *depth);
+                        }
+                    }
+                });
+            }
+        });
+    }
 
-        let screen_x = (viewport_x / viewport_width + 0.5) * self.screen.width as f32;
-        let screen_y = (1.0 - (viewport_y / viewport_height + 0.5)) * self.screen.height as f32;
+    // Index of the visible point drawn closest to a screen pixel (the nearest
+    // to the camera among equally close ones), if any is within PICK_RADIUS
+    pub fn pick(&self, point_cloud: &PointCloud, target: &Point2D) -> Option<usize> {
+        let projection = self.projection();
+        point_cloud
+            .visible()
+            .map(|index| (index, &point_cloud.points[index]))
+            .filter_map(|(index, point)| {
+                let camera_point = projection.to_camera(point);
+                if camera_point.z < self.viewport_distance {
+                    return None;
+                }
+                let pixel = projection.to_screen(&camera_point);
+                let distance = ((pixel.x - target.x) as f32).hypot((pixel.y - target.y) as f32);
+                (distance <= PICK_RADIUS).then_some((index, distance, camera_point.z))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)))
+            .map(|(index, _, _)| index)
+    }
 
-        Point2D::new(screen_x.round() as i32, screen_y.round() as i32)
+    // Render each point, with the projection worked out once for all of them
+    pub fn plot_points(&mut self, points: impl IntoIterator<Item = Point3D>) {
+        let projection = self.projection();
+        for point in points {
+            self.plot_projected(&projection, &point, None);
+        }
     }
 
-    pub fn plot_point(&mut self, point: &Point3D) {
-        let camera_point = self.world_to_camera(point);
-        if camera_point.z >= self.viewport_distance {
-            self.screen
-                .write_colored(true, &self.camera_to_screen(&camera_point), point.color);
+    // Draw a point, shaded and its splat tilted by `normal` if it has one
+    fn plot_projected(
+        &mut self,
+        projection: &Projection,
+        point: &Point3D,
+        normal: Option<[f32; 3]>,
+    ) {
+        let mut camera_point = projection.to_camera(point);
+        if camera_point.z + self.point_style.reach() < self.viewport_distance {
+            return;
+        }
+        // A shape reaching past the near plane from behind it is drawn on the
+        // plane, rather than disappearing as soon as its center crosses it.
+        // Shapes are clipped to the screen pixel by pixel, so ones centered
+        // just off screen still show the part that's on it.
+        camera_point.z = camera_point.z.max(self.viewport_distance);
+
+        let center = projection.to_screen(&camera_point);
+        let color = self.shade(point.color, normal);
+        match self.point_style {
+            PointStyle::Dot => self.screen.write_with_depth(&center, color, camera_point.z),
+            PointStyle::Splat { radius } => {
+                self.plot_splat(projection, normal, &camera_point, center, radius, color)
+            }
+            PointStyle::Gaussian { sigma } => {
+                let sigma = projection.to_pixels(sigma, camera_point.z).max(0.5);
+                let extent = (2.0 * sigma).ceil() as i32;
+                let linear: [f32; 3] = color.into();
+                for dy in -extent..=extent {
+                    for dx in -extent..=extent {
+                        // Cut off beyond two standard deviations
+                        let distance_squared = (dx * dx + dy * dy) as f32;
+                        if distance_squared > 4.0 * sigma * sigma {
+                            continue;
+                        }
+                        let weight = (-distance_squared / (2.0 * sigma * sigma)).exp();
+                        self.screen.add_light(
+                            &Point2D::new(center.x + dx, center.y + dy),
+                            linear.map(|channel| channel * weight),
+                            camera_point.z,
+                        );
+                    }
+                }
+            }
+            PointStyle::Square { size } => {
+                let extent = projection.to_pixels(size / 2.0, camera_point.z).round() as i32;
+                for dy in -extent..=extent {
+                    for dx in -extent..=extent {
+                        let pixel = Point2D::new(center.x + dx, center.y + dy);
+                        self.screen.write_with_depth(&pixel, color, camera_point.z);
+                    }
+                }
+            }
+        }
+    }
+
+    // Fill the ellipse a disk of `radius` around the point projects to
+    fn plot_splat(
+        &mut self,
+        projection: &Projection,
+        normal: Option<[f32; 3]>,
+        camera_point: &Point3D,
+        center: Point2D,
+        radius: f32,
+        color: Color,
+    ) {
+        let pixel_radius = projection.to_pixels(radius, camera_point.z);
+
+        // A tilted disk is squashed along the screen direction of its normal
+        let (minor_axis, squash) = match normal {
+            Some(normal) => {
+                let origin = projection.to_camera(&Point3D::new(0., 0., 0.));
+                let tip = projection.to_camera(&Point3D::new(normal[0], normal[1], normal[2]));
+                let (nx, ny, nz) = (tip.x - origin.x, tip.y - origin.y, tip.z - origin.z);
+                let length = (nx * nx + ny * ny).sqrt();
+                if length > f32::EPSILON {
+                    // Screen y points down
+                    ((nx / length, -ny / length), nz.abs().max(0.2))
+                } else {
+                    ((1.0, 0.0), 1.0)
+                }
+            }
+            None => ((1.0, 0.0), 1.0),
+        };
+
+        let extent = pixel_radius.ceil() as i32;
+        for dy in -extent..=extent {
+            for dx in -extent..=extent {
+                let (x, y) = (dx as f32, dy as f32);
+                let along = (x * minor_axis.0 + y * minor_axis.1) / squash;
+                let across = -x * minor_axis.1 + y * minor_axis.0;
+                if along * along + across * across <= pixel_radius * pixel_radius {
+                    self.screen.write_with_depth(
+                        &Point2D::new(center.x + dx, center.y + dy),
+                        color,
+                        camera_point.z,
+                    );
+                }
+            }
+        }
+    }
+
+    // Lambert shading against the light; normals are unoriented, so both
+    // sides of a surface are lit
+    fn shade(&self, color: Color, normal: Option<[f32; 3]>) -> Color {
+        match (self.light, normal) {
+            (Some(light), Some(normal)) => {
+                let lambert =
+                    (light[0] * normal[0] + light[1] * normal[1] + light[2] * normal[2]).abs();
+                color * (AMBIENT_LIGHT + (1.0 - AMBIENT_LIGHT) * lambert)
+            }
+            _ => color,
         }
     }
 
     pub fn plot_line(&mut self, start: &Point3D, end: &Point3D) {
-        let camera_start = self.world_to_camera(start);
-        let camera_end = self.world_to_camera(end);
+        let projection = self.projection();
+        self.plot_projected_line(&projection, start, end);
+    }
+
+    fn plot_projected_line(&mut self, projection: &Projection, start: &Point3D, end: &Point3D) {
+        let camera_start = projection.to_camera(start);
+        let camera_end = projection.to_camera(end);
         let clip_start = camera_start.z < self.viewport_distance;
         let clip_end = camera_end.z < self.viewport_distance;
 
@@ -479,10 +1511,7 @@ impl Camera {
         }
 
         if !clip_start && !clip_end {
-            self.screen.line(
-                &self.camera_to_screen(&camera_start),
-                &self.camera_to_screen(&camera_end),
-            );
+            self.draw_segment(projection, &camera_start, &camera_end);
             return;
         }
 
@@ -506,287 +1535,777 @@ impl Camera {
             clipped.color,
         );
 
-        self.screen.line(
-            &self.camera_to_screen(&new_clipped),
-            &self.camera_to_screen(&unclipped),
-        )
+        self.draw_segment(projection, &new_clipped, &unclipped);
+    }
+
+    // Line between two camera-space points in front of the near plane
+    fn draw_segment(&mut self, projection: &Projection, start: &Point3D, end: &Point3D) {
+        if self.screen.smooth_lines {
+            let exact = |point: &Point3D| {
+                let ([x], [y]) = projection.screen_lanes([[point.x], [point.y], [point.z]]);
+                (x, y)
+            };
+            self.screen.smooth_line(exact(start), exact(end));
+        } else {
+            self.screen
+                .line(&projection.to_screen(start), &projection.to_screen(end));
+        }
+    }
+}
+
+// A camera's view and its screen size, reduced to what mapping world
+// positions onto pixels needs. Only valid until the camera moves or the
+// screen is resized.
+pub struct Projection {
+    origin: [f32; 3],
+    rotation: [[f32; 3]; 3], // World to camera, by rows
+    viewport_distance: f32,
+    viewport_size: (f32, f32), // Of the viewport plane, in world units
+    screen_size: (f32, f32),
+}
+
+impl Projection {
+    pub fn to_camera(&self, point: &Point3D) -> Point3D {
+        let [[x], [y], [z]] = self.rotate_lanes([[point.x], [point.y], [point.z]]);
+        Point3D::new_with_color(x, y, z, point.color)
+    }
+
+    pub fn to_screen(&self, point: &Point3D) -> Point2D {
+        let ([x], [y]) = self.screen_lanes([[point.x], [point.y], [point.z]]);
+        Point2D::new(x.round() as i32, y.round() as i32)
+    }
+
+    // Screen pixel and depth of every position, in order, or None for those
+    // behind the near plane. Positions go through eight at a time with each
+    // coordinate of the eight in an array of its own, for the compiler to
+    // vectorize. On its own this is only slightly faster than projecting one
+    // point at a time (see benches/projection.rs).
+    pub fn project_batch(
+        &self,
+        positions: &[[f32; 3]],
+        projected: &mut Vec<Option<(Point2D, f32)>>,
+    ) {
+        projected.clear();
+        for chunk in positions.chunks(PROJECTION_LANES) {
+            // Short last chunks are padded with the camera's own position
+            let lanes: [[f32; PROJECTION_LANES]; 3] = array::from_fn(|axis| {
+                array::from_fn(|lane| chunk.get(lane).map_or(self.origin[axis], |p| p[axis]))
+            });
+            let camera = self.rotate_lanes(lanes);
+            let (xs, ys) = self.screen_lanes(camera);
+            projected.extend((0..chunk.len()).map(|lane| {
+                let depth = camera[2][lane];
+                (depth >= self.viewport_distance).then_some((
+                    Point2D::new(xs[lane].round() as i32, ys[lane].round() as i32),
+                    depth,
+                ))
+            }));
+        }
+    }
+
+    // World positions, one per lane, into camera space
+    fn rotate_lanes<const N: usize>(&self, [x, y, z]: [[f32; N]; 3]) -> [[f32; N]; 3] {
+        let mut camera = [[0.0; N]; 3];
+        for lane in 0..N {
+            let delta_x = x[lane] - self.origin[0];
+            let delta_y = y[lane] - self.origin[1];
+            let delta_z = z[lane] - self.origin[2];
+            for (axis, [along_x, along_y, along_z]) in self.rotation.into_iter().enumerate() {
+                camera[axis][lane] = delta_x * along_x + delta_y * along_y + delta_z * along_z;
+            }
+        }
+        camera
+    }
+
+    // Camera-space positions, one per lane, onto the screen in fractional
+    // pixels (pixel centers are whole numbers)
+    fn screen_lanes<const N: usize>(&self, [x, y, z]: [[f32; N]; 3]) -> ([f32; N], [f32; N]) {
+        let (viewport_width, viewport_height) = self.viewport_size;
+        let (screen_width, screen_height) = self.screen_size;
+
+        let (mut screen_xs, mut screen_ys) = ([0.0; N], [0.0; N]);
+        for lane in 0..N {
+            let viewport_x = x[lane] * self.viewport_distance / z[lane];
+            let viewport_y = y[lane] * self.viewport_distance / z[lane];
+
+            screen_xs[lane] = (viewport_x / viewport_width + 0.5) * screen_width;
+            screen_ys[lane] = (1.0 - (viewport_y / viewport_height + 0.5)) * screen_height;
+        }
+        (screen_xs, screen_ys)
+    }
+
+    // On-screen size of a world distance seen at `depth`, capped so that
+    // points right in front of the camera don't flood the screen
+    fn to_pixels(&self, distance: f32, depth: f32) -> f32 {
+        const MAX_POINT_PIXELS: f32 = 8.0;
+
+        let pixels_per_unit =
+            self.screen_size.0 * self.viewport_distance / (depth * self.viewport_size.0);
+        (distance * pixels_per_unit).min(MAX_POINT_PIXELS)
     }
 }
 
 pub struct AxisDecoration {
     pub axis_line: (Point3D, Point3D),
     pub arrowhead_lines: Vec<(Point3D, Point3D)>,
+    pub tick_lines: Vec<(Point3D, Point3D)>,
+}
+
+impl AxisDecoration {
+    pub fn length(&self) -> f32 {
+        let (start, end) = self.axis_line;
+        ((end.x - start.x).powi(2) + (end.y - start.y).powi(2) + (end.z - start.z).powi(2)).sqrt()
+    }
 }
 
+// Tick spacing of 1, 2 or 5 x 10^n giving about `target_count` ticks over `length`
+fn nice_tick_spacing(length: f32, target_count: f32) -> f32 {
+    let raw = length / target_count;
+    let magnitude = 10f32.powf(raw.log10().floor());
+    let nice = match raw / magnitude {
+        fraction if fraction < 1.5 => 1.0,
+        fraction if fraction < 3.5 => 2.0,
+        fraction if fraction < 7.5 => 5.0,
+        _ => 10.0,
+    };
+    nice * magnitude
+}
+
+// Points with optional per-point attributes kept beside them, each column
+// holding one value per point or absent altogether. The points are only
+// reached through accessors, so every column keeps in step with them.
+// Positions and colors stay together in 16-byte `Point3D`s, which editing
+// reads and writes together; the renderers that stream through positions
+// alone copy them into arrays of their own (see `PointArrays`).
 pub struct PointCloud {
-    pub points: Vec<Point3D>,
+    points: Vec<Point3D>,
+    normals: Option<Vec<[f32; 3]>>, // Unit surface normals, NaN for points without one
+    scalars: Option<Vec<f32>>,      // Values of the CSV scalar column, NaN for points without one
+    source_indices: Option<Vec<u32>>, // Index into `sources` of each point's file, or NO_SOURCE
+    hidden: Bitset,                 // Points kept in the cloud but not drawn
     pub axes: Vec<AxisDecoration>,
+    pub sources: Vec<String>, // Files the points were loaded from
+}
+
+// Stand-ins in the normals and source columns for points that don't have one
+const NO_NORMAL: [f32; 3] = [f32::NAN; 3];
+const NO_SOURCE: u32 = u32::MAX;
+
+// Joins the columns of two clouds of `len` and `other_len` points. The
+// result is absent only if both were; `missing` stands in for the values
+// of the cloud without the column.
+fn join_columns<T: Clone>(
+    column: &mut Option<Vec<T>>,
+    other: Option<Vec<T>>,
+    len: usize,
+    other_len: usize,
+    missing: T,
+) {
+    match (column.as_mut(), other) {
+        (None, None) => {}
+        (Some(values), other) => values.extend(other.unwrap_or_else(|| vec![missing; other_len])),
+        (None, Some(other)) => {
+            let mut values = vec![missing; len];
+            values.extend(other);
+            *column = Some(values);
+        }
+    }
+}
+
+// Keeps the values of a column whose entry in `keep` is true
+fn retain_column<T>(column: &mut Option<Vec<T>>, keep: &[bool]) {
+    if let Some(values) = column {
+        let mut keep = keep.iter();
+        values.retain(|_| *keep.next().unwrap());
+    }
 }
 
 impl PointCloud {
-    pub fn from_file(path: &str) -> Result<PointCloud, Box<dyn error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let mut points = Vec::new();
+    pub fn from_file(
+        path: &str,
+        csv_options: &CsvOptions,
+        coordinate_system: CoordinateSystem,
+    ) -> Result<PointCloud, Box<dyn error::Error>> {
+        let mut point_cloud = if let Some(loader) = loaders::find_loader(path) {
+            loader(path, csv_options)?
+        } else {
+            let content = fs::read_to_string(path)?;
+            let mut points = Vec::new();
 
-        for (line_num, line) in content.lines().enumerate() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
+            for (line_num, line) in content.lines().enumerate() {
+                points.extend(Self::parse_line(line, line_num)?);
             }
+            points.into_iter().collect()
+        };
+        point_cloud.convert(coordinate_system);
+        point_cloud.set_file(path);
+        point_cloud.axes = Self::generate_axes(&point_cloud.points);
 
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.is_empty() {
-                continue;
-            }
+        Ok(point_cloud)
+    }
 
-            match parts[0] {
-                "p" => {
-                    // Point format: p x y z [color]
-                    if parts.len() < 4 || parts.len() > 5 {
-                        return Err(format!(
-                            "Invalid point format on line {}: {}. Expected 'p x y z [color]'",
-                            line_num + 1,
-                            line
-                        )
-                        .into());
-                    }
+    pub fn new() -> PointCloud {
+        PointCloud {
+            points: Vec::new(),
+            normals: None,
+            scalars: None,
+            source_indices: None,
+            hidden: Bitset::new(0),
+            axes: Self::generate_axes(&[]),
+            sources: Vec::new(),
+        }
+    }
 
-                    let file_x: f32 = parts[1].parse().map_err(|_| {
-                        format!(
-                            "Invalid x coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[1]
-                        )
-                    })?;
-                    let file_y: f32 = parts[2].parse().map_err(|_| {
-                        format!(
-                            "Invalid y coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[2]
-                        )
-                    })?;
-                    let file_z: f32 = parts[3].parse().map_err(|_| {
-                        format!(
-                            "Invalid z coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[3]
-                        )
-                    })?;
+    pub fn points(&self) -> &[Point3D] {
+        &self.points
+    }
 
-                    // Parse optional color
-                    let color = if parts.len() == 5 {
-                        Color::from_string(parts[4]).unwrap_or(Color::Default)
-                    } else {
-                        Color::Default
-                    };
+    // The points to move or recolor in place; adding or removing points goes
+    // through the cloud, so the attributes stay with their points
+    pub fn points_mut(&mut self) -> &mut [Point3D] {
+        &mut self.points
+    }
 
-                    // Remap coordinates: file_z becomes viewer_y (up axis)
-                    points.push(Point3D::new_with_color(file_x, file_z, file_y, color));
-                }
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
 
-                "pc" => {
-                    // Colored point format: pc x y z color
-                    if parts.len() != 5 {
-                        return Err(format!("Invalid colored point format on line {}: {}. Expected 'pc x y z color'", line_num + 1, line).into());
-                    }
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
 
-                    let file_x: f32 = parts[1].parse().map_err(|_| {
-                        format!(
-                            "Invalid x coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[1]
-                        )
-                    })?;
-                    let file_y: f32 = parts[2].parse().map_err(|_| {
-                        format!(
-                            "Invalid y coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[2]
-                        )
-                    })?;
-                    let file_z: f32 = parts[3].parse().map_err(|_| {
-                        format!(
-                            "Invalid z coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[3]
-                        )
-                    })?;
+    // Add a point without attributes
+    pub fn push(&mut self, point: Point3D) {
+        self.points.push(point);
+        if let Some(normals) = &mut self.normals {
+            normals.push(NO_NORMAL);
+        }
+        if let Some(scalars) = &mut self.scalars {
+            scalars.push(f32::NAN);
+        }
+        if let Some(source_indices) = &mut self.source_indices {
+            source_indices.push(NO_SOURCE);
+        }
+        self.hidden.push(false);
+    }
 
-                    let color = Color::from_string(parts[4]).ok_or_else(|| {
-                        format!("Invalid color '{}' on line {}", parts[4], line_num + 1)
-                    })?;
+    // Drop every point, keeping the axes and sources
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.normals = None;
+        self.scalars = None;
+        self.source_indices = None;
+        self.hidden = Bitset::new(0);
+    }
 
-                    // Remap coordinates: file_z becomes viewer_y (up axis)
-                    points.push(Point3D::new_with_color(file_x, file_z, file_y, color));
-                }
+    // The unit surface normal of the point at `index`, if it has one
+    pub fn normal(&self, index: usize) -> Option<[f32; 3]> {
+        let normal = self.normals.as_ref()?[index];
+        (!normal[0].is_nan()).then_some(normal)
+    }
 
-                "l" => {
-                    // Line format: l x1 y1 z1 x2 y2 z2
-                    if parts.len() != 7 {
-                        return Err(format!(
-                            "Invalid line format on line {}: {}",
-                            line_num + 1,
-                            line
-                        )
-                        .into());
-                    }
+    // One normal per point, or None to drop them all
+    pub fn set_normals(&mut self, normals: Option<Vec<[f32; 3]>>) {
+        if let Some(normals) = &normals {
+            assert_eq!(normals.len(), self.points.len(), "one normal per point");
+        }
+        self.normals = normals;
+    }
 
-                    let x1: f32 = parts[1].parse().map_err(|_| {
-                        format!(
-                            "Invalid x1 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[1]
-                        )
-                    })?;
-                    let y1: f32 = parts[2].parse().map_err(|_| {
-                        format!(
-                            "Invalid y1 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[2]
-                        )
-                    })?;
-                    let z1: f32 = parts[3].parse().map_err(|_| {
-                        format!(
-                            "Invalid z1 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[3]
-                        )
-                    })?;
-                    let x2: f32 = parts[4].parse().map_err(|_| {
-                        format!(
-                            "Invalid x2 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[4]
-                        )
-                    })?;
-                    let y2: f32 = parts[5].parse().map_err(|_| {
-                        format!(
-                            "Invalid y2 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[5]
-                        )
-                    })?;
-                    let z2: f32 = parts[6].parse().map_err(|_| {
-                        format!(
-                            "Invalid z2 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[6]
-                        )
-                    })?;
+    // The scalar of the point at `index`, if it has one
+    pub fn scalar(&self, index: usize) -> Option<f32> {
+        let scalar = self.scalars.as_ref()?[index];
+        (!scalar.is_nan()).then_some(scalar)
+    }
 
-                    // Convert line to points using LINE_DENSITY
-                    let line_points = Self::line_to_points(
-                        Point3D::new(x1, z1, y1), // Remap coordinates
-                        Point3D::new(x2, z2, y2), // Remap coordinates
-                    );
-                    points.extend(line_points);
-                }
+    // One scalar per point (NaN for points without one), or None to drop them all
+    pub fn set_scalars(&mut self, scalars: Option<Vec<f32>>) {
+        if let Some(scalars) = &scalars {
+            assert_eq!(scalars.len(), self.points.len(), "one scalar per point");
+        }
+        self.scalars = scalars;
+    }
 
-                "lc" => {
-                    // Colored line format: lc x1 y1 z1 x2 y2 z2 color
-                    if parts.len() != 8 {
-                        return Err(format!("Invalid colored line format on line {}: {}. Expected 'lc x1 y1 z1 x2 y2 z2 color'", line_num + 1, line).into());
-                    }
+    // Index into `sources` of the file the point at `index` was loaded from
+    pub fn source(&self, index: usize) -> Option<usize> {
+        let source = self.source_indices.as_ref()?[index];
+        (source != NO_SOURCE).then_some(source as usize)
+    }
 
-                    let x1: f32 = parts[1].parse().map_err(|_| {
-                        format!(
-                            "Invalid x1 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[1]
-                        )
-                    })?;
-                    let y1: f32 = parts[2].parse().map_err(|_| {
-                        format!(
-                            "Invalid y1 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[2]
-                        )
-                    })?;
-                    let z1: f32 = parts[3].parse().map_err(|_| {
-                        format!(
-                            "Invalid z1 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[3]
-                        )
-                    })?;
-                    let x2: f32 = parts[4].parse().map_err(|_| {
-                        format!(
-                            "Invalid x2 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[4]
-                        )
-                    })?;
-                    let y2: f32 = parts[5].parse().map_err(|_| {
-                        format!(
-                            "Invalid y2 coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[5]
-                        )
-                    })?;
-                    let z2: f32 = parts[6].parse().map_err(|_| {
+    // Indices of the points loaded from `sources[source]`
+    pub fn source_points(&self, source: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.points.len()).filter(move |&index| self.source(index) == Some(source))
+    }
+
+    // Record that the point at `index` came from `sources[source]`, or from no file
+    pub fn set_source(&mut self, index: usize, source: Option<usize>) {
+        let len = self.points.len();
+        let source_indices = self
+            .source_indices
+            .get_or_insert_with(|| vec![NO_SOURCE; len]);
+        source_indices[index] = source.map_or(NO_SOURCE, |source| source as u32);
+    }
+
+    // Record that every point came from the file at `path`, the cloud's only source
+    pub fn set_file(&mut self, path: &str) {
+        self.sources = vec![path.to_string()];
+        self.source_indices = Some(vec![0; self.points.len()]);
+    }
+
+    // Move the cloud from `coordinate_system` into viewer coordinates
+    pub fn convert(&mut self, coordinate_system: CoordinateSystem) {
+        coordinate_system.convert(&mut self.points);
+        if let Some(normals) = &mut self.normals {
+            coordinate_system.convert_directions(normals);
+        }
+    }
+
+    // Move the cloud from viewer coordinates back into `coordinate_system`
+    pub fn convert_back(&mut self, coordinate_system: CoordinateSystem) {
+        coordinate_system.convert_back(&mut self.points);
+        if let Some(normals) = &mut self.normals {
+            coordinate_system.convert_directions_back(normals);
+        }
+    }
+
+    // A cloud of the points at `indices` with their attributes, the same
+    // sources and axes fitted to them
+    pub fn select(&self, indices: &[usize]) -> PointCloud {
+        let points: Vec<Point3D> = indices.iter().map(|&index| self.points[index]).collect();
+        let normals = self
+            .normals
+            .as_ref()
+            .map(|normals| indices.iter().map(|&index| normals[index]).collect());
+        let scalars = self
+            .scalars
+            .as_ref()
+            .map(|scalars| indices.iter().map(|&index| scalars[index]).collect());
+        let source_indices = self
+            .source_indices
+            .as_ref()
+            .map(|sources| indices.iter().map(|&index| sources[index]).collect());
+        let mut hidden = Bitset::new(0);
+        hidden.extend(indices.iter().map(|&index| self.hidden.get(index)));
+        PointCloud {
+            axes: Self::generate_axes(&points),
+            points,
+            normals,
+            scalars,
+            source_indices,
+            hidden,
+            sources: self.sources.clone(),
+        }
+    }
+
+    // Take `other`'s points and their attributes, keeping this cloud's axes
+    // and sources
+    pub fn replace_points(&mut self, other: &PointCloud) {
+        self.points.clone_from(&other.points);
+        self.normals.clone_from(&other.normals);
+        self.scalars.clone_from(&other.scalars);
+        self.source_indices.clone_from(&other.source_indices);
+        self.hidden.clone_from(&other.hidden);
+    }
+
+    // Move `other`'s attribute columns onto the end of this cloud's, ahead
+    // of appending its points
+    fn append_columns(&mut self, other: &mut PointCloud) {
+        let (len, other_len) = (self.points.len(), other.points.len());
+        join_columns(
+            &mut self.normals,
+            other.normals.take(),
+            len,
+            other_len,
+            NO_NORMAL,
+        );
+        join_columns(
+            &mut self.scalars,
+            other.scalars.take(),
+            len,
+            other_len,
+            f32::NAN,
+        );
+        join_columns(
+            &mut self.source_indices,
+            other.source_indices.take(),
+            len,
+            other_len,
+            NO_SOURCE,
+        );
+        self.hidden.extend(other.hidden.iter());
+    }
+
+    // Keep the points whose entry in `keep` is true, with their attributes
+    fn retain_mask(&mut self, keep: &[bool]) {
+        let mut flags = keep.iter();
+        self.points.retain(|_| *flags.next().unwrap());
+        retain_column(&mut self.normals, keep);
+        retain_column(&mut self.scalars, keep);
+        retain_column(&mut self.source_indices, keep);
+        self.hidden.retain(keep);
+    }
+
+    // Points in viewer coordinates, the color at the same index going to each.
+    // Panics unless there is one color per position.
+    pub fn from_positions_and_colors(positions: &[[f32; 3]], colors: &[Color]) -> PointCloud {
+        assert_eq!(positions.len(), colors.len(), "one color per position");
+        positions
+            .iter()
+            .zip(colors)
+            .map(|(&[x, y, z], &color)| Point3D::new_with_color(x, y, z, color))
+            .collect()
+    }
+
+    // Add another cloud's points, keeping track of the files they came from
+    pub fn append(&mut self, mut other: PointCloud) {
+        let offset = self.sources.len() as u32;
+        for source in other.source_indices.iter_mut().flatten() {
+            if *source != NO_SOURCE {
+                *source += offset;
+            }
+        }
+        self.append_columns(&mut other);
+        self.sources.extend(other.sources);
+        self.points.extend(other.points);
+        self.axes = Self::generate_axes(&self.points);
+    }
+
+    // Index into `sources` of the dataset called `name`: either its path as
+    // loaded or, if no other dataset shares it, just its file name
+    pub fn find_source(&self, name: &str) -> Result<usize, String> {
+        if let Some(index) = self.sources.iter().position(|source| source == name) {
+            return Ok(index);
+        }
+
+        let matches: Vec<usize> = (0..self.sources.len())
+            .filter(|&index| self.source_name(index) == name)
+            .collect();
+        match matches[..] {
+            [index] => Ok(index),
+            [] => Err(format!("No dataset named {}", name)),
+            _ => Err(format!(
+                "Several datasets are named {}; give the path",
+                name
+            )),
+        }
+    }
+
+    // Short name of a dataset, its file name
+    pub fn source_name(&self, index: usize) -> &str {
+        let path = path::Path::new(&self.sources[index]);
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.sources[index])
+    }
+
+    // Delete a dataset and its points; later datasets move down one index
+    pub fn remove_source(&mut self, index: usize) {
+        let keep: Vec<bool> = (0..self.points.len())
+            .map(|point| self.source(point) != Some(index))
+            .collect();
+        self.retain_mask(&keep);
+        for source in self.source_indices.iter_mut().flatten() {
+            if *source != NO_SOURCE && *source > index as u32 {
+                *source -= 1;
+            }
+        }
+        self.sources.remove(index);
+        self.axes = Self::generate_axes(&self.points);
+    }
+
+    // Read a dataset's file again and put its points in place of the old
+    // ones, hidden if the dataset was. Other datasets and streamed points
+    // are left as they are. On error nothing changes.
+    pub fn reload_source(
+        &mut self,
+        index: usize,
+        csv_options: &CsvOptions,
+        coordinate_system: CoordinateSystem,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let mut reloaded =
+            PointCloud::from_file(&self.sources[index], csv_options, coordinate_system)?;
+        let old: Vec<usize> = self.source_points(index).collect();
+        let hidden = !old.is_empty() && old.iter().all(|&point| self.hidden.get(point));
+
+        let keep: Vec<bool> = (0..self.points.len())
+            .map(|point| self.source(point) != Some(index))
+            .collect();
+        self.retain_mask(&keep);
+        reloaded.source_indices = Some(vec![index as u32; reloaded.points.len()]);
+        reloaded.hidden.fill(hidden);
+        self.append_columns(&mut reloaded);
+        self.points.extend(reloaded.points);
+        self.axes = Self::generate_axes(&self.points);
+        Ok(())
+    }
+
+    // Parse one line of the point file format into the points it describes,
+    // in file coordinates (see CoordinateSystem). Comments and blank lines
+    // produce no points.
+    pub fn parse_line(line: &str, line_num: usize) -> Result<Vec<Point3D>, Box<dyn error::Error>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(Vec::new());
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let points = match parts[0] {
+            "p" => {
+                // Point format: p x y z [color] | p x y z r g b
+                if !matches!(parts.len(), 4 | 5 | 7) {
+                    return Err(format!(
+                        "Invalid point format on line {}: {}. Expected 'p x y z [color]' or 'p x y z r g b'",
+                        line_num + 1,
+                        line
+                    )
+                    .into());
+                }
+
+                let file_x: f32 = parts[1].parse().map_err(|_| {
+                    format!(
+                        "Invalid x coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[1]
+                    )
+                })?;
+                let file_y: f32 = parts[2].parse().map_err(|_| {
+                    format!(
+                        "Invalid y coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[2]
+                    )
+                })?;
+                let file_z: f32 = parts[3].parse().map_err(|_| {
+                    format!(
+                        "Invalid z coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[3]
+                    )
+                })?;
+
+                // Parse optional color, by name or as 0-255 channels
+                let channel = |index: usize, name: &str| {
+                    parts[index].parse::<u8>().map_err(|_| {
                         format!(
-                            "Invalid z2 coordinate on line {}: {}",
+                            "Invalid {} channel on line {}: {} (expected 0-255)",
+                            name,
                             line_num + 1,
-                            parts[6]
+                            parts[index]
                         )
-                    })?;
+                    })
+                };
+                let color = match parts.len() {
+                    5 => Color::from_string(parts[4]).unwrap_or(Color::Default),
+                    7 => Color::Rgb(
+                        channel(4, "red")?,
+                        channel(5, "green")?,
+                        channel(6, "blue")?,
+                    ),
+                    _ => Color::Default,
+                };
+
+                vec![Point3D::new_with_color(file_x, file_y, file_z, color)]
+            }
+
+            "pc" => {
+                // Colored point format: pc x y z color
+                if parts.len() != 5 {
+                    return Err(format!(
+                        "Invalid colored point format on line {}: {}. Expected 'pc x y z color'",
+                        line_num + 1,
+                        line
+                    )
+                    .into());
+                }
 
-                    let color = Color::from_string(parts[7]).ok_or_else(|| {
-                        format!("Invalid color '{}' on line {}", parts[7], line_num + 1)
-                    })?;
+                let file_x: f32 = parts[1].parse().map_err(|_| {
+                    format!(
+                        "Invalid x coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[1]
+                    )
+                })?;
+                let file_y: f32 = parts[2].parse().map_err(|_| {
+                    format!(
+                        "Invalid y coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[2]
+                    )
+                })?;
+                let file_z: f32 = parts[3].parse().map_err(|_| {
+                    format!(
+                        "Invalid z coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[3]
+                    )
+                })?;
+
+                let color = Color::from_string(parts[4]).ok_or_else(|| {
+                    format!("Invalid color '{}' on line {}", parts[4], line_num + 1)
+                })?;
+
+                vec![Point3D::new_with_color(file_x, file_y, file_z, color)]
+            }
 
-                    // Convert line to colored points using LINE_DENSITY
-                    let line_points = Self::line_to_points(
-                        Point3D::new_with_color(x1, z1, y1, color), // Remap coordinates with color
-                        Point3D::new_with_color(x2, z2, y2, color), // Remap coordinates with color
+            "l" => {
+                // Line format: l x1 y1 z1 x2 y2 z2
+                if parts.len() != 7 {
+                    return Err(
+                        format!("Invalid line format on line {}: {}", line_num + 1, line).into(),
                     );
-                    points.extend(line_points);
                 }
 
-                _ => {
-                    // Legacy format: assume three numbers are x y z coordinates
-                    if parts.len() != 3 {
-                        return Err(format!("Invalid format on line {}: {}. Expected 'p x y z', 'pc x y z color', 'l x1 y1 z1 x2 y2 z2', 'lc x1 y1 z1 x2 y2 z2 color', or legacy 'x y z'", line_num + 1, line).into());
-                    }
-
-                    let file_x: f32 = parts[0].parse().map_err(|_| {
-                        format!(
-                            "Invalid x coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[0]
-                        )
-                    })?;
-                    let file_y: f32 = parts[1].parse().map_err(|_| {
-                        format!(
-                            "Invalid y coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[1]
-                        )
-                    })?;
-                    let file_z: f32 = parts[2].parse().map_err(|_| {
-                        format!(
-                            "Invalid z coordinate on line {}: {}",
-                            line_num + 1,
-                            parts[2]
-                        )
-                    })?;
+                let x1: f32 = parts[1].parse().map_err(|_| {
+                    format!(
+                        "Invalid x1 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[1]
+                    )
+                })?;
+                let y1: f32 = parts[2].parse().map_err(|_| {
+                    format!(
+                        "Invalid y1 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[2]
+                    )
+                })?;
+                let z1: f32 = parts[3].parse().map_err(|_| {
+                    format!(
+                        "Invalid z1 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[3]
+                    )
+                })?;
+                let x2: f32 = parts[4].parse().map_err(|_| {
+                    format!(
+                        "Invalid x2 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[4]
+                    )
+                })?;
+                let y2: f32 = parts[5].parse().map_err(|_| {
+                    format!(
+                        "Invalid y2 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[5]
+                    )
+                })?;
+                let z2: f32 = parts[6].parse().map_err(|_| {
+                    format!(
+                        "Invalid z2 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[6]
+                    )
+                })?;
+
+                // Convert line to points using LINE_DENSITY
+                Self::line_to_points(Point3D::new(x1, y1, z1), Point3D::new(x2, y2, z2))
+            }
 
-                    // Remap coordinates: file_z becomes viewer_y (up axis)
-                    points.push(Point3D::new(file_x, file_z, file_y));
+            "lc" => {
+                // Colored line format: lc x1 y1 z1 x2 y2 z2 color
+                if parts.len() != 8 {
+                    return Err(format!("Invalid colored line format on line {}: {}. Expected 'lc x1 y1 z1 x2 y2 z2 color'", line_num + 1, line).into());
                 }
+
+                let x1: f32 = parts[1].parse().map_err(|_| {
+                    format!(
+                        "Invalid x1 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[1]
+                    )
+                })?;
+                let y1: f32 = parts[2].parse().map_err(|_| {
+                    format!(
+                        "Invalid y1 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[2]
+                    )
+                })?;
+                let z1: f32 = parts[3].parse().map_err(|_| {
+                    format!(
+                        "Invalid z1 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[3]
+                    )
+                })?;
+                let x2: f32 = parts[4].parse().map_err(|_| {
+                    format!(
+                        "Invalid x2 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[4]
+                    )
+                })?;
+                let y2: f32 = parts[5].parse().map_err(|_| {
+                    format!(
+                        "Invalid y2 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[5]
+                    )
+                })?;
+                let z2: f32 = parts[6].parse().map_err(|_| {
+                    format!(
+                        "Invalid z2 coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[6]
+                    )
+                })?;
+
+                let color = Color::from_string(parts[7]).ok_or_else(|| {
+                    format!("Invalid color '{}' on line {}", parts[7], line_num + 1)
+                })?;
+
+                // Convert line to colored points using LINE_DENSITY
+                Self::line_to_points(
+                    Point3D::new_with_color(x1, y1, z1, color),
+                    Point3D::new_with_color(x2, y2, z2, color),
+                )
             }
-        }
 
-        let axes = Self::generate_axes(&points);
+            _ => {
+                // Legacy format: assume three numbers are x y z coordinates
+                if parts.len() != 3 {
+                    return Err(format!("Invalid format on line {}: {}. Expected 'p x y z', 'pc x y z color', 'l x1 y1 z1 x2 y2 z2', 'lc x1 y1 z1 x2 y2 z2 color', or legacy 'x y z'", line_num + 1, line).into());
+                }
 
-        Ok(PointCloud { points, axes })
+                let file_x: f32 = parts[0].parse().map_err(|_| {
+                    format!(
+                        "Invalid x coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[0]
+                    )
+                })?;
+                let file_y: f32 = parts[1].parse().map_err(|_| {
+                    format!(
+                        "Invalid y coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[1]
+                    )
+                })?;
+                let file_z: f32 = parts[2].parse().map_err(|_| {
+                    format!(
+                        "Invalid z coordinate on line {}: {}",
+                        line_num + 1,
+                        parts[2]
+                    )
+                })?;
+
+                vec![Point3D::new(file_x, file_y, file_z)]
+            }
+        };
+
+        Ok(points)
     }
 
     pub fn generate_axes_public(points: &[Point3D]) -> Vec<AxisDecoration> {
         Self::generate_axes(points)
     }
 
-    fn line_to_points(start: Point3D, end: Point3D) -> Vec<Point3D> {
+    pub fn line_to_points(start: Point3D, end: Point3D) -> Vec<Point3D> {
         // Calculate line length
         let dx = end.x - start.x;
         let dy = end.y - start.y;
@@ -829,28 +2348,108 @@ impl PointCloud {
                 .fold(0.0, f32::max);
 
             // Use minimum axis length or 110% of furthest point, whichever is larger
-            (furthest_point_distance * 1.1).max(MIN_AXIS_LENGTH)
+            (furthest_point_distance * AXIS_MARGIN).max(MIN_AXIS_LENGTH)
         };
 
+        Self::axes_with_length(max_distance)
+    }
+
+    // Axes reaching past the box from `get_percentile_bounds` instead of past
+    // every point, so outliers don't stretch them
+    pub fn percentile_axes(&self, percentile: f32) -> Vec<AxisDecoration> {
+        let (min, max) = self.get_percentile_bounds(percentile);
+        let reach = |low: f32, high: f32| low.abs().max(high.abs());
+        let corner = (reach(min.x, max.x).powi(2)
+            + reach(min.y, max.y).powi(2)
+            + reach(min.z, max.z).powi(2))
+        .sqrt();
+        Self::axes_with_length((corner * AXIS_MARGIN).max(MIN_AXIS_LENGTH))
+    }
+
+    // Axes from the origin along x, y and z, each `length` long
+    pub fn axes_with_length(length: f32) -> Vec<AxisDecoration> {
         let origin = Point3D::new(0., 0., 0.);
-        let x_end = Point3D::new(max_distance, 0., 0.);
-        let y_end = Point3D::new(0., max_distance, 0.);
-        let z_end = Point3D::new(0., 0., max_distance);
+        let x_end = Point3D::new(length, 0., 0.);
+        let y_end = Point3D::new(0., length, 0.);
+        let z_end = Point3D::new(0., 0., length);
 
         vec![
-            Self::create_axis_decoration(origin, x_end, max_distance),
-            Self::create_axis_decoration(origin, y_end, max_distance),
-            Self::create_axis_decoration(origin, z_end, max_distance),
+            Self::create_axis_decoration(origin, x_end, length),
+            Self::create_axis_decoration(origin, y_end, length),
+            Self::create_axis_decoration(origin, z_end, length),
         ]
     }
 
+    // Lines of a square grid on the horizontal plane through the origin,
+    // reaching `length` in every direction, spaced like the axis ticks
+    pub fn generate_grid(length: f32) -> Vec<(Point3D, Point3D)> {
+        let spacing = nice_tick_spacing(length, TARGET_TICK_COUNT);
+        let steps = (length / spacing).floor() as i32;
+        let reach = steps as f32 * spacing;
+
+        (-steps..=steps)
+            .flat_map(|step| {
+                let offset = step as f32 * spacing;
+                [
+                    (
+                        Point3D::new(-reach, 0., offset),
+                        Point3D::new(reach, 0., offset),
+                    ),
+                    (
+                        Point3D::new(offset, 0., -reach),
+                        Point3D::new(offset, 0., reach),
+                    ),
+                ]
+            })
+            .collect()
+    }
+
     fn create_axis_decoration(start: Point3D, end: Point3D, scale: f32) -> AxisDecoration {
         let arrowhead_lines = Self::generate_arrowhead(&start, &end, scale);
+        let tick_lines = Self::generate_ticks(&start, &end, scale);
 
         AxisDecoration {
             axis_line: (start, end),
             arrowhead_lines,
+            tick_lines,
+        }
+    }
+
+    // Short marks across the axis at "nice" multiples (1, 2 or 5 x 10^n)
+    fn generate_ticks(start: &Point3D, end: &Point3D, scale: f32) -> Vec<(Point3D, Point3D)> {
+        let (dx, dy, dz) = (end.x - start.x, end.y - start.y, end.z - start.z);
+        let length = (dx * dx + dy * dy + dz * dz).sqrt();
+        if length == 0.0 {
+            return vec![];
         }
+
+        let spacing = nice_tick_spacing(length, TARGET_TICK_COUNT);
+        let (dir_x, dir_y, dir_z) = (dx / length, dy / length, dz / length);
+
+        // Ticks cross vertical axes horizontally and all others vertically
+        let half = scale * 0.015;
+        let (across_x, across_y) = if dir_y.abs() > 0.9 {
+            (half, 0.0)
+        } else {
+            (0.0, half)
+        };
+
+        // Stop short of the arrowhead
+        let tick_count = ((length - scale * 0.05) / spacing).floor() as usize;
+        (1..=tick_count)
+            .map(|i| {
+                let distance = i as f32 * spacing;
+                let (x, y, z) = (
+                    start.x + dir_x * distance,
+                    start.y + dir_y * distance,
+                    start.z + dir_z * distance,
+                );
+                (
+                    Point3D::new(x - across_x, y - across_y, z),
+                    Point3D::new(x + across_x, y + across_y, z),
+                )
+            })
+            .collect()
     }
 
     fn generate_arrowhead(start: &Point3D, end: &Point3D, scale: f32) -> Vec<(Point3D, Point3D)> {
@@ -930,6 +2529,328 @@ impl PointCloud {
         vec![(*end, arrow1), (*end, arrow2)]
     }
 
+    // Decimate to one point per occupied voxel of `cell_size`, placed at the
+    // average position of the voxel's points with their average color. Each
+    // dataset, and the hidden points apart from the visible ones, are
+    // decimated on their own, so points keep their source and visibility.
+    pub fn voxel_downsample(&self, cell_size: f32) -> PointCloud {
+        struct Voxel {
+            source: Option<usize>,
+            hidden: bool,
+            sum: [f32; 3],
+            color_sum: [f32; 3],
+            first_color: Color,
+            mixed_colors: bool,
+            count: usize,
+        }
+
+        let mut voxel_index: HashMap<(i32, i32, i32, Option<usize>, bool), usize> = HashMap::new();
+        let mut voxels: Vec<Voxel> = Vec::new(); // In first-seen order
+        for (index, point) in self.points.iter().enumerate() {
+            let (source, hidden) = (self.source(index), self.hidden.get(index));
+            let key = (
+                (point.x / cell_size).floor() as i32,
+                (point.y / cell_size).floor() as i32,
+                (point.z / cell_size).floor() as i32,
+                source,
+                hidden,
+            );
+            let index = *voxel_index.entry(key).or_insert_with(|| {
+                voxels.push(Voxel {
+                    source,
+                    hidden,
+                    sum: [0.0; 3],
+                    color_sum: [0.0; 3],
+                    first_color: point.color,
+                    mixed_colors: false,
+                    count: 0,
+                });
+                voxels.len() - 1
+            });
+
+            let voxel = &mut voxels[index];
+            let linear: [f32; 3] = point.color.into();
+            for (sum, channel) in voxel.color_sum.iter_mut().zip(linear) {
+                *sum += channel;
+            }
+            voxel.sum[0] += point.x;
+            voxel.sum[1] += point.y;
+            voxel.sum[2] += point.z;
+            voxel.mixed_colors |= point.color != voxel.first_color;
+            voxel.count += 1;
+        }
+
+        let points: Vec<Point3D> = voxels
+            .iter()
+            .map(|voxel| {
+                let count = voxel.count as f32;
+                // Uniform voxels keep their color as-is (e.g. the terminal default)
+                let color = if voxel.mixed_colors {
+                    Color::from(voxel.color_sum.map(|channel| channel / count))
+                } else {
+                    voxel.first_color
+                };
+                Point3D::new_with_color(
+                    voxel.sum[0] / count,
+                    voxel.sum[1] / count,
+                    voxel.sum[2] / count,
+                    color,
+                )
+            })
+            .collect();
+        let source_indices = self.source_indices.as_ref().map(|_| {
+            voxels
+                .iter()
+                .map(|voxel| voxel.source.map_or(NO_SOURCE, |source| source as u32))
+                .collect()
+        });
+        let mut hidden = Bitset::new(0);
+        hidden.extend(voxels.iter().map(|voxel| voxel.hidden));
+
+        let axes = Self::generate_axes(&points);
+        PointCloud {
+            points,
+            normals: None,
+            scalars: None,
+            source_indices,
+            hidden,
+            axes,
+            sources: self.sources.clone(),
+        }
+    }
+
+    // Keep `count` points chosen uniformly at random (in their original order)
+    pub fn sample_random(&self, count: usize, seed: u64) -> PointCloud {
+        let mut rng = Rng::new(seed);
+        let mut indices: Vec<usize> = (0..self.points.len()).collect();
+        let count = count.min(indices.len());
+
+        // Partial Fisher-Yates shuffle: the first `count` slots are the sample
+        for i in 0..count {
+            let j = i + rng.below(indices.len() - i);
+            indices.swap(i, j);
+        }
+        indices.truncate(count);
+        indices.sort_unstable();
+        self.select(&indices)
+    }
+
+    // Keep every `step`-th point, starting with the first
+    pub fn sample_every(&self, step: usize) -> PointCloud {
+        let indices: Vec<usize> = (0..self.points.len()).step_by(step.max(1)).collect();
+        self.select(&indices)
+    }
+
+    pub fn is_hidden(&self, index: usize) -> bool {
+        self.hidden.get(index)
+    }
+
+    // Hide or show the point at `index` without removing it
+    pub fn set_hidden(&mut self, index: usize, hidden: bool) {
+        self.hidden.set(index, hidden);
+    }
+
+    // Indices of the points that are drawn, in order
+    pub fn visible(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.points.len()).filter(|&index| !self.hidden.get(index))
+    }
+
+    // The points that are drawn, in order
+    pub fn visible_points(&self) -> impl Iterator<Item = &Point3D> + '_ {
+        self.visible().map(|index| &self.points[index])
+    }
+
+    // Hide the points matching `predicate` without removing them
+    pub fn hide_where<F: FnMut(&Point3D) -> bool>(&mut self, mut predicate: F) {
+        for (index, point) in self.points.iter().enumerate() {
+            if predicate(point) {
+                self.hidden.set(index, true);
+            }
+        }
+    }
+
+    pub fn show_where<F: FnMut(&Point3D) -> bool>(&mut self, mut predicate: F) {
+        for (index, point) in self.points.iter().enumerate() {
+            if predicate(point) {
+                self.hidden.set(index, false);
+            }
+        }
+    }
+
+    // Hide or show every point loaded from `sources[source]`
+    pub fn set_source_hidden(&mut self, source: usize, hidden: bool) {
+        let points: Vec<usize> = self.source_points(source).collect();
+        for point in points {
+            self.hidden.set(point, hidden);
+        }
+    }
+
+    pub fn show_all(&mut self) {
+        self.hidden.fill(false);
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.points.len() - self.hidden.count_ones()
+    }
+
+    // Hide the points whose attribute lies outside `range`, or that don't have it
+    pub fn filter_attribute(
+        &mut self,
+        attribute: &str,
+        range: ops::RangeInclusive<f32>,
+    ) -> Result<(), String> {
+        let values = self.attribute(attribute)?;
+        for (index, value) in values.into_iter().enumerate() {
+            if !value.is_some_and(|value| range.contains(&value)) {
+                self.hidden.set(index, true);
+            }
+        }
+        Ok(())
+    }
+
+    // Darken points by an attribute while keeping their hues, so it can be
+    // layered over another coloring (e.g. intensity over height). The bottom
+    // of `range` gets MIN_BRIGHTNESS, the top keeps full brightness; points
+    // without the attribute are left alone.
+    pub fn modulate_brightness_by(
+        &mut self,
+        attribute: &str,
+        range: ops::RangeInclusive<f32>,
+    ) -> Result<(), String> {
+        let values = self.attribute(attribute)?;
+        let span = (range.end() - range.start()).max(f32::EPSILON);
+        for (point, value) in self.points.iter_mut().zip(values) {
+            if let Some(value) = value {
+                let t = ((value - range.start()) / span).clamp(0.0, 1.0);
+                point.color = point.color * (MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * t);
+            }
+        }
+        Ok(())
+    }
+
+    // Range of an attribute over the points that have it
+    pub fn attribute_range(&self, attribute: &str) -> Result<ops::RangeInclusive<f32>, String> {
+        let (min, max) = self
+            .attribute(attribute)?
+            .into_iter()
+            .flatten()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        Ok(min..=max)
+    }
+
+    // Each point's value of a named attribute: "scalar" (the CSV scalar
+    // column) or "height". Fails if no point has it.
+    fn attribute(&self, attribute: &str) -> Result<Vec<Option<f32>>, String> {
+        let values: Vec<Option<f32>> = match attribute {
+            "scalar" => (0..self.points.len())
+                .map(|index| self.scalar(index))
+                .collect(),
+            "height" => self.points.iter().map(|point| Some(point.y)).collect(),
+            _ => {
+                return Err(format!(
+                    "Unknown attribute: {} (expected scalar or height)",
+                    attribute
+                ));
+            }
+        };
+        if values.iter().all(Option::is_none) {
+            return Err(format!("No points have a {} value", attribute));
+        }
+        Ok(values)
+    }
+
+    // Attach a normal estimated from the neighbors within `radius` to every point
+    pub fn estimate_normals(&mut self, radius: f32) {
+        self.normals = Some(segmentation::estimate_normals(&self.points, radius));
+    }
+
+    // Median distance from a point to its nearest neighbor. The search radius
+    // starts small and doubles until most points have found a neighbor.
+    pub fn median_spacing(&self) -> f32 {
+        let diagonal = self.get_diagonal().max(f32::EPSILON);
+        let mut search_radius = diagonal * 0.01;
+
+        loop {
+            let grid = SpatialGrid::new(&self.points, search_radius);
+            let mut spacings: Vec<f32> = self
+                .points
+                .iter()
+                .enumerate()
+                .filter_map(|(index, point)| {
+                    grid.neighbors_within(point, search_radius)
+                        .into_iter()
+                        .filter(|&neighbor| neighbor != index)
+                        .map(|neighbor| {
+                            let other = &self.points[neighbor];
+                            ((other.x - point.x).powi(2)
+                                + (other.y - point.y).powi(2)
+                                + (other.z - point.z).powi(2))
+                            .sqrt()
+                        })
+                        .filter(|&distance| distance > 0.0)
+                        .min_by(f32::total_cmp)
+                })
+                .collect();
+
+            if spacings.len() * 2 >= self.points.len() && !spacings.is_empty() {
+                let middle = spacings.len() / 2;
+                return *spacings.select_nth_unstable_by(middle, f32::total_cmp).1;
+            }
+            if search_radius >= diagonal {
+                return search_radius;
+            }
+            search_radius *= 2.0;
+        }
+    }
+
+    // Keep only the points matching `predicate`
+    pub fn retain<F: FnMut(&Point3D) -> bool>(&mut self, predicate: F) {
+        let keep: Vec<bool> = self.points.iter().map(predicate).collect();
+        self.retain_mask(&keep);
+        self.axes = Self::generate_axes(&self.points);
+    }
+
+    // Keep the points inside the axis-aligned box spanned by `min` and `max`
+    pub fn crop_box(&mut self, min: Point3D, max: Point3D) {
+        self.retain(|p| {
+            (min.x..=max.x).contains(&p.x)
+                && (min.y..=max.y).contains(&p.y)
+                && (min.z..=max.z).contains(&p.z)
+        });
+    }
+
+    // Keep the points within `radius` of `center`
+    pub fn crop_sphere(&mut self, center: Point3D, radius: f32) {
+        self.retain(|p| {
+            (p.x - center.x).powi(2) + (p.y - center.y).powi(2) + (p.z - center.z).powi(2)
+                <= radius * radius
+        });
+    }
+
+    // Mirror the points (and their normals) along an axis
+    pub fn flip_axis(&mut self, axis: Axis) {
+        self.map_coordinates(|coordinates| coordinates[axis.index()] = -coordinates[axis.index()]);
+    }
+
+    // Exchange two coordinates of every point (and normal)
+    pub fn swap_axes(&mut self, a: Axis, b: Axis) {
+        self.map_coordinates(|coordinates| coordinates.swap(a.index(), b.index()));
+    }
+
+    fn map_coordinates<F: Fn(&mut [f32; 3])>(&mut self, transform: F) {
+        for point in &mut self.points {
+            let mut coordinates = [point.x, point.y, point.z];
+            transform(&mut coordinates);
+            [point.x, point.y, point.z] = coordinates;
+        }
+        for normal in self.normals.iter_mut().flatten() {
+            transform(normal);
+        }
+        self.axes = Self::generate_axes(&self.points);
+    }
+
     pub fn get_bounds(&self) -> (Point3D, Point3D) {
         if self.points.is_empty() {
             return (Point3D::new(0., 0., 0.), Point3D::new(0., 0., 0.));
@@ -951,6 +2872,35 @@ impl PointCloud {
         (min_bounds, max_bounds)
     }
 
+    // Like `get_bounds`, without the `percentile` percent of points beyond
+    // either end of each axis, so a single far-away outlier doesn't set the
+    // extent. Large clouds are sampled evenly to estimate the percentiles.
+    pub fn get_percentile_bounds(&self, percentile: f32) -> (Point3D, Point3D) {
+        if self.points.is_empty() {
+            return (Point3D::new(0., 0., 0.), Point3D::new(0., 0., 0.));
+        }
+
+        let step = self.points.len().div_ceil(PERCENTILE_SAMPLES);
+        let fraction = (percentile / 100.0).clamp(0.0, 0.5);
+        let bounds = |coordinate: fn(&Point3D) -> f32| {
+            let mut values: Vec<f32> = self.points.iter().step_by(step).map(coordinate).collect();
+            let last = values.len() - 1;
+            let low_index = (fraction * last as f32).round() as usize;
+            let high_index = last - low_index;
+            let low = *values.select_nth_unstable_by(low_index, f32::total_cmp).1;
+            let high = *values.select_nth_unstable_by(high_index, f32::total_cmp).1;
+            (low, high)
+        };
+        let (min_x, max_x) = bounds(|point| point.x);
+        let (min_y, max_y) = bounds(|point| point.y);
+        let (min_z, max_z) = bounds(|point| point.z);
+
+        (
+            Point3D::new(min_x, min_y, min_z),
+            Point3D::new(max_x, max_y, max_z),
+        )
+    }
+
     pub fn get_center(&self) -> Point3D {
         let (min_bounds, max_bounds) = self.get_bounds();
         Point3D::new(
@@ -968,3 +2918,155 @@ impl PointCloud {
         .sqrt()
     }
 }
+
+impl Default for PointCloud {
+    fn default() -> PointCloud {
+        PointCloud::new()
+    }
+}
+
+// A cloud of loose points, with axes to fit them and no source files
+impl FromIterator<Point3D> for PointCloud {
+    fn from_iter<I: IntoIterator<Item = Point3D>>(iter: I) -> PointCloud {
+        let points: Vec<Point3D> = iter.into_iter().collect();
+        PointCloud {
+            axes: PointCloud::generate_axes(&points),
+            hidden: Bitset::new(points.len()),
+            points,
+            normals: None,
+            scalars: None,
+            source_indices: None,
+            sources: Vec::new(),
+        }
+    }
+}
+
+// More loose points; the axes grow to fit them, as with `append`
+impl Extend<Point3D> for PointCloud {
+    fn extend<I: IntoIterator<Item = Point3D>>(&mut self, iter: I) {
+        self.points.extend(iter);
+        if let Some(normals) = &mut self.normals {
+            normals.resize(self.points.len(), NO_NORMAL);
+        }
+        if let Some(scalars) = &mut self.scalars {
+            scalars.resize(self.points.len(), f32::NAN);
+        }
+        if let Some(source_indices) = &mut self.source_indices {
+            source_indices.resize(self.points.len(), NO_SOURCE);
+        }
+        self.hidden.resize(self.points.len());
+        self.axes = PointCloud::generate_axes(&self.points);
+    }
+}
+
+// Positions in viewer coordinates, in the default color
+impl From<Vec<[f32; 3]>> for PointCloud {
+    fn from(positions: Vec<[f32; 3]>) -> PointCloud {
+        positions
+            .into_iter()
+            .map(|[x, y, z]| Point3D::new(x, y, z))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloading_a_source_keeps_other_points() {
+        let path = env::temp_dir().join(format!("graphics-{}.txt", process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, "p 1 1 1\np 2 2 2\n").unwrap();
+        let mut cloud = PointCloud::new();
+        cloud.append(
+            PointCloud::from_file(path, &CsvOptions::new(), CoordinateSystem::ZUp).unwrap(),
+        );
+        cloud.push(Point3D::new(9.0, 9.0, 9.0)); // Streamed, from no file
+        cloud.set_source_hidden(0, true);
+
+        fs::write(path, "p 3 3 3\n").unwrap();
+        let reloaded = cloud.reload_source(0, &CsvOptions::new(), CoordinateSystem::ZUp);
+        fs::remove_file(path).unwrap();
+        reloaded.unwrap();
+
+        let points: Vec<_> = (0..cloud.len())
+            .map(|index| {
+                let point = &cloud.points()[index];
+                (point.x, cloud.source(index), cloud.is_hidden(index))
+            })
+            .collect();
+        assert_eq!(points, [(9.0, None, false), (3.0, Some(0), true)]);
+    }
+
+    #[test]
+    fn sources_follow_append_and_remove() {
+        let file = |path: &str, x: f32| {
+            let mut cloud = PointCloud::from_iter([Point3D::new(x, 0.0, 0.0)]);
+            cloud.set_file(path);
+            cloud
+        };
+        let mut cloud = file("a.txt", 1.0);
+        cloud.push(Point3D::new(2.0, 0.0, 0.0));
+        cloud.append(file("b.txt", 3.0));
+        cloud.append(file("c.txt", 4.0));
+        cloud.remove_source(1);
+
+        let sources: Vec<_> = (0..cloud.len()).map(|index| cloud.source(index)).collect();
+        assert_eq!(sources, [Some(0), None, Some(1)]);
+        assert_eq!(cloud.sources, ["a.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn voxel_downsample_keeps_hidden_points_hidden() {
+        let mut cloud: PointCloud = [0.1, 0.2, 0.3, 5.0]
+            .map(|x| Point3D::new(x, 0.0, 0.0))
+            .into_iter()
+            .collect();
+        cloud.hide_where(|point| point.x == 0.3 || point.x == 5.0);
+
+        let downsampled = cloud.voxel_downsample(1.0);
+        let points: Vec<_> = (0..downsampled.len())
+            .map(|index| (downsampled.points()[index].x, downsampled.is_hidden(index)))
+            .collect();
+        assert_eq!(points, [(0.15, false), (0.3, true), (5.0, true)]);
+    }
+
+    #[test]
+    fn normals_follow_their_points() {
+        let mut cloud: PointCloud = (0..3).map(|i| Point3D::new(i as f32, 0.0, 0.0)).collect();
+        cloud.set_normals(Some(vec![
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]));
+        cloud.append(PointCloud::from_iter([Point3D::new(5.0, 0.0, 0.0)]));
+        cloud.retain(|point| point.x != 1.0);
+
+        let normals: Vec<_> = (0..cloud.len()).map(|index| cloud.normal(index)).collect();
+        assert_eq!(
+            normals,
+            [Some([1.0, 0.0, 0.0]), Some([0.0, 0.0, 1.0]), None]
+        );
+
+        // Moving into z-up coordinates turns the normals with the points
+        cloud.convert_back(CoordinateSystem::ZUp);
+        assert_eq!(cloud.normal(1), Some([0.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn color_light_adds_and_scales_saturating() {
+        let grey = Color::Rgb(128, 128, 128);
+        let light = Color::Rgb(200, 200, 200); // Over half of white in linear light
+        assert_eq!(light.saturating_add(light), Color::Rgb(255, 255, 255));
+        assert_ne!(grey.saturating_add(grey), Color::Rgb(255, 255, 255));
+        assert_eq!(
+            Color::Rgb(255, 0, 0) + Color::Rgb(0, 0, 255),
+            Color::Rgb(255, 0, 255)
+        );
+        assert_eq!(grey.scale(0.0), Color::Rgb(0, 0, 0));
+        assert_eq!(grey.scale(-1.0), Color::Rgb(0, 0, 0));
+        assert_eq!(grey * 10.0, Color::Rgb(255, 255, 255));
+        assert_eq!(grey.scale(1.0), grey);
+    }
+}
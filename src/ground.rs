@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+use crate::graphics::Point3D;
+
+// Grid-minimum ground classification for terrain (2.5D) data.
+// The horizontal plane is divided into square cells of `cell_size`; a point is
+// ground if it lies within `height_threshold` of the lowest point in its cell.
+pub fn classify_ground(points: &[Point3D], cell_size: f32, height_threshold: f32) -> Vec<bool> {
+    let cell_size = cell_size.max(f32::EPSILON);
+    let cell_of = |point: &Point3D| {
+        (
+            (point.x / cell_size).floor() as i32,
+            (point.z / cell_size).floor() as i32,
+        )
+    };
+
+    // Viewer y is the up axis
+    let mut lowest: HashMap<(i32, i32), f32> = HashMap::new();
+    for point in points {
+        let level = lowest.entry(cell_of(point)).or_insert(point.y);
+        *level = level.min(point.y);
+    }
+
+    points
+        .iter()
+        .map(|point| point.y - lowest[&cell_of(point)] <= height_threshold)
+        .collect()
+}
@@ -0,0 +1,55 @@
+use std::*;
+
+use collections::HashMap;
+
+use crate::graphics::{Color, Point3D};
+
+const MIN_HEAT: f32 = 0.01; // Cells that cool below this are forgotten
+
+// Where points have been arriving lately: each point warms the cell of a
+// world-space grid it lands in, and every cell cools off exponentially, so
+// bursts of e.g. detections show up as hot spots that fade away
+pub struct HeatMap {
+    cell_size: f32,
+    half_life: f32, // Seconds for a cell's heat to halve
+    cells: HashMap<[i32; 3], f32>,
+}
+
+impl HeatMap {
+    pub fn new(cell_size: f32, half_life: f32) -> HeatMap {
+        HeatMap {
+            cell_size,
+            half_life,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn add<'a>(&mut self, points: impl IntoIterator<Item = &'a Point3D>) {
+        for point in points {
+            let cell = [point.x, point.y, point.z].map(|c| (c / self.cell_size).floor() as i32);
+            *self.cells.entry(cell).or_insert(0.0) += 1.0;
+        }
+    }
+
+    pub fn decay(&mut self, dt: f32) {
+        let factor = 0.5f32.powf(dt / self.half_life);
+        self.cells.retain(|_, heat| {
+            *heat *= factor;
+            *heat >= MIN_HEAT
+        });
+    }
+
+    // A point at the center of every warm cell, colored by its heat relative
+    // to the hottest one on a log scale, so single recent points still show
+    pub fn points(&self) -> Vec<Point3D> {
+        let max_heat = self.cells.values().copied().fold(0.0, f32::max);
+        self.cells
+            .iter()
+            .map(|(cell, &heat)| {
+                let [x, y, z] = cell.map(|c| (c as f32 + 0.5) * self.cell_size);
+                let color = Color::ramp(heat.ln_1p() / max_heat.ln_1p());
+                Point3D::new_with_color(x, y, z, color)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,105 @@
+use std::*;
+
+use crate::graphics::Color;
+use crate::quantize;
+
+// Colors selected by SGR codes 30-37
+const ANSI_COLORS: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+// Standalone HTML page showing a rendered frame, for embedding in reports
+pub fn frame_to_html(frame: &str, background: Option<Color>) -> String {
+    let (r, g, b) = background.unwrap_or(Color::Black).to_rgb();
+    let (fr, fg, fb) = Color::Default.to_rgb();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>altostratus</title></head>\n<body>\n\
+         <pre style=\"background:#{:02x}{:02x}{:02x};color:#{:02x}{:02x}{:02x};line-height:1\">{}</pre>\n\
+         </body>\n</html>\n",
+        r,
+        g,
+        b,
+        fr,
+        fg,
+        fb,
+        ansi_to_html(frame)
+    )
+}
+
+// Convert text with ANSI color escapes into HTML with inline color spans.
+// Foreground colors (8-color, 24-bit and reset) are kept; other escape
+// sequences such as cursor movement are dropped.
+pub fn ansi_to_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len() * 2);
+    let mut span_open = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut sequence = String::new();
+                let mut command = None;
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        command = Some(c);
+                        break;
+                    }
+                    sequence.push(c);
+                }
+                if command != Some('m') {
+                    continue;
+                }
+
+                let Some(color) = sgr_color(&sequence) else {
+                    continue;
+                };
+                if span_open {
+                    html.push_str("</span>");
+                    span_open = false;
+                }
+                if let Some((r, g, b)) = color {
+                    html.push_str(&format!(
+                        "<span style=\"color:#{:02x}{:02x}{:02x}\">",
+                        r, g, b
+                    ));
+                    span_open = true;
+                }
+            }
+            '\r' => {}
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            c => html.push(c),
+        }
+    }
+
+    if span_open {
+        html.push_str("</span>");
+    }
+    html
+}
+
+// The foreground color an SGR sequence selects: Some(None) for a reset to the
+// default color, None if the sequence doesn't change the foreground
+fn sgr_color(sequence: &str) -> Option<Option<(u8, u8, u8)>> {
+    let codes: Vec<u8> = sequence
+        .split(';')
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+
+    match codes[..] {
+        [0] | [39] => Some(None),
+        [code @ 30..=37] => Some(Some(ANSI_COLORS[(code - 30) as usize].to_rgb())),
+        [38, 2, r, g, b] => Some(Some((r, g, b))),
+        [38, 5, index] => quantize::xterm_color(index).map(|[r, g, b]| Some((r, g, b))),
+        _ => None,
+    }
+}
@@ -0,0 +1,718 @@
+use std::*;
+
+use crate::graphics::Color;
+use crate::jpeg;
+use crate::quantize::{self, Quantizer};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+const HASH_SIZE: usize = 1 << 15; // Slots in the match finder's table
+const MAX_DISTANCE: usize = 32768; // Deflate window
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const GIF_CODE_LIMIT: u16 = 4096; // LZW codes are at most 12 bits
+const GIF_CLEAR: u16 = 256; // LZW codes after the 256 palette indices
+const GIF_END: u16 = 257;
+const GIF_COLORS: usize = 256;
+const BMP_HEADER_SIZE: usize = 54; // File header and info header
+const BMP_PIXELS_PER_METER: u32 = 2835; // 72 DPI
+const JPEG_QUALITY: u8 = 90;
+
+// File formats images are written in
+#[derive(Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    Png,  // Lossless and compressed, with room for text metadata
+    Bmp,  // Uncompressed, for tools that read nothing else
+    Jpeg, // Smallest, for sharing; single points come out softened
+}
+
+impl ImageFormat {
+    pub fn from_string(s: &str) -> Option<ImageFormat> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "bmp" => Some(ImageFormat::Bmp),
+            "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
+            _ => None,
+        }
+    }
+
+    // The format a file name's extension stands for, if any
+    pub fn from_path(path: &str) -> Option<ImageFormat> {
+        let extension = path::Path::new(path).extension()?.to_str()?;
+        ImageFormat::from_string(extension)
+    }
+}
+
+// 3x5 pixel glyphs, one row per entry (bit 2 is the leftmost pixel). Lowercase
+// letters are drawn as capitals; anything missing is left blank.
+const FONT: [(char, [u8; 5]); 47] = [
+    ('0', [7, 5, 5, 5, 7]),
+    ('1', [2, 6, 2, 2, 7]),
+    ('2', [7, 1, 7, 4, 7]),
+    ('3', [7, 1, 7, 1, 7]),
+    ('4', [5, 5, 7, 1, 1]),
+    ('5', [7, 4, 7, 1, 7]),
+    ('6', [7, 4, 7, 5, 7]),
+    ('7', [7, 1, 1, 1, 1]),
+    ('8', [7, 5, 7, 5, 7]),
+    ('9', [7, 5, 7, 1, 7]),
+    ('A', [2, 5, 7, 5, 5]),
+    ('B', [6, 5, 6, 5, 6]),
+    ('C', [3, 4, 4, 4, 3]),
+    ('D', [6, 5, 5, 5, 6]),
+    ('E', [7, 4, 6, 4, 7]),
+    ('F', [7, 4, 6, 4, 4]),
+    ('G', [3, 4, 5, 5, 3]),
+    ('H', [5, 5, 7, 5, 5]),
+    ('I', [7, 2, 2, 2, 7]),
+    ('J', [1, 1, 1, 5, 2]),
+    ('K', [5, 5, 6, 5, 5]),
+    ('L', [4, 4, 4, 4, 7]),
+    ('M', [5, 7, 7, 5, 5]),
+    ('N', [6, 5, 5, 5, 5]),
+    ('O', [2, 5, 5, 5, 2]),
+    ('P', [6, 5, 6, 4, 4]),
+    ('Q', [2, 5, 5, 6, 3]),
+    ('R', [6, 5, 6, 5, 5]),
+    ('S', [3, 4, 2, 1, 6]),
+    ('T', [7, 2, 2, 2, 2]),
+    ('U', [5, 5, 5, 5, 7]),
+    ('V', [5, 5, 5, 5, 2]),
+    ('W', [5, 5, 7, 7, 5]),
+    ('X', [5, 5, 2, 5, 5]),
+    ('Y', [5, 5, 2, 2, 2]),
+    ('Z', [7, 1, 2, 4, 7]),
+    ('.', [0, 0, 0, 0, 2]),
+    (',', [0, 0, 0, 2, 4]),
+    (':', [0, 2, 0, 2, 0]),
+    ('-', [0, 0, 7, 0, 0]),
+    ('+', [0, 2, 7, 2, 0]),
+    ('=', [0, 7, 0, 7, 0]),
+    ('/', [1, 1, 2, 4, 4]),
+    ('(', [1, 2, 2, 2, 1]),
+    (')', [4, 2, 2, 2, 4]),
+    ('%', [5, 1, 2, 4, 5]),
+    ('#', [5, 7, 5, 7, 5]),
+];
+
+// Deflate length codes 257-285: smallest length and extra bits
+const LENGTH_BASES: [(u16, u8); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+// Deflate distance codes 0-29: smallest distance and extra bits
+const DISTANCE_BASES: [(u16, u8); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+// 8-bit RGB raster, for writing renders to image files
+#[derive(Clone, PartialEq)]
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<[u8; 3]>, // Row by row from the top left
+}
+
+impl RgbImage {
+    pub fn new(width: usize, height: usize, background: Color) -> RgbImage {
+        let (r, g, b) = background.to_rgb();
+        RgbImage {
+            width,
+            height,
+            pixels: vec![[r, g, b]; width * height],
+        }
+    }
+
+    // Pixels outside the image are ignored
+    pub fn put(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            let (r, g, b) = color.to_rgb();
+            self.pixels[y * self.width + x] = [r, g, b];
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        for row in y..y + height {
+            for column in x..x + width {
+                self.put(column, row, color);
+            }
+        }
+    }
+
+    // The color at (x, y), or None outside the image
+    pub fn get(&self, x: usize, y: usize) -> Option<Color> {
+        if x < self.width && y < self.height {
+            let [r, g, b] = self.pixels[y * self.width + x];
+            Some(Color::Rgb(r, g, b))
+        } else {
+            None
+        }
+    }
+
+    // The part `width` x `height` pixels across from the top left corner
+    // (x, y); any of it past the edges is left out
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> RgbImage {
+        let width = width.min(self.width.saturating_sub(x));
+        let height = height.min(self.height.saturating_sub(y));
+        RgbImage {
+            width,
+            height,
+            pixels: (y..y + height)
+                .flat_map(|row| &self.pixels[row * self.width + x..][..width])
+                .copied()
+                .collect(),
+        }
+    }
+
+    // Copy another image over this one, aligned at the top left
+    pub fn paste(&mut self, other: &RgbImage) {
+        for y in 0..other.height.min(self.height) {
+            for x in 0..other.width.min(self.width) {
+                self.pixels[y * self.width + x] = other.pixels[y * other.width + x];
+            }
+        }
+    }
+
+    // Draw text with the built-in 3x5 font, each font pixel `scale` pixels
+    // wide, starting at the top left corner (x, y)
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, color: Color) {
+        for (index, c) in text.chars().enumerate() {
+            let Some((_, rows)) = FONT
+                .iter()
+                .find(|(glyph, _)| *glyph == c.to_ascii_uppercase())
+            else {
+                continue;
+            };
+            let left = x + index * text_advance(scale);
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (4 >> column) != 0 {
+                        self.fill_rect(left + column * scale, y + row * scale, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // Encode as a truecolor PNG
+    pub fn to_png(&self) -> Vec<u8> {
+        self.to_png_with_text(&[])
+    }
+
+    // Like `to_png`, with a text chunk for every keyword and value, which
+    // image viewers show as the file's properties. Keywords are 1 to 79
+    // Latin-1 characters.
+    pub fn to_png_with_text(&self, text: &[(&str, String)]) -> Vec<u8> {
+        // Every row starts with its filter type (0 = none)
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]); // 8-bit RGB, no interlacing
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        for (keyword, value) in text {
+            // Keyword, a zero byte, then the text
+            let chunk: Vec<u8> = [keyword.as_bytes(), &[0], value.as_bytes()].concat();
+            write_chunk(&mut png, b"tEXt", &chunk);
+        }
+        write_chunk(&mut png, b"IDAT", &zlib_compress(&raw));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    // Shrunk by a whole `factor`, each pixel the average of the block of
+    // `factor` x `factor` pixels it stands for, taken in linear light.
+    // Leftover rows and columns at the edges are dropped.
+    pub fn downsample(&self, factor: usize) -> RgbImage {
+        let factor = factor.max(1);
+        let (width, height) = (self.width / factor, self.height / factor);
+        let mut image = RgbImage::new(width, height, Color::Black);
+        let samples = (factor * factor) as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 3];
+                for row in y * factor..(y + 1) * factor {
+                    for &[r, g, b] in &self.pixels[row * self.width + x * factor..][..factor] {
+                        let linear: [f32; 3] = Color::Rgb(r, g, b).into();
+                        for (total, channel) in sum.iter_mut().zip(linear) {
+                            *total += channel;
+                        }
+                    }
+                }
+                image.put(x, y, Color::from(sum.map(|total| total / samples)));
+            }
+        }
+        image
+    }
+
+    pub fn pixels(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+
+    // Encode as an uncompressed 24-bit BMP
+    pub fn to_bmp(&self) -> Vec<u8> {
+        // Rows are stored bottom up, in BGR order, padded to whole 4 bytes
+        let row_size = (self.width * 3).div_ceil(4) * 4;
+        let data_size = row_size * self.height;
+        let mut bmp = Vec::with_capacity(BMP_HEADER_SIZE + data_size);
+        bmp.extend(b"BM");
+        bmp.extend(((BMP_HEADER_SIZE + data_size) as u32).to_le_bytes());
+        bmp.extend([0; 4]); // Reserved
+        bmp.extend((BMP_HEADER_SIZE as u32).to_le_bytes()); // Where the pixels start
+        bmp.extend(40u32.to_le_bytes()); // Size of the info header
+        bmp.extend((self.width as i32).to_le_bytes());
+        bmp.extend((self.height as i32).to_le_bytes());
+        bmp.extend(1u16.to_le_bytes()); // Planes
+        bmp.extend(24u16.to_le_bytes()); // Bits per pixel
+        bmp.extend(0u32.to_le_bytes()); // No compression
+        bmp.extend((data_size as u32).to_le_bytes());
+        bmp.extend(BMP_PIXELS_PER_METER.to_le_bytes());
+        bmp.extend(BMP_PIXELS_PER_METER.to_le_bytes());
+        bmp.extend([0; 8]); // No palette
+        for row in self
+            .pixels
+            .chunks(self.width.max(1))
+            .take(self.height)
+            .rev()
+        {
+            bmp.extend(row.iter().flat_map(|&[r, g, b]| [b, g, r]));
+            bmp.resize(bmp.len() + row_size - self.width * 3, 0);
+        }
+        bmp
+    }
+
+    // Read a PNG of any color type and bit depth, dropping transparency, as
+    // for photos to draw over
+    pub fn load_png(path: &str) -> Result<RgbImage, Box<dyn error::Error>> {
+        let mut decoder = png::Decoder::new(io::BufReader::new(fs::File::open(path)?));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().ok_or("image too large")?];
+        let info = reader.next_frame(&mut buffer)?;
+        // Palettes are expanded to RGB, and gray to one or two samples
+        let samples = reader.output_color_type().0.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks_exact(samples)
+            .map(|pixel| match samples {
+                1 | 2 => [pixel[0]; 3],
+                _ => [pixel[0], pixel[1], pixel[2]],
+            })
+            .collect();
+        Ok(RgbImage {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_png())
+    }
+
+    // Encode as a baseline JPEG at `quality` from 1 to 100, with the text as
+    // comments
+    pub fn to_jpeg(&self, quality: u8, text: &[(&str, String)]) -> Vec<u8> {
+        jpeg::encode(self, quality, text)
+    }
+
+    // Write in the given format, with the text as PNG metadata or JPEG
+    // comments (BMP files have nowhere to keep it)
+    pub fn save(&self, path: &str, format: ImageFormat, text: &[(&str, String)]) -> io::Result<()> {
+        match format {
+            ImageFormat::Png => fs::write(path, self.to_png_with_text(text)),
+            ImageFormat::Bmp => fs::write(path, self.to_bmp()),
+            ImageFormat::Jpeg => fs::write(path, self.to_jpeg(JPEG_QUALITY, text)),
+        }
+    }
+}
+
+// Looping animated GIF of frames shown for the given number of hundredths of
+// a second each. Frames are drawn from the top left of a canvas as large as
+// the largest frame. All frames share one palette, picked by `quantizer`, so
+// colors don't flicker from frame to frame.
+pub fn encode_gif(frames: &[(RgbImage, u16)], quantizer: &dyn Quantizer) -> Vec<u8> {
+    let width = frames
+        .iter()
+        .map(|(frame, _)| frame.width)
+        .max()
+        .unwrap_or(0);
+    let height = frames
+        .iter()
+        .map(|(frame, _)| frame.height)
+        .max()
+        .unwrap_or(0);
+    let histogram = quantize::histogram(frames.iter().map(|(frame, _)| frame));
+    let palette = quantizer.palette(&histogram, GIF_COLORS);
+    let indices: collections::HashMap<[u8; 3], u8> = histogram
+        .iter()
+        .map(|&(rgb, _)| (rgb, quantizer.index(&palette, rgb)))
+        .collect();
+
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend((width as u16).to_le_bytes());
+    gif.extend((height as u16).to_le_bytes());
+    gif.extend([0xF7, 0, 0]); // 256-color global palette
+    gif.extend(palette.colors.iter().flatten());
+    gif.extend(iter::repeat_n(0, (GIF_COLORS - palette.colors.len()) * 3));
+    // Loop forever
+    gif.extend(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for (frame, delay) in frames {
+        // Graphic control: clear to the background afterwards, in case the
+        // next frame is smaller
+        gif.extend([0x21, 0xF9, 0x04, 0x08]);
+        gif.extend(delay.to_le_bytes());
+        gif.extend([0, 0]);
+
+        gif.push(0x2C);
+        gif.extend([0, 0, 0, 0]);
+        gif.extend((frame.width as u16).to_le_bytes());
+        gif.extend((frame.height as u16).to_le_bytes());
+        gif.push(0);
+
+        let frame_indices: Vec<u8> = frame.pixels.iter().map(|rgb| indices[rgb]).collect();
+        gif.push(8); // Minimum code size
+        for block in lzw_compress(&frame_indices).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend(block);
+        }
+        gif.push(0);
+    }
+
+    gif.push(0x3B);
+    gif
+}
+
+// GIF flavor of LZW over 8-bit indices, variable code width from 9 to 12
+// bits, starting over with a clear code whenever the table fills up
+fn lzw_compress(indices: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        count: 0,
+    };
+    let mut table: collections::HashMap<(u16, u8), u16> = collections::HashMap::new();
+    let mut next_code = GIF_END + 1;
+    let mut code_size = 9;
+
+    writer.write(GIF_CLEAR as u32, code_size);
+    let Some((&first, rest)) = indices.split_first() else {
+        writer.write(GIF_END as u32, code_size);
+        return writer.finish();
+    };
+
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        writer.write(prefix as u32, code_size);
+        // The decoder adds its table entries one code behind, so the width
+        // grows after the code that fills the current range
+        if next_code > (1 << code_size) - 1 && code_size < 12 {
+            code_size += 1;
+        }
+        if next_code < GIF_CODE_LIMIT {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+        } else {
+            writer.write(GIF_CLEAR as u32, code_size);
+            table.clear();
+            next_code = GIF_END + 1;
+            code_size = 9;
+        }
+        prefix = index as u16;
+    }
+
+    writer.write(prefix as u32, code_size);
+    if next_code > (1 << code_size) - 1 && code_size < 12 {
+        code_size += 1;
+    }
+    writer.write(GIF_END as u32, code_size);
+    writer.finish()
+}
+
+// Horizontal distance between characters drawn with `draw_text`
+pub fn text_advance(scale: usize) -> usize {
+    (GLYPH_WIDTH + 1) * scale
+}
+
+pub fn text_height(scale: usize) -> usize {
+    GLYPH_HEIGHT * scale
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+// Bits packed least significant first, as deflate expects
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are stored most significant bit first
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// zlib stream of a single deflate block with the fixed Huffman codes. Matches
+// come from a table of the last position of every 3-byte sequence, which finds
+// the long runs of background pixels that make up most of a render.
+fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        bytes: vec![0x78, 0x01],
+        buffer: 0,
+        count: 0,
+    };
+    writer.write(1, 1); // Final block
+    writer.write(1, 2); // Fixed codes
+
+    let hash = |i: usize| {
+        let key = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+        key.wrapping_mul(2654435761) >> 7 & (HASH_SIZE - 1)
+    };
+    let mut last_seen = vec![usize::MAX; HASH_SIZE];
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut length = 0;
+        let mut distance = 0;
+        if i + MIN_MATCH <= data.len() {
+            let slot = hash(i);
+            let candidate = last_seen[slot];
+            last_seen[slot] = i;
+            if candidate != usize::MAX && i - candidate <= MAX_DISTANCE {
+                let limit = MAX_MATCH.min(data.len() - i);
+                while length < limit && data[candidate + length] == data[i + length] {
+                    length += 1;
+                }
+                distance = i - candidate;
+            }
+        }
+
+        if length >= MIN_MATCH {
+            write_length(&mut writer, length);
+            write_distance(&mut writer, distance);
+            // Keep the table current inside the match, cheaply
+            for position in (i + 1..i + length).filter(|p| p + MIN_MATCH <= data.len()) {
+                last_seen[hash(position)] = position;
+            }
+            i += length;
+        } else {
+            write_literal(&mut writer, data[i] as u32);
+            i += 1;
+        }
+    }
+    write_literal(&mut writer, 256); // End of block
+
+    let mut output = writer.finish();
+    output.extend(adler32(data).to_be_bytes());
+    output
+}
+
+fn write_literal(writer: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let code = LENGTH_BASES
+        .iter()
+        .rposition(|&(base, _)| base as usize <= length)
+        .unwrap();
+    let (base, extra) = LENGTH_BASES[code];
+    write_literal(writer, 257 + code as u32);
+    writer.write((length - base as usize) as u32, extra as u32);
+}
+
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let code = DISTANCE_BASES
+        .iter()
+        .rposition(|&(base, _)| base as usize <= distance)
+        .unwrap();
+    let (base, extra) = DISTANCE_BASES[code];
+    writer.write_code(code as u32, 5);
+    writer.write((distance - base as usize) as u32, extra as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quantize::MedianCut;
+
+    // Noisy enough to fill the GIF's LZW table and start it over, with few
+    // enough colors for the palette to keep them all
+    fn pattern(width: usize, height: usize, seed: usize) -> RgbImage {
+        let mut image = RgbImage::new(width, height, Color::Black);
+        for y in 0..height {
+            for x in 0..width {
+                let i = (x * 7 + y * 13 + seed) * 2654435761 % 200;
+                image.put(x, y, Color::Rgb(i as u8, 255 - i as u8, (i * 3) as u8));
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn png_decodes_to_the_same_pixels() {
+        let image = pattern(97, 40, 0);
+        let png = image.to_png_with_text(&[("Title", "pattern".to_string())]);
+        let decoder = png::Decoder::new(io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (97, 40));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(&buffer[..info.buffer_size()], image.pixels().as_flattened());
+    }
+
+    #[test]
+    fn gif_decodes_to_the_same_frames() {
+        let frames = [(pattern(120, 90, 0), 5), (pattern(60, 30, 1), 10)];
+        let gif = encode_gif(&frames, &MedianCut);
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(io::Cursor::new(gif)).unwrap();
+        for (image, delay) in &frames {
+            let frame = decoder.read_next_frame().unwrap().unwrap();
+            assert_eq!(
+                (frame.width as usize, frame.height as usize, frame.delay),
+                (image.width, image.height, *delay)
+            );
+            let rgb: Vec<[u8; 3]> = frame
+                .buffer
+                .chunks(4)
+                .map(|rgba| [rgba[0], rgba[1], rgba[2]])
+                .collect();
+            assert!(rgb == image.pixels());
+        }
+        assert!(decoder.read_next_frame().unwrap().is_none());
+    }
+}
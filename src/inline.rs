@@ -0,0 +1,181 @@
+use std::*;
+
+use fmt::Write;
+
+use collections::HashMap;
+
+use crate::image::RgbImage;
+use crate::quantize::{self, MedianCut, Quantizer};
+
+const KITTY_CHUNK: usize = 4096; // Largest base64 payload per kitty escape sequence
+const SIXEL_COLORS: usize = 256; // Palette registers most sixel terminals offer
+const SIXEL_BAND: usize = 6; // Pixel rows per sixel character
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Shows images in terminals that can draw pixels instead of characters
+pub trait Renderer {
+    // Escape sequences drawing `image` at the cursor, one terminal pixel per
+    // image pixel
+    fn encode(&self, image: &RgbImage) -> String;
+
+    // Escape sequences removing the images printed before, for protocols
+    // that keep them apart from the text they were printed over
+    fn erase(&self) -> &str {
+        ""
+    }
+}
+
+pub fn from_string(s: &str) -> Option<Box<dyn Renderer>> {
+    match s {
+        "kitty" => Some(Box::new(KittyRenderer)),
+        "iterm2" => Some(Box::new(ITermRenderer)),
+        "sixel" => Some(Box::new(SixelRenderer)),
+        _ => None,
+    }
+}
+
+// Kitty's graphics protocol (also understood by WezTerm, Ghostty and
+// Konsole): the image is sent as a PNG, split over escape sequences since
+// each may only carry a few kilobytes
+pub struct KittyRenderer;
+
+impl Renderer for KittyRenderer {
+    fn encode(&self, image: &RgbImage) -> String {
+        let payload = base64(&image.to_png());
+        let chunks: Vec<&str> = payload
+            .as_bytes()
+            .chunks(KITTY_CHUNK)
+            .map(|chunk| str::from_utf8(chunk).unwrap())
+            .collect();
+
+        let mut output = String::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            // Only the first chunk carries the image's settings: transmit and
+            // display a PNG, without replying to say so
+            let keys = if index == 0 { "a=T,f=100,q=2," } else { "" };
+            let more = (index + 1 < chunks.len()) as u8;
+            let _ = write!(output, "\x1b_G{}m={};{}\x1b\\", keys, more, chunk);
+        }
+        output
+    }
+
+    // Delete every placement along with its image data
+    fn erase(&self) -> &str {
+        "\x1b_Ga=d,d=A,q=2\x1b\\"
+    }
+}
+
+// iTerm2's inline images (also understood by WezTerm and mintty): one escape
+// sequence carrying the whole PNG
+pub struct ITermRenderer;
+
+impl Renderer for ITermRenderer {
+    fn encode(&self, image: &RgbImage) -> String {
+        let png = image.to_png();
+        format!(
+            "\x1b]1337;File=inline=1;size={};width={}px;height={}px;preserveAspectRatio=0:{}\x07",
+            png.len(),
+            image.width,
+            image.height,
+            base64(&png)
+        )
+    }
+}
+
+// DEC sixel graphics (xterm -ti vt340, mlterm, WezTerm, foot, ...): the
+// image is reduced to a median-cut palette, then sent in bands of six pixel
+// rows, one pass over each band per color in it
+pub struct SixelRenderer;
+
+impl Renderer for SixelRenderer {
+    fn encode(&self, image: &RgbImage) -> String {
+        let palette = MedianCut.palette(&quantize::histogram([image]), SIXEL_COLORS);
+        let mut indices: HashMap<[u8; 3], u8> = HashMap::new();
+        let pixels: Vec<u8> = image
+            .pixels()
+            .iter()
+            .map(|&rgb| {
+                *indices
+                    .entry(rgb)
+                    .or_insert_with(|| MedianCut.index(&palette, rgb))
+            })
+            .collect();
+
+        // Raster attributes give the size up front; palette channels are percentages
+        let mut output = format!("\x1bPq\"1;1;{};{}", image.width, image.height);
+        for (index, rgb) in palette.colors.iter().enumerate() {
+            let [r, g, b] = rgb.map(|channel| channel as u32 * 100 / 255);
+            let _ = write!(output, "#{};2;{};{};{}", index, r, g, b);
+        }
+
+        let mut row = vec![0u8; image.width];
+        for (band_index, band) in pixels.chunks(image.width * SIXEL_BAND).enumerate() {
+            if band_index > 0 {
+                output.push('-'); // Next band
+            }
+            let mut colors: Vec<u8> = band.to_vec();
+            colors.sort_unstable();
+            colors.dedup();
+            for (pass, &color) in colors.iter().enumerate() {
+                if pass > 0 {
+                    output.push('$'); // Back to the start of the band
+                }
+                row.fill(0);
+                for (bit, line) in band.chunks(image.width).enumerate() {
+                    for (bits, &pixel) in row.iter_mut().zip(line) {
+                        if pixel == color {
+                            *bits |= 1 << bit;
+                        }
+                    }
+                }
+                let _ = write!(output, "#{}", color);
+                write_sixel_runs(&mut output, &row);
+            }
+        }
+        output.push_str("\x1b\\");
+        output
+    }
+}
+
+// Sixel characters for one pass, with runs of the same one shortened to
+// `!count` followed by the character
+fn write_sixel_runs(output: &mut String, row: &[u8]) {
+    let mut index = 0;
+    while index < row.len() {
+        let bits = row[index];
+        let run = row[index..]
+            .iter()
+            .take_while(|&&other| other == bits)
+            .count();
+        let character = (b'?' + bits) as char;
+        if run > 3 {
+            let _ = write!(output, "!{}{}", run, character);
+        } else {
+            output.extend(iter::repeat_n(character, run));
+        }
+        index += run;
+    }
+}
+
+// Standard base64 with padding
+fn base64(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (bits >> (18 - 6 * index)) & 0x3F;
+                output.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
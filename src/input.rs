@@ -0,0 +1,360 @@
+use std::*;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
+
+use crate::arcball::Arcball;
+use crate::graphics::{BRAILLE_CELL, Point2D, Viewport};
+use crate::keys::{Action, KeyBindings};
+use crate::layout::Preset;
+use crate::quaternion::Quaternion;
+
+const MOUSE_SPEED_MULTIPLIER: f32 = 30.; // Radians of orbit per viewport width dragged
+const PAN_MULTIPLIER: f32 = 0.1; // Pan per drag, relative to the orbit it would make
+const SCROLL_MULTIPLIER: f32 = 0.03; // Zoom per scroll step as a fraction of the diagonal
+const KEY_ORBIT_STEP: f32 = 0.05; // Radians per key press
+const KEY_PAN_FRACTION: f32 = 0.02; // Pan per key press as a fraction of the diagonal
+pub const DEFAULT_FRICTION: f32 = 4.0; // Gliding slows by e every quarter second
+
+// What the user asked for, independent of the terminal events that said it.
+// Distances are fractions of the scene's diagonal, angles are in radians.
+#[derive(Copy, Clone, PartialEq)]
+pub enum InputEvent {
+    Orbit { yaw: f32, pitch: f32 },
+    Tumble(Quaternion), // Turn the scene about the center, in camera space
+    Hold(bool),         // A mouse button went down (true) or up while navigating
+    Pan { right: f32, up: f32 },
+    Zoom(f32), // Towards the center; negative moves away
+    View(ViewChange),
+    Pick(PickInput),
+    Command(CommandInput),
+    Playback(PlaybackInput),
+    Resize { columns: u16, rows: u16 },
+    CycleLayout,
+    LinkPanes,
+    ToggleAxes,
+    Quit,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ViewChange {
+    Reset,
+    Preset(Preset),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PickInput {
+    Toggle,
+    Point(Point2D),          // Put the crosshair on this terminal cell
+    Step { x: i32, y: i32 }, // Move the crosshair by whole cells
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PlaybackInput {
+    Toggle,
+    Step(i32), // Pause and move this many frames on, or back if negative
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CommandInput {
+    Open,
+    Insert(char),
+    Backspace,
+    Submit,
+    Cancel,
+}
+
+// What keys mean at the moment
+#[derive(Copy, Clone, PartialEq)]
+pub enum InputMode {
+    Navigate,
+    Pick,    // Arrow keys and the mouse move the crosshair
+    Command, // Keys edit the command line; the mouse is ignored
+}
+
+// How far the mouse moves the camera, as multipliers on the built-in speeds
+#[derive(Copy, Clone, PartialEq)]
+pub struct MouseSettings {
+    pub speed: f32, // Orbiting and panning by dragging
+    pub scroll_speed: f32,
+    pub invert_y: bool, // Orbiting by dragging up tilts the camera up instead of down
+    pub arcball: bool,  // Dragging rolls a virtual trackball instead of orbiting
+    pub friction: Option<f32>, // Keep the view gliding after input, slowing by this rate per second
+}
+
+impl MouseSettings {
+    pub fn new() -> MouseSettings {
+        MouseSettings {
+            speed: 1.0,
+            scroll_speed: 1.0,
+            invert_y: false,
+            arcball: false,
+            friction: None,
+        }
+    }
+}
+
+impl Default for MouseSettings {
+    fn default() -> MouseSettings {
+        MouseSettings::new()
+    }
+}
+
+// Turns crossterm events into input events. Drags are reported as they
+// happen, so the mapper keeps track of where the mouse was last seen.
+pub struct InputMapper {
+    key_bindings: KeyBindings,
+    last_mouse: Point2D,
+}
+
+impl InputMapper {
+    pub fn new(key_bindings: KeyBindings) -> InputMapper {
+        InputMapper {
+            key_bindings,
+            last_mouse: Point2D::new(0, 0),
+        }
+    }
+
+    // `viewport` is the main view's size, which drags are measured against
+    pub fn map(
+        &mut self,
+        event: &Event,
+        mode: InputMode,
+        viewport: Viewport,
+        mouse: MouseSettings,
+    ) -> Option<InputEvent> {
+        match event {
+            Event::Key(key_event) if mode == InputMode::Command => command_key(key_event),
+            Event::Key(key_event) => self.key(key_event, mode),
+            Event::Mouse(mouse_event) if mode != InputMode::Command => {
+                let cell = Point2D::new(mouse_event.column as i32, mouse_event.row as i32);
+                match mouse_event.kind {
+                    MouseEventKind::Moved if mode == InputMode::Pick => {
+                        Some(InputEvent::Pick(PickInput::Point(cell)))
+                    }
+                    MouseEventKind::Down(_) => {
+                        self.last_mouse = cell;
+                        Some(match mode {
+                            InputMode::Pick => InputEvent::Pick(PickInput::Point(cell)),
+                            _ => InputEvent::Hold(true),
+                        })
+                    }
+                    MouseEventKind::Up(_) if mode == InputMode::Navigate => {
+                        Some(InputEvent::Hold(false))
+                    }
+                    MouseEventKind::Drag(_) => {
+                        let scale =
+                            MOUSE_SPEED_MULTIPLIER * mouse.speed / viewport.width.max(1) as f32;
+                        let right = (cell.x - self.last_mouse.x) as f32 * scale;
+                        let up = (self.last_mouse.y - cell.y) as f32 * scale;
+                        let last = mem::replace(&mut self.last_mouse, cell);
+                        // Dragging with ctrl held pans instead of orbiting
+                        if mouse_event.modifiers == KeyModifiers::CONTROL {
+                            Some(InputEvent::Pan {
+                                right: right * PAN_MULTIPLIER,
+                                up: up * PAN_MULTIPLIER,
+                            })
+                        } else if mouse.arcball {
+                            let arcball = Arcball::new(viewport);
+                            let rotation = arcball.rotation(cell_middle(last), cell_middle(cell));
+                            Some(InputEvent::Tumble(rotation))
+                        } else {
+                            Some(InputEvent::Orbit {
+                                yaw: -right,
+                                pitch: if mouse.invert_y { up } else { -up },
+                            })
+                        }
+                    }
+                    MouseEventKind::ScrollUp => {
+                        Some(InputEvent::Zoom(SCROLL_MULTIPLIER * mouse.scroll_speed))
+                    }
+                    MouseEventKind::ScrollDown => {
+                        Some(InputEvent::Zoom(-SCROLL_MULTIPLIER * mouse.scroll_speed))
+                    }
+                    _ => None,
+                }
+            }
+            Event::Resize(columns, rows) => Some(InputEvent::Resize {
+                columns: *columns,
+                rows: *rows,
+            }),
+            _ => None,
+        }
+    }
+
+    fn key(&self, key_event: &KeyEvent, mode: InputMode) -> Option<InputEvent> {
+        let plain = key_event.modifiers == KeyModifiers::NONE;
+        match key_event.code {
+            KeyCode::Char('c') if key_event.modifiers == KeyModifiers::CONTROL => {
+                return Some(InputEvent::Quit);
+            }
+            KeyCode::Char('/') => return Some(InputEvent::Command(CommandInput::Open)),
+            KeyCode::Char('v') => return Some(InputEvent::CycleLayout),
+            KeyCode::Char('l') => return Some(InputEvent::LinkPanes),
+            KeyCode::Char('p') => return Some(InputEvent::Pick(PickInput::Toggle)),
+            // Arrows move the crosshair instead of the camera while picking
+            KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down
+                if mode == InputMode::Pick && plain =>
+            {
+                let (x, y) = match key_event.code {
+                    KeyCode::Left => (-1, 0),
+                    KeyCode::Right => (1, 0),
+                    KeyCode::Up => (0, -1),
+                    _ => (0, 1),
+                };
+                return Some(InputEvent::Pick(PickInput::Step { x, y }));
+            }
+            _ => {}
+        }
+
+        let event = match self.key_bindings.action(key_event)? {
+            Action::OrbitLeft => InputEvent::Orbit {
+                yaw: KEY_ORBIT_STEP,
+                pitch: 0.0,
+            },
+            Action::OrbitRight => InputEvent::Orbit {
+                yaw: -KEY_ORBIT_STEP,
+                pitch: 0.0,
+            },
+            Action::OrbitUp => InputEvent::Orbit {
+                yaw: 0.0,
+                pitch: -KEY_ORBIT_STEP,
+            },
+            Action::OrbitDown => InputEvent::Orbit {
+                yaw: 0.0,
+                pitch: KEY_ORBIT_STEP,
+            },
+            Action::ZoomIn => InputEvent::Zoom(SCROLL_MULTIPLIER),
+            Action::ZoomOut => InputEvent::Zoom(-SCROLL_MULTIPLIER),
+            Action::PanLeft => InputEvent::Pan {
+                right: KEY_PAN_FRACTION,
+                up: 0.0,
+            },
+            Action::PanRight => InputEvent::Pan {
+                right: -KEY_PAN_FRACTION,
+                up: 0.0,
+            },
+            Action::PanUp => InputEvent::Pan {
+                right: 0.0,
+                up: -KEY_PAN_FRACTION,
+            },
+            Action::PanDown => InputEvent::Pan {
+                right: 0.0,
+                up: KEY_PAN_FRACTION,
+            },
+            Action::ResetView => InputEvent::View(ViewChange::Reset),
+            Action::ViewTop => InputEvent::View(ViewChange::Preset(Preset::Top)),
+            Action::ViewFront => InputEvent::View(ViewChange::Preset(Preset::Front)),
+            Action::ViewSide => InputEvent::View(ViewChange::Preset(Preset::Side)),
+            Action::ViewIsometric => InputEvent::View(ViewChange::Preset(Preset::Isometric)),
+            Action::ToggleAxes => InputEvent::ToggleAxes,
+            Action::PlayPause => InputEvent::Playback(PlaybackInput::Toggle),
+            Action::StepForward => InputEvent::Playback(PlaybackInput::Step(1)),
+            Action::StepBack => InputEvent::Playback(PlaybackInput::Step(-1)),
+        };
+        Some(event)
+    }
+}
+
+// Braille pixel at the middle of a terminal cell, as the viewport measures
+fn cell_middle(cell: Point2D) -> (f32, f32) {
+    (
+        (cell.x as f32 + 0.5) * BRAILLE_CELL.0 as f32,
+        (cell.y as f32 + 0.5) * BRAILLE_CELL.1 as f32,
+    )
+}
+
+fn command_key(key_event: &KeyEvent) -> Option<InputEvent> {
+    let command = match key_event.code {
+        KeyCode::Esc => CommandInput::Cancel,
+        KeyCode::Enter => CommandInput::Submit,
+        KeyCode::Backspace => CommandInput::Backspace,
+        KeyCode::Char(c) => CommandInput::Insert(c),
+        _ => return None,
+    };
+    Some(InputEvent::Command(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::{MouseButton, MouseEvent};
+
+    const VIEWPORT: Viewport = Viewport {
+        width: 200,
+        height: 100,
+    };
+
+    fn key(code: KeyCode, modifiers: KeyModifiers, mode: InputMode) -> Option<InputEvent> {
+        let event = Event::Key(KeyEvent::new(code, modifiers));
+        InputMapper::new(KeyBindings::new()).map(&event, mode, VIEWPORT, MouseSettings::new())
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, modifiers: KeyModifiers) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row: 5,
+            modifiers,
+        })
+    }
+
+    #[test]
+    fn keys_map_by_mode() {
+        let none = KeyModifiers::NONE;
+        assert!(
+            key(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL,
+                InputMode::Navigate
+            ) == Some(InputEvent::Quit)
+        );
+        assert!(
+            key(KeyCode::Char('/'), none, InputMode::Navigate)
+                == Some(InputEvent::Command(CommandInput::Open))
+        );
+        // Typing a command doesn't also move the camera or quit
+        assert!(
+            key(KeyCode::Char('v'), none, InputMode::Command)
+                == Some(InputEvent::Command(CommandInput::Insert('v')))
+        );
+        assert!(
+            key(KeyCode::Esc, none, InputMode::Command)
+                == Some(InputEvent::Command(CommandInput::Cancel))
+        );
+        assert!(
+            key(KeyCode::Left, none, InputMode::Pick)
+                == Some(InputEvent::Pick(PickInput::Step { x: -1, y: 0 }))
+        );
+    }
+
+    #[test]
+    fn drags_orbit_or_pan_and_scrolling_zooms() {
+        let mut mapper = InputMapper::new(KeyBindings::new());
+        let settings = MouseSettings::new();
+        let mut map = |event: Event| mapper.map(&event, InputMode::Navigate, VIEWPORT, settings);
+        let none = KeyModifiers::NONE;
+        let left = MouseButton::Left;
+
+        assert!(map(mouse(MouseEventKind::Down(left), 10, none)) == Some(InputEvent::Hold(true)));
+        let scale = MOUSE_SPEED_MULTIPLIER / VIEWPORT.width as f32;
+        assert!(
+            map(mouse(MouseEventKind::Drag(left), 12, none))
+                == Some(InputEvent::Orbit {
+                    yaw: -2.0 * scale,
+                    pitch: 0.0,
+                })
+        );
+        assert!(
+            map(mouse(MouseEventKind::Drag(left), 13, KeyModifiers::CONTROL))
+                == Some(InputEvent::Pan {
+                    right: scale * PAN_MULTIPLIER,
+                    up: 0.0,
+                })
+        );
+        assert!(
+            map(mouse(MouseEventKind::ScrollDown, 13, none))
+                == Some(InputEvent::Zoom(-SCROLL_MULTIPLIER))
+        );
+    }
+}
@@ -0,0 +1,26 @@
+// Reading point files, for applications built on the library. A loader
+// registered here is used wherever a file is opened, so the viewer's /load
+// and the subcommands read a custom format once it's registered.
+//
+//     altostratus::io::register_loader("xyz", read_xyz);
+//     let cloud = PointCloud::from_file("scan.xyz", &CsvOptions::new(), CoordinateSystem::ZUp)?;
+//
+// watch_file follows a file for hot-reloading, as --watch does:
+//
+//     let mut watcher = altostratus::io::watch_file("scan.xyz");
+//     if !watcher.changed().is_empty() {
+//         cloud.reload_source(0, &CsvOptions::new(), CoordinateSystem::ZUp)?;
+//     }
+//
+// PointChunks reads a point file too big for memory a chunk at a time.
+
+pub use crate::chunks::PointChunks;
+pub use crate::csv::{CsvOptions, load_csv};
+pub use crate::graphics::{Color, CoordinateSystem, Point3D, PointCloud};
+pub use crate::loaders::{Loader, register_loader};
+pub use crate::watch::FileWatcher;
+
+// A watcher over one file; FileWatcher::changed lists it once it's modified
+pub fn watch_file(path: &str) -> FileWatcher {
+    FileWatcher::new(&[path.to_string()])
+}
@@ -0,0 +1,365 @@
+use std::*;
+
+use crate::image::RgbImage;
+
+// Baseline JPEG: 8x8 blocks of YCbCr at full resolution (no chroma
+// subsampling, which would smear colored single-pixel points), quantized by
+// the example tables of the standard scaled for quality, and coded with its
+// example Huffman tables
+const LUMA_QUANTIZATION: [u8; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, //
+    12, 12, 14, 19, 26, 58, 60, 55, //
+    14, 13, 16, 24, 40, 57, 69, 56, //
+    14, 17, 22, 29, 51, 87, 80, 62, //
+    18, 22, 37, 56, 68, 109, 103, 77, //
+    24, 35, 55, 64, 81, 104, 113, 92, //
+    49, 64, 78, 87, 103, 121, 120, 101, //
+    72, 92, 95, 98, 112, 100, 103, 99, //
+];
+const CHROMA_QUANTIZATION: [u8; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, //
+    18, 21, 26, 66, 99, 99, 99, 99, //
+    24, 26, 56, 99, 99, 99, 99, 99, //
+    47, 66, 99, 99, 99, 99, 99, 99, //
+    99, 99, 99, 99, 99, 99, 99, 99, //
+    99, 99, 99, 99, 99, 99, 99, 99, //
+    99, 99, 99, 99, 99, 99, 99, 99, //
+    99, 99, 99, 99, 99, 99, 99, 99, //
+];
+
+// Huffman tables as they're stored: how many codes there are of each length
+// from 1 to 16 bits, then the symbols in order of their codes
+const LUMA_DC_COUNTS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const CHROMA_DC_COUNTS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_SYMBOLS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const LUMA_AC_COUNTS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d];
+const LUMA_AC_SYMBOLS: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xa1, 0x08, 0x23, 0x42, 0xb1, 0xc1, 0x15, 0x52, 0xd1, 0xf0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0a, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2a, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7,
+    0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5,
+    0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe1, 0xe2,
+    0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+const CHROMA_AC_COUNTS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const CHROMA_AC_SYMBOLS: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xa1, 0xb1, 0xc1, 0x09, 0x23, 0x33, 0x52, 0xf0,
+    0x15, 0x62, 0x72, 0xd1, 0x0a, 0x16, 0x24, 0x34, 0xe1, 0x25, 0xf1, 0x17, 0x18, 0x19, 0x1a, 0x26,
+    0x27, 0x28, 0x29, 0x2a, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5,
+    0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3,
+    0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda,
+    0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+const END_OF_BLOCK: u8 = 0x00;
+const SIXTEEN_ZEROS: u8 = 0xf0;
+const MAX_SEGMENT: usize = 65533; // Bytes of data after a marker's length
+
+// One color channel's tables: quantizer steps in zigzag order, and the
+// Huffman code of every symbol as (code, length)
+struct Channel {
+    steps: [f32; 64],
+    dc: Vec<(u16, u8)>,
+    ac: Vec<(u16, u8)>,
+}
+
+// Encode at `quality` from 1 (smallest) to 100 (closest to the image), with a
+// comment for every keyword and value, as JPEG has no standard text fields
+pub fn encode(image: &RgbImage, quality: u8, text: &[(&str, String)]) -> Vec<u8> {
+    let zigzag = zigzag();
+    let luma_steps = scaled_steps(&LUMA_QUANTIZATION, quality, &zigzag);
+    let chroma_steps = scaled_steps(&CHROMA_QUANTIZATION, quality, &zigzag);
+
+    let mut jpeg = vec![0xFF, 0xD8];
+    // JFIF header: version 1.1, no units, square pixels, no thumbnail
+    segment(&mut jpeg, 0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+    for (keyword, value) in text {
+        let comment = format!("{}: {}", keyword, value);
+        let comment = &comment.as_bytes()[..comment.len().min(MAX_SEGMENT)];
+        segment(&mut jpeg, 0xFE, comment);
+    }
+    for (id, steps) in [(0, &luma_steps), (1, &chroma_steps)] {
+        let mut table = vec![id];
+        table.extend(steps);
+        segment(&mut jpeg, 0xDB, &table);
+    }
+
+    // Frame: 8-bit samples, the size, then three channels of one sample per
+    // pixel each, luma on table 0 and chroma on table 1
+    let mut frame = vec![8];
+    frame.extend((image.height as u16).to_be_bytes());
+    frame.extend((image.width as u16).to_be_bytes());
+    frame.extend([3, 1, 0x11, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    segment(&mut jpeg, 0xC0, &frame);
+
+    // Huffman tables: class (0 DC, 1 AC) in the high nibble, id in the low
+    let tables: [(u8, &[u8; 16], &[u8]); 4] = [
+        (0x00, &LUMA_DC_COUNTS, &DC_SYMBOLS),
+        (0x10, &LUMA_AC_COUNTS, &LUMA_AC_SYMBOLS),
+        (0x01, &CHROMA_DC_COUNTS, &DC_SYMBOLS),
+        (0x11, &CHROMA_AC_COUNTS, &CHROMA_AC_SYMBOLS),
+    ];
+    for (class, counts, symbols) in tables {
+        let table: Vec<u8> = [&[class][..], counts, symbols].concat();
+        segment(&mut jpeg, 0xC4, &table);
+    }
+    segment(&mut jpeg, 0xDA, &[3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+
+    let channels = [
+        Channel {
+            steps: luma_steps.map(f32::from),
+            dc: huffman_codes(&LUMA_DC_COUNTS, &DC_SYMBOLS),
+            ac: huffman_codes(&LUMA_AC_COUNTS, &LUMA_AC_SYMBOLS),
+        },
+        Channel {
+            steps: chroma_steps.map(f32::from),
+            dc: huffman_codes(&CHROMA_DC_COUNTS, &DC_SYMBOLS),
+            ac: huffman_codes(&CHROMA_AC_COUNTS, &CHROMA_AC_SYMBOLS),
+        },
+    ];
+    let mut writer = JpegWriter {
+        bytes: jpeg,
+        buffer: 0,
+        count: 0,
+    };
+    let mut previous_dc = [0; 3];
+    let cosines: [[f32; 8]; 8] = array::from_fn(|x| {
+        array::from_fn(|u| ((2 * x + 1) as f32 * u as f32 * f32::consts::PI / 16.0).cos())
+    });
+    for top in (0..image.height).step_by(8) {
+        for left in (0..image.width).step_by(8) {
+            // Blocks past the edges repeat the last row and column
+            let pixels: [[f32; 3]; 64] = array::from_fn(|i| {
+                let x = (left + i % 8).min(image.width - 1);
+                let y = (top + i / 8).min(image.height - 1);
+                let [r, g, b] = image.pixels()[y * image.width + x].map(f32::from);
+                [
+                    0.299 * r + 0.587 * g + 0.114 * b - 128.0,
+                    -0.168736 * r - 0.331264 * g + 0.5 * b,
+                    0.5 * r - 0.418688 * g - 0.081312 * b,
+                ]
+            });
+            let blocks: [[f32; 64]; 3] = array::from_fn(|c| array::from_fn(|i| pixels[i][c]));
+            for (index, block) in blocks.iter().enumerate() {
+                let channel = &channels[index.min(1)];
+                let coefficients = forward_dct(block, &cosines);
+                let quantized: [i32; 64] =
+                    array::from_fn(|k| (coefficients[zigzag[k]] / channel.steps[k]).round() as i32);
+                write_block(&mut writer, &quantized, previous_dc[index], channel);
+                previous_dc[index] = quantized[0];
+            }
+        }
+    }
+
+    let mut jpeg = writer.finish();
+    jpeg.extend([0xFF, 0xD9]);
+    jpeg
+}
+
+// Natural (row by row) index of each coefficient in zigzag order, walking
+// the diagonals from the top left and turning at the edges
+fn zigzag() -> [usize; 64] {
+    let mut order = [0; 64];
+    let mut k = 0;
+    for diagonal in 0..15usize {
+        let rows = diagonal.saturating_sub(7)..=diagonal.min(7);
+        let rows: Vec<usize> = match diagonal % 2 {
+            0 => rows.rev().collect(),
+            _ => rows.collect(),
+        };
+        for row in rows {
+            order[k] = row * 8 + diagonal - row;
+            k += 1;
+        }
+    }
+    order
+}
+
+// A table scaled for quality as the IJG library does, in zigzag order
+fn scaled_steps(table: &[u8; 64], quality: u8, zigzag: &[usize; 64]) -> [u8; 64] {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - 2 * quality
+    };
+    array::from_fn(|k| ((table[zigzag[k]] as u32 * scale + 50) / 100).clamp(1, 255) as u8)
+}
+
+// Codes assigned in order of length, each one more than the last
+fn huffman_codes(counts: &[u8; 16], symbols: &[u8]) -> Vec<(u16, u8)> {
+    let mut codes = vec![(0, 0); 256];
+    let mut symbols = symbols.iter();
+    let mut code = 0u16;
+    for (length, &count) in (1..=16).zip(counts) {
+        for &symbol in symbols.by_ref().take(count as usize) {
+            codes[symbol as usize] = (code, length);
+            code += 1;
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+// 2D DCT of a level-shifted block, rows then columns
+fn forward_dct(block: &[f32; 64], cosines: &[[f32; 8]; 8]) -> [f32; 64] {
+    let scale = |u: usize| {
+        if u == 0 {
+            f32::consts::FRAC_1_SQRT_2
+        } else {
+            1.0
+        }
+    };
+    let mut rows = [0.0; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            let sum: f32 = (0..8).map(|x| block[y * 8 + x] * cosines[x][u]).sum();
+            rows[y * 8 + u] = sum * scale(u) / 2.0;
+        }
+    }
+    array::from_fn(|i| {
+        let (v, u) = (i / 8, i % 8);
+        let sum: f32 = (0..8).map(|y| rows[y * 8 + u] * cosines[y][v]).sum();
+        sum * scale(v) / 2.0
+    })
+}
+
+// The DC coefficient as a difference from the last block's, then each
+// nonzero AC coefficient with the run of zeros before it
+fn write_block(
+    writer: &mut JpegWriter,
+    coefficients: &[i32; 64],
+    previous_dc: i32,
+    channel: &Channel,
+) {
+    let difference = coefficients[0] - previous_dc;
+    let size = magnitude_size(difference);
+    writer.write_code(channel.dc[size as usize]);
+    writer.write(magnitude_bits(difference, size), size);
+
+    let mut zeros = 0;
+    for &coefficient in &coefficients[1..] {
+        if coefficient == 0 {
+            zeros += 1;
+            continue;
+        }
+        while zeros > 15 {
+            writer.write_code(channel.ac[SIXTEEN_ZEROS as usize]);
+            zeros -= 16;
+        }
+        let size = magnitude_size(coefficient);
+        writer.write_code(channel.ac[(zeros << 4 | size) as usize]);
+        writer.write(magnitude_bits(coefficient, size), size);
+        zeros = 0;
+    }
+    if zeros > 0 {
+        writer.write_code(channel.ac[END_OF_BLOCK as usize]);
+    }
+}
+
+// Bits needed for the value's magnitude
+fn magnitude_size(value: i32) -> u32 {
+    32 - value.unsigned_abs().leading_zeros()
+}
+
+// Negative values are stored one less, in `size` bits
+fn magnitude_bits(value: i32, size: u32) -> u32 {
+    match value < 0 {
+        true => (value - 1) as u32 & ((1 << size) - 1),
+        false => value as u32,
+    }
+}
+
+fn segment(jpeg: &mut Vec<u8>, marker: u8, data: &[u8]) {
+    jpeg.extend([0xFF, marker]);
+    jpeg.extend((data.len() as u16 + 2).to_be_bytes());
+    jpeg.extend(data);
+}
+
+// Bits packed most significant first, with a zero byte after every 0xFF so
+// it can't be mistaken for a marker
+struct JpegWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl JpegWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer = self.buffer << bits | value;
+        self.count += bits;
+        while self.count >= 8 {
+            self.count -= 8;
+            let byte = (self.buffer >> self.count) as u8;
+            self.bytes.push(byte);
+            if byte == 0xFF {
+                self.bytes.push(0);
+            }
+        }
+        self.buffer &= (1 << self.count) - 1;
+    }
+
+    fn write_code(&mut self, (code, length): (u16, u8)) {
+        self.write(code as u32, length as u32);
+    }
+
+    // The last byte is padded with ones
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.write((1 << (8 - self.count)) - 1, 8 - self.count);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graphics::Color;
+
+    #[test]
+    fn zigzag_walks_every_coefficient() {
+        let order = zigzag();
+        assert_eq!(order[..10], [0, 1, 8, 16, 9, 2, 3, 10, 17, 24]);
+        assert_eq!(order[63], 63);
+        let mut sorted = order;
+        sorted.sort();
+        assert_eq!(sorted, array::from_fn(|i| i));
+    }
+
+    #[test]
+    fn decodes_close_to_the_image() {
+        // Not a multiple of 8 across or down, with smooth areas and a point
+        let mut image = RgbImage::new(37, 21, Color::Rgb(20, 30, 60));
+        for y in 0..21 {
+            for x in 0..20 {
+                image.put(x, y, Color::Rgb(x as u8 * 12, y as u8 * 12, 200));
+            }
+        }
+        image.put(30, 10, Color::Rgb(255, 255, 255));
+        let jpeg = encode(&image, 95, &[("Title", "gradient".to_string())]);
+
+        let mut decoder = jpeg_decoder::Decoder::new(&jpeg[..]);
+        let pixels = decoder.decode().unwrap();
+        let info = decoder.info().unwrap();
+        assert_eq!((info.width, info.height), (37, 21));
+        assert_eq!(info.pixel_format, jpeg_decoder::PixelFormat::RGB24);
+        let squared_error: f64 = pixels
+            .iter()
+            .zip(image.pixels().as_flattened())
+            .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+            .sum();
+        let rms = (squared_error / pixels.len() as f64).sqrt();
+        assert!(rms < 4.0, "rms error {}", rms);
+    }
+}
@@ -0,0 +1,224 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// Camera movements and view changes that can be bound to keys
+#[derive(Copy, Clone, PartialEq)]
+pub enum Action {
+    OrbitLeft,
+    OrbitRight,
+    OrbitUp,
+    OrbitDown,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ResetView,
+    ViewTop,
+    ViewFront,
+    ViewSide,
+    ViewIsometric,
+    ToggleAxes,
+    PlayPause,
+    StepForward,
+    StepBack,
+}
+
+impl Action {
+    const ALL: [Action; 19] = [
+        Action::OrbitLeft,
+        Action::OrbitRight,
+        Action::OrbitUp,
+        Action::OrbitDown,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::ResetView,
+        Action::ViewTop,
+        Action::ViewFront,
+        Action::ViewSide,
+        Action::ViewIsometric,
+        Action::ToggleAxes,
+        Action::PlayPause,
+        Action::StepForward,
+        Action::StepBack,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::OrbitLeft => "orbit-left",
+            Action::OrbitRight => "orbit-right",
+            Action::OrbitUp => "orbit-up",
+            Action::OrbitDown => "orbit-down",
+            Action::ZoomIn => "zoom-in",
+            Action::ZoomOut => "zoom-out",
+            Action::PanLeft => "pan-left",
+            Action::PanRight => "pan-right",
+            Action::PanUp => "pan-up",
+            Action::PanDown => "pan-down",
+            Action::ResetView => "reset-view",
+            Action::ViewTop => "view-top",
+            Action::ViewFront => "view-front",
+            Action::ViewSide => "view-side",
+            Action::ViewIsometric => "view-isometric",
+            Action::ToggleAxes => "toggle-axes",
+            Action::PlayPause => "play-pause",
+            Action::StepForward => "step-forward",
+            Action::StepBack => "step-back",
+        }
+    }
+
+    fn default_keys(self) -> &'static str {
+        match self {
+            Action::OrbitLeft => "a,left",
+            Action::OrbitRight => "d,right",
+            Action::OrbitUp => "w,up",
+            Action::OrbitDown => "s,down",
+            Action::ZoomIn => "+,=",
+            Action::ZoomOut => "-",
+            Action::PanLeft => "shift+left",
+            Action::PanRight => "shift+right",
+            Action::PanUp => "shift+up",
+            Action::PanDown => "shift+down",
+            // Numpad-style, as in Blender
+            Action::ResetView => "r",
+            Action::ViewTop => "7",
+            Action::ViewFront => "1",
+            Action::ViewSide => "3",
+            Action::ViewIsometric => "0",
+            Action::ToggleAxes => "x", // "a" orbits, as in wasd
+            // As in video players
+            Action::PlayPause => "space",
+            Action::StepForward => ".",
+            Action::StepBack => "comma",
+        }
+    }
+}
+
+// A key with the modifiers that must be held
+#[derive(Copy, Clone, PartialEq)]
+struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    // Parse names like "a", "+", "left", "pageup" or "shift+left"
+    fn from_string(s: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = s.to_lowercase();
+        loop {
+            if let Some(rest) = name.strip_prefix("shift+") {
+                modifiers |= KeyModifiers::SHIFT;
+                name = rest.to_string();
+            } else if let Some(rest) = name.strip_prefix("ctrl+") {
+                modifiers |= KeyModifiers::CONTROL;
+                name = rest.to_string();
+            } else if let Some(rest) = name.strip_prefix("alt+") {
+                modifiers |= KeyModifiers::ALT;
+                name = rest.to_string();
+            } else {
+                break;
+            }
+        }
+
+        let code = match name.as_str() {
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "space" => KeyCode::Char(' '),
+            "plus" => KeyCode::Char('+'),
+            "minus" => KeyCode::Char('-'),
+            "comma" => KeyCode::Char(','),
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Key { code, modifiers })
+    }
+
+    fn matches(self, event: &KeyEvent) -> bool {
+        match (self.code, event.code) {
+            // Shift is part of the character itself ('+', 'A'), so it's ignored
+            (KeyCode::Char(bound), KeyCode::Char(pressed)) => {
+                bound == pressed.to_ascii_lowercase()
+                    && self.modifiers - KeyModifiers::SHIFT == event.modifiers - KeyModifiers::SHIFT
+            }
+            (bound, pressed) => bound == pressed && self.modifiers == event.modifiers,
+        }
+    }
+}
+
+// Keys for each action
+pub struct KeyBindings {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl KeyBindings {
+    pub fn new() -> KeyBindings {
+        let bindings = Action::ALL
+            .iter()
+            .map(|&action| (action, parse_keys(action.default_keys()).unwrap()))
+            .collect();
+        KeyBindings { bindings }
+    }
+
+    // Apply a binding like "orbit-left=h,left", replacing that action's keys
+    pub fn bind(&mut self, binding: &str) -> Result<(), String> {
+        let (name, keys) = binding
+            .split_once('=')
+            .ok_or_else(|| format!("invalid key binding: {} (expected ACTION=KEYS)", binding))?;
+        let keys =
+            parse_keys(keys).ok_or_else(|| format!("invalid keys in binding: {}", binding))?;
+
+        match self
+            .bindings
+            .iter_mut()
+            .find(|(action, _)| action.name() == name.trim())
+        {
+            Some((_, bound)) => {
+                *bound = keys;
+                Ok(())
+            }
+            None => {
+                let names: Vec<&str> = Action::ALL.iter().map(|action| action.name()).collect();
+                Err(format!(
+                    "unknown action: {} (expected one of {})",
+                    name,
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(event)))
+            .map(|(action, _)| *action)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings::new()
+    }
+}
+
+fn parse_keys(keys: &str) -> Option<Vec<Key>> {
+    keys.split(',')
+        .map(|key| Key::from_string(key.trim()))
+        .collect()
+}
@@ -0,0 +1,136 @@
+use std::*;
+
+use crossterm::{execute, style};
+
+use crate::graphics::{Camera, Screen, Viewport};
+use crate::view::ViewState;
+
+// Split-screen arrangements: the interactive view, optionally joined by
+// views of the same scene from fixed directions, like a CAD viewer
+#[derive(Copy, Clone, PartialEq)]
+pub enum Layout {
+    Single,
+    Double, // Interactive view | top
+    Quad,   // Interactive view, top / front, side
+}
+
+impl Layout {
+    pub fn next(self) -> Layout {
+        match self {
+            Layout::Single => Layout::Double,
+            Layout::Double => Layout::Quad,
+            Layout::Quad => Layout::Single,
+        }
+    }
+
+    // Fixed directions shown after the interactive view
+    pub fn presets(self) -> &'static [Preset] {
+        match self {
+            Layout::Single => &[],
+            Layout::Double => &[Preset::Top],
+            Layout::Quad => &[Preset::Top, Preset::Front, Preset::Side],
+        }
+    }
+
+    // Panes per row
+    pub fn columns(self) -> usize {
+        match self {
+            Layout::Single => 1,
+            Layout::Double | Layout::Quad => 2,
+        }
+    }
+
+    // Size of each pane for a terminal of the given size (one row is kept
+    // for the status line)
+    pub fn pane_viewport(self, columns: u16, rows: u16) -> Viewport {
+        let pane_rows = match self {
+            Layout::Single | Layout::Double => 1,
+            Layout::Quad => 2,
+        };
+        Viewport::new(
+            columns / self.columns() as u16 * 2,
+            rows.saturating_sub(1) / pane_rows * 4,
+        )
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Preset {
+    Top,
+    Front,
+    Side,
+    Isometric, // Between front and side, looking down along a cube diagonal
+}
+
+impl Preset {
+    pub fn from_string(s: &str) -> Option<Preset> {
+        match s {
+            "top" => Some(Preset::Top),
+            "front" => Some(Preset::Front),
+            "side" => Some(Preset::Side),
+            "iso" | "isometric" => Some(Preset::Isometric),
+            _ => None,
+        }
+    }
+
+    // Orbit angles (yaw, pitch) looking along the preset direction
+    pub fn angles(self) -> (f32, f32) {
+        match self {
+            Preset::Top => (f32::consts::PI / 2.0, f32::consts::PI / 2.0),
+            Preset::Front => (f32::consts::PI / 2.0, 0.0),
+            Preset::Side => (0.0, 0.0),
+            Preset::Isometric => (f32::consts::PI / 4.0, (1.0 / 2f32.sqrt()).atan()),
+        }
+    }
+}
+
+// A fixed-direction view with its own camera
+pub struct Pane {
+    pub camera: Camera,
+    pub preset: Preset,
+    pub view: ViewState,
+}
+
+impl Pane {
+    pub fn new(preset: Preset, camera: Camera, main_view: &ViewState) -> Pane {
+        let mut pane = Pane {
+            camera,
+            preset,
+            view: *main_view,
+        };
+        pane.follow(main_view);
+        pane
+    }
+
+    // Look at what the main view looks at, from the pane's own direction
+    pub fn follow(&mut self, main_view: &ViewState) {
+        let (yaw, pitch) = self.preset.angles();
+        let (_, _, roll) = main_view.angles();
+        self.view = ViewState {
+            orientation: ViewState::orientation_at(yaw, pitch, roll),
+            ..*main_view
+        };
+    }
+}
+
+// Print screens side by side, `columns` per row, starting at the top left
+pub fn render_panes(screens: &[&Screen], columns: usize) {
+    let mut output = String::from("\x1b[H");
+
+    for row in screens.chunks(columns) {
+        let frames: Vec<String> = row.iter().map(|screen| screen.frame()).collect();
+        let mut lines: Vec<str::Split<&str>> =
+            frames.iter().map(|frame| frame.split("\r\n")).collect();
+
+        // Every frame row ends with the color reset, so rows can be joined freely
+        let line_count = row[0].viewport.height.div_ceil(4);
+        for _ in 0..line_count {
+            for frame_lines in &mut lines {
+                output.push_str(frame_lines.next().unwrap_or(""));
+            }
+            output.push_str("\r\n");
+        }
+    }
+
+    execute!(io::stdout(), style::Print(output)).unwrap();
+}
@@ -0,0 +1,51 @@
+// The renderer as a library: point clouds, their loaders and analysis, and
+// the image and text renderers. The viewer in main.rs is built on it, and so
+// are the Python and C bindings.
+
+pub mod analysis;
+pub mod arcball;
+pub mod arrays;
+pub mod ascii;
+pub mod bitset;
+pub mod chunks;
+pub mod compact;
+pub mod contours;
+pub mod csv;
+pub mod curves;
+pub mod demo;
+pub mod filters;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod graphics;
+pub mod ground;
+pub mod heat;
+pub mod html;
+pub mod image;
+pub mod inline;
+pub mod input;
+pub mod io;
+pub mod jpeg;
+pub mod keys;
+pub mod layout;
+pub mod loaders;
+pub mod mesh;
+pub mod plot;
+pub mod quantize;
+pub mod quaternion;
+pub mod record;
+pub mod render;
+pub mod rng;
+pub mod segmentation;
+pub mod sequence;
+pub mod serve;
+pub mod session;
+pub mod spatial;
+pub mod stream;
+pub mod summary;
+pub mod view;
+pub mod watch;
+
+#[cfg(feature = "capi")]
+mod capi;
+#[cfg(feature = "python")]
+mod python;
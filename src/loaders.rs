@@ -0,0 +1,104 @@
+use std::*;
+
+use sync::{PoisonError, RwLock};
+
+use crate::csv::{CsvOptions, load_csv};
+use crate::graphics::PointCloud;
+
+// Reads the points of one file. Gets the path and the CSV options given on
+// the command line (which it is free to ignore) and returns the points, with
+// any attributes the format carries, in the file's own axes; the caller
+// converts them to the viewer's and records which file they came from.
+pub type Loader = fn(&str, &CsvOptions) -> Result<PointCloud, Box<dyn error::Error>>;
+
+// Lowercase extensions (without the dot) and their loaders
+static LOADERS: RwLock<Vec<(String, Loader)>> = RwLock::new(Vec::new());
+
+// Read files ending in `.extension` (any case) with `loader`, replacing
+// whatever was registered for it before, built-in formats included. Every way
+// of opening a file (the command line, /load, /open, the subcommands, the
+// bindings) picks it up.
+pub fn register_loader(extension: &str, loader: Loader) {
+    let extension = extension.trim_start_matches('.').to_lowercase();
+    let mut loaders = LOADERS.write().unwrap_or_else(PoisonError::into_inner);
+    loaders.retain(|(registered, _)| *registered != extension);
+    loaders.push((extension, loader));
+}
+
+// Formats other than the native point file format that are read without
+// registering anything
+fn builtin_loader(extension: &str) -> Option<Loader> {
+    match extension {
+        "csv" | "tsv" => Some(load_csv),
+        _ => None,
+    }
+}
+
+// The loader registered for the path's extension, or the built-in one; None
+// means the path is read as a point file
+pub fn find_loader(path: &str) -> Option<Loader> {
+    let extension = path::Path::new(path).extension()?.to_str()?.to_lowercase();
+    let loaders = LOADERS.read().unwrap_or_else(PoisonError::into_inner);
+    loaders
+        .iter()
+        .find(|(registered, _)| *registered == extension)
+        .map(|&(_, loader)| loader)
+        .or_else(|| builtin_loader(&extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{CoordinateSystem, Point3D};
+
+    // One point per line, written as "x;y;z"
+    fn load_semicolons(path: &str, _: &CsvOptions) -> Result<PointCloud, Box<dyn error::Error>> {
+        let mut points = Vec::new();
+        for line in fs::read_to_string(path)?.lines() {
+            let values: Vec<f32> = line.split(';').map(str::parse).collect::<Result<_, _>>()?;
+            points.push(Point3D::new(values[0], values[1], values[2]));
+        }
+        Ok(points.into_iter().collect())
+    }
+
+    #[test]
+    fn registered_loader_opens_files_with_its_extension() {
+        let path = env::temp_dir().join(format!("loaders-{}.SEMI", process::id()));
+        fs::write(&path, "1;2;3\n4;5;6\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(find_loader(path).is_none());
+        register_loader(".semi", load_semicolons);
+        // PointCloud::from_file is what the command line, /load and /open use
+        let cloud = PointCloud::from_file(path, &CsvOptions::new(), CoordinateSystem::YUp);
+        fs::remove_file(path).unwrap();
+
+        let cloud = cloud.unwrap();
+        let positions: Vec<_> = cloud.points().iter().map(|p| (p.x, p.y, p.z)).collect();
+        let expected = [(1.0, 2.0, 3.0), (4.0, 5.0, 6.0)]
+            .map(|(x, y, z)| CoordinateSystem::YUp.to_viewer(x, y, z));
+        assert_eq!(positions, expected);
+        assert_eq!(cloud.sources, [path]);
+    }
+
+    #[test]
+    fn builtin_formats_can_be_replaced() {
+        let path = env::temp_dir().join(format!("loaders-{}.tsv", process::id()));
+        fs::write(&path, "7;8;9\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(find_loader("points.CSV").is_some());
+        assert!(find_loader("points.txt").is_none());
+        register_loader("tsv", load_semicolons);
+        let points = find_loader(path).unwrap()(path, &CsvOptions::new());
+        fs::remove_file(path).unwrap();
+
+        let positions: Vec<_> = points
+            .unwrap()
+            .points()
+            .iter()
+            .map(|p| (p.x, p.y, p.z))
+            .collect();
+        assert_eq!(positions, [(7.0, 8.0, 9.0)]);
+    }
+}
@@ -2,22 +2,73 @@ use process::exit;
 use std::*;
 use time::Duration;
 
-use crossterm::{cursor, event, execute, style, terminal};
-
-mod graphics;
-use graphics::*;
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, event, execute, style};
 
 mod args;
+mod backend;
+mod config;
+mod terminal;
+#[cfg(feature = "window")]
+mod window;
+
+#[cfg(feature = "gpu")]
+use altostratus::gpu;
+use altostratus::{
+    analysis, ascii, chunks, compact, contours, csv, curves, demo, filters, graphics, ground, heat,
+    html, image, inline, input, keys, layout, mesh, plot, quantize, record, render, rng,
+    segmentation, sequence, serve, session, stream, summary, view, watch,
+};
 use args::*;
+use ascii::AsciiRenderer;
+use backend::Backend;
+use chunks::PointChunks;
+use compact::CompactCloud;
+use csv::CsvOptions;
+use graphics::*;
+use heat::HeatMap;
+use input::{
+    CommandInput, DEFAULT_FRICTION, InputEvent, InputMapper, InputMode, MouseSettings, PickInput,
+    PlaybackInput, ViewChange,
+};
+use layout::{Layout, Pane, Preset};
+use mesh::Mesh;
+use record::Recorder;
+use render::{ImageRenderer, Renderer};
+use rng::Rng;
+use sequence::CloudSequence;
+use stream::StreamingCloud;
+use terminal::{TerminalGuard, TerminalOptions};
+use view::{Inertia, VIEWPORT_DISTANCE, VIEWPORT_FOV, ViewState};
+use watch::FileWatcher;
 
 // Config
-const VIEWPORT_FOV: f32 = 1.7;
-const VIEWPORT_DISTANCE: f32 = 0.1;
 const TARGET_DURATION_PER_FRAME: Duration = Duration::from_millis(1000 / 60);
-const MOUSE_SPEED_MULTIPLIER: f32 = 30.;
+const UPDATE_TIMESTEP: Duration = Duration::from_micros(1_000_000 / 120); // Simulation step
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250); // Longest frame simulated in full
+const SPIN_SPEED_DEGREES: f32 = 30.0; // Default auto-rotation, degrees per second
+const DEFAULT_BOUNDS_PERCENTILE: f32 = 1.0; // Outliers left out by /bounds percentile
+const IDLE_DELAY: Duration = Duration::from_millis(300); // Stillness before drawing every point
+const STRIDE_TARGET: f32 = 0.75; // Fraction of the frame budget adaptive drawing aims for
+const MAX_STRIDE: usize = 256;
+const DEFAULT_QUANTIZER: &str = "median-cut"; // Palette picker for recorded GIFs
+const HEAT_HALF_LIFE: f32 = 5.0; // Default seconds for accumulated heat to halve
+const HEAT_CELL_FRACTION: f32 = 0.01; // Heat map cell size as a fraction of the diagonal
 const INITIAL_DISTANCE_MULTIPLIER: f32 = 1.5;
-const SCROLL_MULTIPLIER: f32 = 0.03;
-const PAN_MULTIPLIER: f32 = 0.1;
+const CROSSHAIR_COLOR: Color = Color::Yellow;
+const OVERLAY_POINTS: usize = 10000; // Default number of points sampled from an overlay mesh
+const OVERLAY_COLOR: Color = Color::Cyan;
+const SEGMENT_ANGLE_DEGREES: f32 = 15.0;
+const SEGMENT_DISTANCE_FRACTION: f32 = 0.02; // Neighbor distance as a fraction of the diagonal
+const SEGMENT_MIN_DISTANCE: f32 = 1e-6; // Default neighbor distance when the points coincide
+const PLANE_COUNT: f32 = 3.0;
+const PLANE_THRESHOLD_FRACTION: f32 = 0.01; // Inlier distance as a fraction of the diagonal
+const GROUND_CELL_FRACTION: f32 = 0.05; // Ground grid cell size as a fraction of the diagonal
+const GROUND_HEIGHT_FRACTION: f32 = 0.01; // Ground tolerance as a fraction of the diagonal
+const CLUSTER_MIN_POINTS: f32 = 4.0;
+const LIGHT_DIRECTION: [f32; 3] = [1.0, 1.0, 2.0]; // Default light, z-up
+const EDGE_DEPTH_RATIO: f32 = 0.1; // Relative depth jump that counts as a silhouette edge
+const SPLINE_SMOOTHING: f32 = 2.0; // Width of the spline smoothing window, in samples
 // const LINE_DENSITY: f32 = 10.0; // Points per unit length for line rendering
 
 // Command mode state
@@ -25,14 +76,72 @@ struct CommandState {
     active: bool,
     buffer: String,
     error_message: Option<String>,
+    csv_options: CsvOptions,
+    coordinate_system: CoordinateSystem, // Axis convention of loaded files
+    seed: u64,                           // Seed for stochastic commands
+    edge_ratio: Option<f32>,             // Silhouette edge threshold, if edges are shown
+    aggregation: AggregationMode,        // How points sharing a pixel are colored
+    light: Option<[f32; 3]>,             // Direction towards the shading light, if shading
+    point_style: PointStyle,
+    density: Option<DensityScale>, // Color by point count per pixel, if set
+    tone: Option<(f32, ToneMapping)>, // Exposure factor and curve for additive light, if set
+    depth_map: bool,               // Color by distance from the camera
+    pending_export: Option<String>, // HTML file to write the next frame to
+    recording: Option<Recorder>,   // Frames captured since /record start
+    axes: bool,                    // Draw the axes
+    lines: Vec<Point3D>,           // Plane outlines, contours and splines over the points
+    axis_length: Option<f32>,      // Fixed axis length (None fits the points)
+    grid: bool,                    // Draw a grid on the horizontal plane
+    message: Option<String>,       // Output of the last command, shown until the next one
+    spin: Option<f32>,             // Auto-rotation about the vertical, radians per second
+    adaptive: bool,                // Draw fewer points while the view moves, to keep it smooth
+    heat: Option<HeatMap>,         // Decaying density of arriving points, drawn instead of them
+    mouse: MouseSettings,          // Drag and scroll speeds
+    bounds_percentile: Option<f32>, // Frame the view and axes without this percent of outliers
+    sequence: Option<CloudSequence>, // Files played back as frames, with --sequence
 }
 
 impl CommandState {
-    fn new() -> Self {
+    fn new(csv_options: CsvOptions, coordinate_system: CoordinateSystem, seed: u64) -> Self {
         CommandState {
             active: false,
             buffer: String::new(),
             error_message: None,
+            csv_options,
+            coordinate_system,
+            seed,
+            edge_ratio: None,
+            aggregation: AggregationMode::Nearest,
+            light: None,
+            point_style: PointStyle::Dot,
+            density: None,
+            tone: None,
+            depth_map: false,
+            pending_export: None,
+            recording: None,
+            message: None,
+            axes: true,
+            lines: Vec::new(),
+            axis_length: None,
+            grid: false,
+            spin: None,
+            adaptive: false,
+            heat: None,
+            mouse: MouseSettings::new(),
+            bounds_percentile: None,
+            sequence: None,
+        }
+    }
+
+    // Commands that change the points check this first. During a --sequence
+    // they're refused, since the next frame would put the file's points back.
+    fn edits_points(&self, command: &str) -> Result<(), String> {
+        match &self.sequence {
+            Some(_) => Err(format!(
+                "/{} would be undone by the next frame, so it's off while playing a sequence",
+                command.split_whitespace().next().unwrap_or_default()
+            )),
+            None => Ok(()),
         }
     }
 
@@ -40,6 +149,7 @@ impl CommandState {
         self.active = true;
         self.buffer.clear();
         self.error_message = None;
+        self.message = None;
     }
 
     fn exit_command_mode(&mut self) {
@@ -51,45 +161,1070 @@ impl CommandState {
         self.buffer.push(c);
     }
 
-    fn backspace(&mut self) {
-        self.buffer.pop();
-    }
+    fn backspace(&mut self) {
+        self.buffer.pop();
+    }
+
+    fn execute_command(&mut self, point_cloud: &mut PointCloud, view: &mut ViewState) -> bool {
+        let command = self.buffer.trim();
+
+        if command.starts_with("load ") {
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let path = command.strip_prefix("load ").unwrap().trim();
+            match PointCloud::from_file(path, &self.csv_options, self.coordinate_system) {
+                Ok(new_cloud) => {
+                    if new_cloud.is_empty() {
+                        self.error_message = Some("No points found in file".to_string());
+                        return false;
+                    }
+
+                    // Add new points to existing point cloud (axes are regenerated)
+                    point_cloud.append(new_cloud);
+
+                    self.exit_command_mode();
+                    return false; // Don't reset view parameters
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to load: {}", e));
+                    return false;
+                }
+            }
+        } else if let Some(args) = command_args(command, "overlay") {
+            // Usage: overlay <file.obj> [count] [color]
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let count = match args.get(1).map(|value| value.parse::<usize>()) {
+                None => Ok(OVERLAY_POINTS),
+                Some(Ok(count)) if count > 0 => Ok(count),
+                _ => Err(()),
+            };
+            let color = match args.get(2) {
+                None => Some(OVERLAY_COLOR),
+                Some(name) => Color::from_string(name),
+            };
+            let (Some(&path), Ok(count), Some(color)) = (args.first(), count, color) else {
+                self.error_message = Some("Usage: overlay <file.obj> [count] [color]".to_string());
+                return false;
+            };
+
+            let mesh = match Mesh::from_obj(path) {
+                Ok(mesh) => mesh,
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to load: {}", e));
+                    return false;
+                }
+            };
+
+            // The reference surface joins the scene as a cloud of its own file
+            let mut overlay = PointCloud::new();
+            overlay.extend(mesh.sample_surface(count, color, &mut Rng::new(self.seed)));
+            overlay.convert(self.coordinate_system);
+            overlay.set_file(path);
+            point_cloud.append(overlay);
+
+            self.exit_command_mode();
+            return false;
+        } else if command == "clear lines" {
+            self.lines.clear();
+
+            self.exit_command_mode();
+            return false;
+        } else if command == "clear" {
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            // Clear all points from the point cloud
+            point_cloud.clear();
+            point_cloud.sources.clear();
+            self.lines.clear();
+
+            // Regenerate axes (will use minimum length since no points)
+            point_cloud.axes = PointCloud::generate_axes_public(point_cloud.points());
+
+            self.exit_command_mode();
+            return false; // Don't reset view parameters
+        } else if command == "list" {
+            if point_cloud.sources.is_empty() {
+                self.error_message = Some("No datasets loaded".to_string());
+                return false;
+            }
+            let datasets: Vec<String> = (0..point_cloud.sources.len())
+                .map(|index| {
+                    let points = point_cloud.source_points(index);
+                    let (total, hidden) = points.fold((0, 0), |(total, hidden), point| {
+                        (total + 1, hidden + point_cloud.is_hidden(point) as usize)
+                    });
+                    match hidden {
+                        0 => format!("{} ({})", point_cloud.source_name(index), total),
+                        _ => format!(
+                            "{} ({}, {} hidden)",
+                            point_cloud.source_name(index),
+                            total,
+                            hidden
+                        ),
+                    }
+                })
+                .collect();
+            self.message = Some(format!("Datasets: {}", datasets.join(", ")));
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "remove")
+            .or_else(|| command_args(command, "hide"))
+            .or_else(|| command_args(command, "show"))
+        {
+            // Usage: remove|hide|show <name>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let name = args.join(" ");
+            let index = match point_cloud.find_source(&name) {
+                Ok(index) => index,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+
+            if command.starts_with("remove") {
+                point_cloud.remove_source(index);
+            } else {
+                point_cloud.set_source_hidden(index, command.starts_with("hide"));
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "color") {
+            // Usage: color <name> <r> <g> <b> | color <name> <color>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let (name, color) = match args[..] {
+                [name, r, g, b] => {
+                    let channels: Result<Vec<u8>, _> =
+                        [r, g, b].iter().map(|c| c.parse()).collect();
+                    match channels.as_deref() {
+                        Ok(&[r, g, b]) => (name, Some(Color::Rgb(r, g, b))),
+                        _ => (name, None),
+                    }
+                }
+                [name, color] => (name, Color::from_string(color)),
+                _ => {
+                    self.error_message = Some(
+                        "Usage: /color <name> <r> <g> <b> | /color <name> <color>".to_string(),
+                    );
+                    return false;
+                }
+            };
+            let Some(color) = color else {
+                self.error_message =
+                    Some("Invalid color (expected 0-255 channels or a color name)".to_string());
+                return false;
+            };
+            let index = match point_cloud.find_source(name) {
+                Ok(index) => index,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+            let points: Vec<usize> = point_cloud.source_points(index).collect();
+            for point in points {
+                point_cloud.points_mut()[point].color = color;
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "filter") {
+            // Usage: filter <attribute> <min> <max>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let range = match (
+                args.get(1).map(|v| v.parse()),
+                args.get(2).map(|v| v.parse()),
+            ) {
+                (Some(Ok(min)), Some(Ok(max))) if args.len() == 3 && min <= max => min..=max,
+                _ => {
+                    self.error_message =
+                        Some("Usage: filter scalar|height <min> <max>".to_string());
+                    return false;
+                }
+            };
+
+            // Slice the full dataset, replacing earlier filters
+            point_cloud.show_all();
+            if let Err(e) = point_cloud.filter_attribute(args[0], range) {
+                self.error_message = Some(e);
+                return false;
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "brightness") {
+            // Usage: brightness <attribute> [min max]
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let usage = "Usage: brightness scalar|height [min max]";
+            let range = match (args.len(), args.get(1), args.get(2)) {
+                (1, _, _) => point_cloud.attribute_range(args[0]),
+                (3, Some(min), Some(max)) => match (min.parse::<f32>(), max.parse::<f32>()) {
+                    (Ok(min), Ok(max)) if min <= max => Ok(min..=max),
+                    _ => Err(usage.to_string()),
+                },
+                _ => Err(usage.to_string()),
+            };
+
+            let result = range.and_then(|range| point_cloud.modulate_brightness_by(args[0], range));
+            if let Err(e) = result {
+                self.error_message = Some(e);
+                return false;
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if command == "unhide" {
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            // Bring back points hidden by filters
+            point_cloud.show_all();
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "segment") {
+            // Usage: segment [angle_degrees] [distance]
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let angle = match parse_arg(&args, 0, SEGMENT_ANGLE_DEGREES) {
+                Ok(angle) if angle > 0.0 && angle <= 180.0 => angle,
+                Ok(_) => {
+                    self.error_message =
+                        Some("Angle must be above 0 and at most 180 degrees".to_string());
+                    return false;
+                }
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+            let default_distance =
+                (point_cloud.get_diagonal() * SEGMENT_DISTANCE_FRACTION).max(SEGMENT_MIN_DISTANCE);
+            let distance = match parse_arg(&args, 1, default_distance) {
+                Ok(distance) if distance > 0.0 => distance,
+                Ok(_) => {
+                    self.error_message = Some("Distance must be positive".to_string());
+                    return false;
+                }
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+
+            let normals = segmentation::estimate_normals(point_cloud.points(), distance);
+            let labels = segmentation::region_growing(
+                point_cloud.points(),
+                &normals,
+                angle.to_radians(),
+                distance,
+            );
+            segmentation::color_by_labels(point_cloud.points_mut(), &labels);
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "planes") {
+            // Usage: planes [count] [threshold] [outline]
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let outline = args.last() == Some(&"outline");
+            let numeric_args = if outline {
+                &args[..args.len() - 1]
+            } else {
+                &args[..]
+            };
+
+            let count = match parse_arg(numeric_args, 0, PLANE_COUNT) {
+                Ok(count) if count >= 1.0 => count as usize,
+                Ok(_) => {
+                    self.error_message = Some("Plane count must be at least 1".to_string());
+                    return false;
+                }
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+            let default_threshold = point_cloud.get_diagonal() * PLANE_THRESHOLD_FRACTION;
+            let threshold = match parse_arg(numeric_args, 1, default_threshold) {
+                Ok(threshold) => threshold,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+
+            let planes = segmentation::detect_planes(
+                point_cloud.points(),
+                count,
+                threshold,
+                &mut Rng::new(self.seed),
+            );
+            if planes.is_empty() {
+                self.error_message = Some("No planes found".to_string());
+                return false;
+            }
+            segmentation::color_planes(point_cloud.points_mut(), &planes);
+
+            if outline {
+                // Draw each plane's extent as a quad made of line points
+                for (plane_index, plane) in planes.iter().enumerate() {
+                    let mut corners = plane.outline(point_cloud.points());
+                    for corner in &mut corners {
+                        corner.color = segmentation::label_color(plane_index);
+                    }
+                    for i in 0..corners.len() {
+                        self.lines.extend(PointCloud::line_to_points(
+                            corners[i],
+                            corners[(i + 1) % corners.len()],
+                        ));
+                    }
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "ground") {
+            // Usage: ground ground|nonground|off [cell_size] [height]
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let keep_ground = match args.first() {
+                Some(&"ground") => true,
+                Some(&"nonground") => false,
+                Some(&"off") => {
+                    point_cloud.show_all();
+                    self.exit_command_mode();
+                    return false;
+                }
+                _ => {
+                    self.error_message =
+                        Some("Usage: ground ground|nonground|off [cell] [height]".to_string());
+                    return false;
+                }
+            };
+
+            // Classify the full dataset, not what a previous filter left visible
+            point_cloud.show_all();
+
+            let diagonal = point_cloud.get_diagonal();
+            let cell_size = match parse_arg(&args, 1, diagonal * GROUND_CELL_FRACTION) {
+                Ok(cell_size) => cell_size,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+            let height = match parse_arg(&args, 2, diagonal * GROUND_HEIGHT_FRACTION) {
+                Ok(height) => height,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+
+            let is_ground = ground::classify_ground(point_cloud.points(), cell_size, height);
+            let mut is_ground = is_ground.into_iter();
+            point_cloud.hide_where(|_| is_ground.next() != Some(keep_ground));
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "contours") {
+            // Usage: contours <interval>
+            let interval = match args.first().map(|value| value.parse::<f32>()) {
+                Some(Ok(interval)) if interval > 0.0 => interval,
+                _ => {
+                    self.error_message = Some("Usage: contours <interval>".to_string());
+                    return false;
+                }
+            };
+
+            let segments = match contours::extract(point_cloud.points(), interval) {
+                Ok(segments) => segments,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            };
+            for (start, end) in segments {
+                self.lines.extend(PointCloud::line_to_points(start, end));
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "spline") {
+            // Usage: spline [smoothing]
+            let smoothing = match parse_arg(&args, 0, SPLINE_SMOOTHING) {
+                Ok(smoothing) if smoothing >= 0.0 => smoothing,
+                _ => {
+                    self.error_message = Some("Usage: spline [smoothing]".to_string());
+                    return false;
+                }
+            };
+
+            // The visible points, in file order, are the samples
+            let samples: Vec<Point3D> = point_cloud.visible_points().copied().collect();
+            let curve = curves::fit_spline(&samples, smoothing);
+
+            for pair in curve.windows(2) {
+                self.lines
+                    .extend(PointCloud::line_to_points(pair[0], pair[1]));
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "voxel") {
+            // Usage: voxel <cell_size>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let cell_size = match args.first().map(|value| value.parse::<f32>()) {
+                Some(Ok(cell_size)) if cell_size > 0.0 => cell_size,
+                _ => {
+                    self.error_message = Some("Usage: voxel <cell_size>".to_string());
+                    return false;
+                }
+            };
+
+            *point_cloud = point_cloud.voxel_downsample(cell_size);
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "sample") {
+            // Usage: sample random <count> | sample every <step>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let amount = args.get(1).and_then(|value| value.parse::<usize>().ok());
+            *point_cloud = match (args.first(), amount) {
+                (Some(&"random"), Some(count)) => point_cloud.sample_random(count, self.seed),
+                (Some(&"every"), Some(step)) if step > 0 => point_cloud.sample_every(step),
+                _ => {
+                    self.error_message =
+                        Some("Usage: sample random <count> | sample every <step>".to_string());
+                    return false;
+                }
+            };
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "flip") {
+            // Usage: flip x|y|z
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let axis = match args[..] {
+                [name] => Axis::from_string(name),
+                _ => None,
+            };
+            let Some(axis) = axis else {
+                self.error_message = Some("Usage: flip x|y|z".to_string());
+                return false;
+            };
+
+            // Axes are named in the data's coordinate system
+            point_cloud.convert_back(self.coordinate_system);
+            point_cloud.flip_axis(axis);
+            point_cloud.convert(self.coordinate_system);
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "swap") {
+            // Usage: swap <axis> <axis>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let axes = match args[..] {
+                [a, b] => Axis::from_string(a).zip(Axis::from_string(b)),
+                _ => None,
+            };
+            let Some((a, b)) = axes else {
+                self.error_message = Some("Usage: swap <axis> <axis>".to_string());
+                return false;
+            };
+
+            point_cloud.convert_back(self.coordinate_system);
+            point_cloud.swap_axes(a, b);
+            point_cloud.convert(self.coordinate_system);
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "crop") {
+            // Usage: crop box x1 y1 z1 x2 y2 z2 | crop sphere x y z radius
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            // Coordinates are given in the data's coordinate system
+            let values: Result<Vec<f32>, _> = args.iter().skip(1).map(|v| v.parse()).collect();
+            let to_viewer = |x, y, z| {
+                let (x, y, z) = self.coordinate_system.to_viewer(x, y, z);
+                Point3D::new(x, y, z)
+            };
+            match (args.first(), values.as_deref()) {
+                (Some(&"box"), Ok(&[x1, y1, z1, x2, y2, z2])) => {
+                    let (a, b) = (to_viewer(x1, y1, z1), to_viewer(x2, y2, z2));
+                    point_cloud.crop_box(
+                        Point3D::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+                        Point3D::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+                    )
+                }
+                (Some(&"sphere"), Ok(&[x, y, z, radius])) if radius > 0.0 => {
+                    point_cloud.crop_sphere(to_viewer(x, y, z), radius)
+                }
+                _ => {
+                    self.error_message = Some(
+                        "Usage: crop box x1 y1 z1 x2 y2 z2 | crop sphere x y z radius".to_string(),
+                    );
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "cluster") {
+            // Usage: cluster dbscan <eps> [min_points] | cluster kmeans <k>
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let eps_or_k = args.get(1).and_then(|value| value.parse::<f32>().ok());
+            let labels = match (args.first(), eps_or_k) {
+                (Some(&"dbscan"), Some(eps)) if eps > 0.0 => {
+                    match parse_arg(&args, 2, CLUSTER_MIN_POINTS) {
+                        Ok(min_points) => {
+                            analysis::cluster_dbscan(point_cloud.points(), eps, min_points as usize)
+                        }
+                        Err(e) => {
+                            self.error_message = Some(e);
+                            return false;
+                        }
+                    }
+                }
+                (Some(&"kmeans"), Some(k)) if k >= 1.0 => analysis::cluster_kmeans(
+                    point_cloud.points(),
+                    k as usize,
+                    &mut Rng::new(self.seed),
+                )
+                .into_iter()
+                .map(Some)
+                .collect(),
+                _ => {
+                    self.error_message = Some(
+                        "Usage: cluster dbscan <eps> [min_points] | cluster kmeans <k>".to_string(),
+                    );
+                    return false;
+                }
+            };
+            analysis::color_by_cluster(point_cloud.points_mut(), &labels);
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "axes") {
+            // Usage: axes on|off | axes length <n>|auto
+            match args[..] {
+                ["on"] => self.axes = true,
+                ["off"] => self.axes = false,
+                ["length", "auto"] => self.axis_length = None,
+                ["length", length] => match length.parse::<f32>() {
+                    Ok(length) if length > 0.0 => self.axis_length = Some(length),
+                    _ => {
+                        self.error_message = Some(format!("Invalid axis length: {}", length));
+                        return false;
+                    }
+                },
+                _ => {
+                    self.error_message =
+                        Some("Usage: /axes on|off | /axes length <n>|auto".to_string());
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "grid") {
+            // Usage: grid on|off
+            match args[..] {
+                ["on"] => self.grid = true,
+                ["off"] => self.grid = false,
+                _ => {
+                    self.error_message = Some("Usage: /grid on|off".to_string());
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "adaptive") {
+            // Usage: adaptive on|off
+            match args[..] {
+                ["on"] => self.adaptive = true,
+                ["off"] => self.adaptive = false,
+                _ => {
+                    self.error_message = Some("Usage: /adaptive on|off".to_string());
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "mouse") {
+            // Usage: mouse [speed <factor> | scroll <factor> | invert on|off]
+            let factor = |value: &str| {
+                value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|f| f.is_finite() && *f > 0.0)
+            };
+            match args[..] {
+                [] => {}
+                ["speed", value] if factor(value).is_some() => {
+                    self.mouse.speed = factor(value).unwrap()
+                }
+                ["scroll", value] if factor(value).is_some() => {
+                    self.mouse.scroll_speed = factor(value).unwrap()
+                }
+                ["invert", "on"] => self.mouse.invert_y = true,
+                ["invert", "off"] => self.mouse.invert_y = false,
+                ["arcball", "on"] => self.mouse.arcball = true,
+                ["arcball", "off"] => self.mouse.arcball = false,
+                ["inertia", "off"] => self.mouse.friction = None,
+                ["inertia"] => self.mouse.friction = Some(DEFAULT_FRICTION),
+                ["inertia", value] if factor(value).is_some() => {
+                    self.mouse.friction = factor(value)
+                }
+                _ => {
+                    self.error_message = Some(
+                        "Usage: /mouse [speed <factor> | scroll <factor> | invert on|off | arcball on|off | inertia [friction]|off]"
+                            .to_string(),
+                    );
+                    return false;
+                }
+            }
+            self.message = Some(format!(
+                "Mouse speed {}, scroll speed {}, inverted y {}, arcball {}, inertia {}",
+                self.mouse.speed,
+                self.mouse.scroll_speed,
+                if self.mouse.invert_y { "on" } else { "off" },
+                if self.mouse.arcball { "on" } else { "off" },
+                match self.mouse.friction {
+                    Some(friction) => format!("friction {}", friction),
+                    None => "off".to_string(),
+                }
+            ));
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "spin") {
+            // Usage: spin [degrees per second] | spin off
+            self.spin = match args[..] {
+                ["off"] => None,
+                _ => match parse_arg(&args, 0, SPIN_SPEED_DEGREES) {
+                    Ok(speed) if args.len() <= 1 => Some(speed.to_radians()),
+                    _ => {
+                        self.error_message =
+                            Some("Usage: /spin [degrees per second] | off".to_string());
+                        return false;
+                    }
+                },
+            };
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "fps") {
+            // Usage: fps [frames per second]
+            let Some(sequence) = &mut self.sequence else {
+                self.error_message =
+                    Some("No sequence to play (view files as frames with --sequence)".to_string());
+                return false;
+            };
+            match parse_arg(&args, 0, sequence.fps) {
+                Ok(fps) if args.len() <= 1 && fps.is_finite() && fps > 0.0 => sequence.fps = fps,
+                _ => {
+                    self.error_message = Some("Usage: /fps [frames per second]".to_string());
+                    return false;
+                }
+            }
+            self.message = Some(format!(
+                "Playing {} frames at {} fps{}",
+                sequence.len(),
+                sequence.fps,
+                if sequence.playing { "" } else { " (paused)" }
+            ));
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "edges") {
+            // Usage: edges on [ratio] | edges off
+            self.edge_ratio = match args.first() {
+                Some(&"on") => match parse_arg(&args, 1, EDGE_DEPTH_RATIO) {
+                    Ok(ratio) if ratio > 0.0 => Some(ratio),
+                    _ => {
+                        self.error_message =
+                            Some("Edge ratio must be a positive number".to_string());
+                        return false;
+                    }
+                },
+                Some(&"off") => None,
+                _ => {
+                    self.error_message = Some("Usage: edges on [ratio] | edges off".to_string());
+                    return false;
+                }
+            };
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "shade") {
+            // Usage: shade [x y z] | shade off
+            if args.first() == Some(&"off") {
+                self.light = None;
+                self.exit_command_mode();
+                return false;
+            }
+
+            let direction: Result<Vec<f32>, _> = args.iter().map(|v| v.parse()).collect();
+            let [x, y, z] = match direction.as_deref() {
+                Ok([]) => LIGHT_DIRECTION,
+                Ok(&[x, y, z]) if x != 0.0 || y != 0.0 || z != 0.0 => [x, y, z],
+                _ => {
+                    self.error_message = Some("Usage: shade [x y z] | shade off".to_string());
+                    return false;
+                }
+            };
+
+            // Light directions are given in the data's coordinate system
+            let length = (x * x + y * y + z * z).sqrt();
+            let (x, y, z) = self.coordinate_system.to_viewer(x, y, z);
+            self.light = Some([x / length, y / length, z / length]);
+            point_cloud.estimate_normals(point_cloud.get_diagonal() * SEGMENT_DISTANCE_FRACTION);
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "smooth") {
+            // Usage: smooth [radius]
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let diagonal = point_cloud.get_diagonal();
+            let radius = match parse_arg(&args, 0, diagonal * SEGMENT_DISTANCE_FRACTION) {
+                Ok(radius) if radius > 0.0 => radius,
+                _ => {
+                    self.error_message = Some("Usage: smooth [radius]".to_string());
+                    return false;
+                }
+            };
+
+            filters::mls_smooth(point_cloud.points_mut(), radius);
+            // Shading uses normals of the old positions otherwise
+            if self.light.is_some() {
+                point_cloud.estimate_normals(diagonal * SEGMENT_DISTANCE_FRACTION);
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "style") {
+            // Usage: style dot | style splat [radius] | style square [size]
+            //        | style gaussian [sigma]
+            // Sizes default to the typical gap between points so the shapes close it
+            let size = || match args.get(1).map(|value| value.parse::<f32>()) {
+                None => Ok(point_cloud.median_spacing()),
+                Some(Ok(size)) if size > 0.0 => Ok(size),
+                _ => Err("Point size must be a positive number".to_string()),
+            };
+            let style = match args.first() {
+                Some(&"dot") => Ok(PointStyle::Dot),
+                Some(&"splat") => size().map(|radius| PointStyle::Splat { radius }),
+                Some(&"square") => size().map(|size| PointStyle::Square { size }),
+                Some(&"gaussian") => size().map(|sigma| PointStyle::Gaussian { sigma }),
+                _ => Err(
+                    "Usage: style dot | splat [radius] | square [size] | gaussian [sigma]"
+                        .to_string(),
+                ),
+            };
+            match style {
+                Ok(style) => self.point_style = style,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "density") {
+            // Usage: density linear|log|off
+            self.density = match args.first() {
+                Some(&"linear") => Some(DensityScale::Linear),
+                Some(&"log") => Some(DensityScale::Log),
+                Some(&"off") => None,
+                _ => {
+                    self.error_message = Some("Usage: density linear|log|off".to_string());
+                    return false;
+                }
+            };
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "exposure") {
+            // Usage: exposure <stops> [clip|reinhard|filmic] | exposure off
+            const USAGE: &str = "Usage: exposure <stops> [clip|reinhard|filmic] | off";
+            self.tone = match args.as_slice() {
+                ["off"] => None,
+                [stops, rest @ ..] if rest.len() <= 1 => {
+                    let stops = stops.parse::<f32>().ok().filter(|stops| stops.is_finite());
+                    let mapping = match rest.first() {
+                        Some(name) => ToneMapping::from_string(name),
+                        None => Some(ToneMapping::Reinhard),
+                    };
+                    match (stops, mapping) {
+                        (Some(stops), Some(mapping)) => Some((stops.exp2(), mapping)),
+                        _ => {
+                            self.error_message = Some(USAGE.to_string());
+                            return false;
+                        }
+                    }
+                }
+                _ => {
+                    self.error_message = Some(USAGE.to_string());
+                    return false;
+                }
+            };
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "depthmap") {
+            // Usage: depthmap on|off
+            self.depth_map = match args.first() {
+                Some(&"on") => true,
+                Some(&"off") => false,
+                _ => {
+                    self.error_message = Some("Usage: depthmap on|off".to_string());
+                    return false;
+                }
+            };
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "aggregate") {
+            // Usage: aggregate nearest|mean|max|sum
+            match args
+                .first()
+                .and_then(|mode| AggregationMode::from_string(mode))
+            {
+                Some(mode) => self.aggregation = mode,
+                None => {
+                    self.error_message = Some("Usage: aggregate nearest|mean|max|sum".to_string());
+                    return false;
+                }
+            }
 
-    fn execute_command(&mut self, point_cloud: &mut PointCloud) -> bool {
-        let command = self.buffer.trim();
+            self.exit_command_mode();
+            return false;
+        } else if let Some(path) = command.strip_prefix("export ") {
+            // The frame is written by the main loop once it has been drawn
+            self.pending_export = Some(path.trim().to_string());
 
-        if command.starts_with("load ") {
-            let path = command.strip_prefix("load ").unwrap().trim();
-            match PointCloud::from_file(path) {
-                Ok(new_cloud) => {
-                    if new_cloud.points.is_empty() {
-                        self.error_message = Some("No points found in file".to_string());
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "heat") {
+            // Usage: heat on [half-life] | heat off
+            match args.first() {
+                Some(&"on") if args.len() <= 2 => match parse_arg(&args, 1, HEAT_HALF_LIFE) {
+                    Ok(half_life) if half_life > 0.0 => {
+                        // Points already loaded are the first to arrive
+                        let cell_size = point_cloud.get_diagonal().max(1.0) * HEAT_CELL_FRACTION;
+                        let mut heat = HeatMap::new(cell_size, half_life);
+                        heat.add(point_cloud.visible_points());
+                        self.heat = Some(heat);
+                    }
+                    _ => {
+                        self.error_message =
+                            Some("Half-life must be a positive number of seconds".to_string());
+                        return false;
+                    }
+                },
+                Some(&"off") if args.len() == 1 => self.heat = None,
+                _ => {
+                    self.error_message =
+                        Some("Usage: /heat on [half-life] | /heat off".to_string());
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "record") {
+            // Usage: record start <file.gif> [quantizer] | record stop
+            match args[..] {
+                ["start", path] | ["start", path, _] => {
+                    let name = args.get(2).copied().unwrap_or(DEFAULT_QUANTIZER);
+                    let Some(quantizer) = quantize::from_string(name) else {
+                        self.error_message = Some(format!(
+                            "Unknown quantizer: {} (expected xterm, median-cut or octree)",
+                            name
+                        ));
+                        return false;
+                    };
+                    if let Some(recorder) = &self.recording {
+                        self.error_message =
+                            Some(format!("Already recording to {}", recorder.path));
+                        return false;
+                    }
+                    // Fail now rather than after the whole recording
+                    if let Err(e) = fs::File::create(path) {
+                        self.error_message = Some(format!("Failed to record: {}", e));
+                        return false;
+                    }
+                    self.recording = Some(Recorder::new(path, quantizer));
+                }
+                ["stop"] => {
+                    let Some(recorder) = self.recording.take() else {
+                        self.error_message = Some("Not recording".to_string());
+                        return false;
+                    };
+                    if let Err(e) = recorder.finish() {
+                        self.error_message = Some(format!("Failed to write recording: {}", e));
                         return false;
                     }
+                }
+                _ => {
+                    self.error_message = Some(
+                        "Usage: /record start <file.gif> [quantizer] | /record stop".to_string(),
+                    );
+                    return false;
+                }
+            }
 
-                    // Add new points to existing point cloud
-                    point_cloud.points.extend(new_cloud.points);
+            self.exit_command_mode();
+            return false;
+        } else if let Some(path) = command.strip_prefix("save ") {
+            let path = path.trim();
+            if let Err(e) = session::save_session(path, point_cloud, view) {
+                self.error_message = Some(format!("Failed to save: {}", e));
+                return false;
+            }
 
-                    // Regenerate axes based on combined dataset
-                    point_cloud.axes = PointCloud::generate_axes_public(&point_cloud.points);
+            self.exit_command_mode();
+            return false;
+        } else if let Some(path) = command.strip_prefix("open ") {
+            if let Err(e) = self.edits_points(command) {
+                self.error_message = Some(e);
+                return false;
+            }
+            let path = path.trim();
+            match session::load_session(path) {
+                Ok((saved_cloud, saved_view)) => {
+                    // Replace the current scene and camera with the saved ones
+                    *point_cloud = saved_cloud;
+                    *view = saved_view;
 
                     self.exit_command_mode();
-                    return false; // Don't reset view parameters
+                    return false;
                 }
                 Err(e) => {
-                    self.error_message = Some(format!("Failed to load: {}", e));
+                    self.error_message = Some(format!("Failed to open: {}", e));
+                    return false;
+                }
+            }
+        } else if let Some(args) = command_args(command, "view") {
+            // Usage: view reset|top|front|side|iso
+            let preset = args.first().and_then(|name| Preset::from_string(name));
+            match (&args[..], preset) {
+                (["reset"], _) => *view = initial_view(point_cloud, self.bounds_percentile),
+                ([_], Some(preset)) => {
+                    view_preset(view, preset, point_cloud, self.bounds_percentile)
+                }
+                _ => {
+                    self.error_message = Some("Usage: view reset|top|front|side|iso".to_string());
                     return false;
                 }
             }
-        } else if command == "clear" {
-            // Clear all points from the point cloud
-            point_cloud.points.clear();
 
-            // Regenerate axes (will use minimum length since no points)
-            point_cloud.axes = PointCloud::generate_axes_public(&point_cloud.points);
+            self.exit_command_mode();
+            return false;
+        } else if let Some(args) = command_args(command, "bounds") {
+            // Usage: bounds all | bounds percentile [p]
+            self.bounds_percentile = match args[..] {
+                ["all"] => None,
+                ["percentile", ..] => match parse_arg(&args, 1, DEFAULT_BOUNDS_PERCENTILE) {
+                    Ok(percentile) if args.len() <= 2 && (0.0..50.0).contains(&percentile) => {
+                        Some(percentile)
+                    }
+                    _ => {
+                        self.error_message = Some("Usage: /bounds percentile [0-50)".to_string());
+                        return false;
+                    }
+                },
+                _ => {
+                    self.error_message = Some("Usage: /bounds all | percentile [p]".to_string());
+                    return false;
+                }
+            };
+            *view = initial_view(point_cloud, self.bounds_percentile);
 
             self.exit_command_mode();
-            return false; // Don't reset view parameters
+            return false;
+        } else if let Some(args) = command_args(command, "seed") {
+            // Usage: seed <n>
+            match args.first().map(|value| value.parse::<u64>()) {
+                Some(Ok(seed)) => self.seed = seed,
+                _ => {
+                    self.error_message = Some("Usage: seed <non-negative integer>".to_string());
+                    return false;
+                }
+            }
+
+            self.exit_command_mode();
+            return false;
+        } else if command == "pose" {
+            // Usage: pose
+            let system = self.coordinate_system;
+            let pose = view.pose().map(|[x, y, z]| {
+                let (x, y, z) = system.to_data(x, y, z);
+                [x, y, z]
+            });
+            let numbers: Vec<String> = pose
+                .to_matrix()
+                .iter()
+                .flatten()
+                .map(|value| value.to_string())
+                .collect();
+            self.message = Some(format!("Pose: {}", numbers.join(",")));
+
+            self.exit_command_mode();
+            return false;
         } else if !command.is_empty() {
             self.error_message = Some("Unknown command".to_string());
             return false;
@@ -107,29 +1242,156 @@ impl CommandState {
     }
 }
 
+// Returns the arguments following `name` if the command is exactly `name ...`
+fn command_args<'a>(command: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let mut parts = command.split_whitespace();
+    if parts.next() == Some(name) {
+        Some(parts.collect())
+    } else {
+        None
+    }
+}
+
+// Parse an optional numeric command argument, falling back to `default`
+fn parse_arg(args: &[&str], index: usize, default: f32) -> Result<f32, String> {
+    match args.get(index) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid number: {}", value)),
+        None => Ok(default),
+    }
+}
+
+// Draw the cloud with the display options chosen through commands, using
+// every `stride`th point
+fn draw_frame(
+    camera: &mut Camera,
+    point_cloud: &PointCloud,
+    command_state: &CommandState,
+    stride: usize,
+) {
+    camera.screen.aggregation = command_state.aggregation;
+    camera.light = command_state.light;
+    camera.point_style = command_state.point_style;
+    camera.screen.clear();
+
+    let fixed_axes = match (command_state.axis_length, command_state.bounds_percentile) {
+        (Some(length), _) => Some(PointCloud::axes_with_length(length)),
+        (None, Some(percentile)) => Some(point_cloud.percentile_axes(percentile)),
+        (None, None) => None,
+    };
+    let axes = fixed_axes.as_deref().unwrap_or(&point_cloud.axes);
+    if command_state.axes {
+        camera.draw_axes(axes);
+    }
+    if command_state.grid {
+        let length = axes.first().map_or(0.0, AxisDecoration::length);
+        for (start, end) in PointCloud::generate_grid(length) {
+            camera.plot_line(&start, &end);
+        }
+    }
+    match &command_state.heat {
+        Some(heat) => camera.plot_points(heat.points()),
+        None => camera.draw_points(point_cloud, stride),
+    }
+    camera.plot_points(command_state.lines.iter().copied());
+
+    // Only summed light has anything past full brightness to map
+    let additive = command_state.aggregation == AggregationMode::Sum
+        || matches!(command_state.point_style, PointStyle::Gaussian { .. });
+    if let Some((exposure, mapping)) = command_state.tone
+        && additive
+    {
+        camera.screen.tone_map(exposure, mapping);
+    }
+    if let Some(scale) = command_state.density {
+        camera.screen.color_by_density(scale);
+    }
+    if command_state.depth_map {
+        camera.screen.color_by_depth();
+    }
+    if let Some(ratio) = command_state.edge_ratio {
+        camera.screen.outline_edges(ratio);
+    }
+}
+
 fn graceful_close() -> ! {
-    execute!(io::stdout(), cursor::Show, event::DisableMouseCapture,).unwrap();
-    terminal::disable_raw_mode().unwrap();
+    terminal::restore();
     exit(0)
 }
 
 fn error_close(msg: &dyn fmt::Display) -> ! {
+    // Back out of raw mode first so the message prints normally
+    terminal::restore();
     execute!(io::stderr(), style::Print(msg)).unwrap();
     graceful_close()
 }
 
-fn load_multiple_files(file_paths: &[String]) -> Result<PointCloud, Box<dyn error::Error>> {
-    let mut combined_points = Vec::new();
+// Pixel at the middle of a terminal cell's left edge
+fn crosshair_pixel(cell: Point2D, (cell_width, cell_height): (u16, u16)) -> Point2D {
+    Point2D::new(
+        cell.x * cell_width as i32,
+        cell.y * cell_height as i32 + cell_height as i32 / 2,
+    )
+}
+
+// Front view framing the whole cloud, as the viewer starts
+fn initial_view(point_cloud: &PointCloud, percentile: Option<f32>) -> ViewState {
+    let (center, diagonal) = fit_box(point_cloud, percentile);
+    ViewState {
+        orientation: ViewState::orientation_at(f32::consts::PI / 2.0, 0.0, 0.0),
+        distance: diagonal * INITIAL_DISTANCE_MULTIPLIER,
+        center,
+        fov: VIEWPORT_FOV,
+    }
+}
+
+// Look along a preset direction from the starting distance, keeping the center
+fn view_preset(
+    view: &mut ViewState,
+    preset: Preset,
+    point_cloud: &PointCloud,
+    percentile: Option<f32>,
+) {
+    let (yaw, pitch) = preset.angles();
+    let (_, _, roll) = view.angles();
+    view.orientation = ViewState::orientation_at(yaw, pitch, roll);
+    view.distance = fit_box(point_cloud, percentile).1 * INITIAL_DISTANCE_MULTIPLIER;
+}
+
+// Center and diagonal (at least 1) of the box to frame: the bounding box, or
+// the box between the given percentiles of each axis to leave outliers out
+fn fit_box(point_cloud: &PointCloud, percentile: Option<f32>) -> (Point3D, f32) {
+    let (min, max) = match percentile {
+        Some(percentile) => point_cloud.get_percentile_bounds(percentile),
+        None => point_cloud.get_bounds(),
+    };
+    let center = Point3D::new(
+        (min.x + max.x) / 2.,
+        (min.y + max.y) / 2.,
+        (min.z + max.z) / 2.,
+    );
+    let diagonal =
+        ((max.x - min.x).powi(2) + (max.y - min.y).powi(2) + (max.z - min.z).powi(2)).sqrt();
+    (center, diagonal.max(1.0))
+}
+
+fn load_multiple_files(
+    file_paths: &[String],
+    csv_options: &CsvOptions,
+    coordinate_system: CoordinateSystem,
+) -> Result<PointCloud, Box<dyn error::Error>> {
+    let mut combined = PointCloud::new();
 
     for path in file_paths {
-        match PointCloud::from_file(path) {
+        match PointCloud::from_file(path, csv_options, coordinate_system) {
             Ok(cloud) => {
-                if cloud.points.is_empty() {
+                if cloud.is_empty() {
                     eprintln!("Warning: No points found in file: {}", path);
                     continue;
                 }
-                let points_count = cloud.points.len();
-                combined_points.extend(cloud.points);
+                let points_count = cloud.len();
+                combined.append(cloud);
                 println!("Loaded {} points from {}", points_count, path);
             }
             Err(e) => {
@@ -138,15 +1400,11 @@ fn load_multiple_files(file_paths: &[String]) -> Result<PointCloud, Box<dyn erro
         }
     }
 
-    if combined_points.is_empty() {
+    if combined.is_empty() {
         return Err("No points found in any of the provided files".into());
     }
 
-    let axes = PointCloud::generate_axes_public(&combined_points);
-    Ok(PointCloud {
-        points: combined_points,
-        axes,
-    })
+    Ok(combined)
 }
 
 fn main() {
@@ -160,198 +1418,864 @@ fn main() {
             print_detailed_help();
             graceful_close();
         }
-        ParseResult::LoadFiles(file_paths) => {
+        ParseResult::Demo => {
+            demo::run_demo(VIEWPORT_FOV, VIEWPORT_DISTANCE);
+        }
+        ParseResult::Serve(options) => {
+            let point_cloud = load_multiple_files(
+                &options.file_paths,
+                &options.csv_options,
+                options.coordinate_system,
+            )
+            .unwrap_or_else(|e| error_close(&e));
+            if let Err(e) = serve::run_server(
+                &point_cloud,
+                (options.bind.as_str(), options.port),
+                options.progressive,
+                VIEWPORT_FOV,
+                VIEWPORT_DISTANCE,
+            ) {
+                error_close(&format!("Failed to serve: {}", e));
+            }
+        }
+        ParseResult::Summary(options) => {
+            // Keep going past broken files, but fail the run for scripts
+            let mut failed = false;
+            for path in &options.file_paths {
+                let result =
+                    PointCloud::from_file(path, &options.csv_options, options.coordinate_system)
+                        .and_then(|point_cloud| summarize_file(path, &point_cloud, options.views));
+                match result {
+                    Ok(outputs) => {
+                        for output in outputs {
+                            println!("Wrote {}", output.display());
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to summarize {}: {}", path, e);
+                        failed = true;
+                    }
+                }
+            }
+            exit(if failed { 1 } else { 0 });
+        }
+        ParseResult::Render(options) => match render_file(&options) {
+            Ok(()) => {
+                if let Some(output) = &options.output {
+                    println!("Wrote {}", output);
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to render {}: {}", options.file_path, e);
+                exit(1);
+            }
+        },
+        ParseResult::Animate(options) => match animate_file(&options) {
+            Ok(()) => println!("Wrote {}", options.output),
+            Err(e) => {
+                eprintln!("Failed to animate {}: {}", options.file_path, e);
+                exit(1);
+            }
+        },
+        ParseResult::Ascii(options) => match ascii_file(&options) {
+            Ok(text) => print!("{}", text),
+            Err(e) => {
+                eprintln!("Failed to print {}: {}", options.file_path, e);
+                exit(1);
+            }
+        },
+        ParseResult::Plot(options) => match plot_file(&options) {
+            Ok(Some(text)) => print!("{}", text),
+            Ok(None) => println!("Wrote {}", options.output.as_deref().unwrap_or_default()),
+            Err(e) => {
+                eprintln!("Failed to plot {}: {}", options.file_path, e);
+                exit(1);
+            }
+        },
+        ParseResult::LoadFiles(options) if options.window => {
+            if let Err(e) = open_window(options) {
+                eprintln!("Failed to open a window: {}", e);
+                exit(1);
+            }
+        }
+        ParseResult::LoadFiles(options) => {
             // Continue with the main application
-            run_application(file_paths);
+            run_application(options);
+        }
+    }
+}
+
+// Write <file>.png and any extra views next to the file; returns the paths written
+fn summarize_file(
+    path: &str,
+    point_cloud: &PointCloud,
+    views: usize,
+) -> Result<Vec<path::PathBuf>, Box<dyn error::Error>> {
+    let path = path::Path::new(path);
+    let mut outputs = vec![path.with_extension("png")];
+    let mut images = vec![summary::render_summary(point_cloud)];
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    for (index, image) in summary::render_orbit_thumbnails(point_cloud, views)
+        .into_iter()
+        .enumerate()
+    {
+        outputs.push(path.with_file_name(format!("{}-view-{}.png", stem, index + 1)));
+        images.push(image);
+    }
+    for (output, image) in outputs.iter().zip(&images) {
+        image.save_png(&output.to_string_lossy())?;
+    }
+    Ok(outputs)
+}
+
+// Headless counterpart of the viewer, for scripts: one frame straight to an
+// image file
+fn render_file(options: &RenderOptions) -> Result<(), Box<dyn error::Error>> {
+    // A file read in chunks is only held whole as the corners of its bounds
+    let chunks = options
+        .chunk_points
+        .map(|chunk_points| {
+            PointChunks::new(&options.file_path, chunk_points, options.coordinate_system)
+        })
+        .transpose()?;
+    let (point_cloud, chunked_count) = match &chunks {
+        Some(chunks) => {
+            let (bounds, count) = chunks.bounds()?;
+            (bounds, Some(count))
+        }
+        None => (
+            PointCloud::from_file(
+                &options.file_path,
+                &options.csv_options,
+                options.coordinate_system,
+            )?,
+            None,
+        ),
+    };
+    if point_cloud.is_empty() {
+        return Err("no points found".into());
+    }
+
+    let to_viewer = |[x, y, z]: [f32; 3]| {
+        let (x, y, z) = options.coordinate_system.to_viewer(x, y, z);
+        Point3D::new(x, y, z)
+    };
+    let mut image_renderer = image_renderer(&point_cloud, &options.image)?;
+    let viewport = image_renderer.viewport();
+    let (yaw, pitch) = Preset::Isometric.angles();
+    let mut view = image_renderer.auto_frame(yaw, pitch);
+    if let Some(target) = options.target {
+        view.center = to_viewer(target);
+    }
+    if let Some(eye) = options.camera {
+        view = ViewState::looking_at(&to_viewer(eye), &view.center);
+    }
+    match options.up {
+        Some(up) => view = view.with_up(&to_viewer(up)),
+        None => view.roll_by(options.roll),
+    }
+    if let Some(pose) = options.pose {
+        let pose = pose.map(|point| {
+            let point = to_viewer(point);
+            [point.x, point.y, point.z]
+        });
+        // Orbiting around a point as far ahead as the cloud's center
+        let [x, y, z] = pose.eye;
+        let distance = ViewState::looking_at(&Point3D::new(x, y, z), &view.center).distance;
+        view = ViewState::from_pose(&pose, distance);
+    }
+
+    let image = match (options.gpu, &chunks, &options.photo) {
+        (true, _, _) => cloud_renderer(&point_cloud, true)?.render(&view, viewport),
+        (false, Some(chunks), _) => image_renderer.render_chunks(&view, viewport, chunks)?,
+        (false, None, Some((photo, intrinsics))) => {
+            image_renderer.render_overlay(&view, intrinsics, &image::RgbImage::load_png(photo)?)?
+        }
+        (false, None, None) => {
+            if let (Some(output), None) = (&options.output, &options.inline) {
+                return Ok(image_renderer.render_to_file(&view, output, options.format)?);
+            }
+            image_renderer.render(&view, viewport)
+        }
+    };
+    match (&options.output, &options.inline) {
+        (_, Some(protocol)) => {
+            let renderer = inline::from_string(protocol).ok_or("unknown inline protocol")?;
+            println!("{}", renderer.encode(&image));
+            Ok(())
+        }
+        (Some(output), None) => {
+            let mut metadata = render::metadata(&point_cloud, &view);
+            if let Some(count) = chunked_count
+                && let Some((_, points)) = metadata.iter_mut().find(|(key, _)| *key == "Points")
+            {
+                *points = count.to_string();
+            }
+            Ok(image.save(output, options.format, &metadata)?)
+        }
+        (None, None) => Err("no output given".into()),
+    }
+}
+
+// The CPU renderer with the image settings given on the command line
+fn image_renderer<'a>(
+    point_cloud: &'a PointCloud,
+    options: &ImageOptions,
+) -> Result<ImageRenderer<'a>, String> {
+    let mut builder = ImageRenderer::builder(point_cloud)
+        .size(options.width, options.height)
+        .background(options.background)
+        .axes(options.axes)
+        .supersampling(options.supersampling);
+    if let Some(size) = options.point_size {
+        builder = builder.point_size(size);
+    }
+    builder.build()
+}
+
+// The GPU renderer if asked for, otherwise the CPU one
+fn cloud_renderer(
+    point_cloud: &PointCloud,
+    gpu: bool,
+) -> Result<Box<dyn Renderer + '_>, Box<dyn error::Error>> {
+    match gpu {
+        #[cfg(feature = "gpu")]
+        true => Ok(Box::new(gpu::WgpuRenderer::new(point_cloud)?)),
+        #[cfg(not(feature = "gpu"))]
+        true => Err("this build has no GPU support (build with --features gpu)".into()),
+        false => Ok(Box::new(ImageRenderer::new(point_cloud))),
+    }
+}
+
+// The viewer in a native window instead of the terminal, drawn by the GPU
+// renderer in builds that have it
+fn open_window(options: RunOptions) -> Result<(), Box<dyn error::Error>> {
+    let point_cloud = load_multiple_files(
+        &options.file_paths,
+        &options.csv_options,
+        options.coordinate_system,
+    )?;
+    if point_cloud.is_empty() {
+        return Err("no points found".into());
+    }
+
+    #[cfg(feature = "window")]
+    return window::run(
+        &point_cloud,
+        cloud_renderer(&point_cloud, cfg!(feature = "gpu"))?,
+        InputMapper::new(options.key_bindings),
+        options.mouse,
+        options.bounds_percentile,
+    );
+    #[cfg(not(feature = "window"))]
+    Err("this build has no window support (build with --features window)".into())
+}
+
+// One isometric frame as text
+fn ascii_file(options: &AsciiOptions) -> Result<String, Box<dyn error::Error>> {
+    let point_cloud = PointCloud::from_file(
+        &options.file_path,
+        &options.csv_options,
+        options.coordinate_system,
+    )?;
+    if point_cloud.is_empty() {
+        return Err("no points found".into());
+    }
+
+    let renderer = AsciiRenderer::builder(&point_cloud)
+        .size(options.columns, options.rows)
+        .charset(options.charset)
+        .color(options.color)
+        .axes(options.axes)
+        .build()?;
+    let (yaw, pitch) = Preset::Isometric.angles();
+    let view = ViewState::framing(&point_cloud, renderer.viewport(), yaw, pitch);
+    Ok(renderer.render(&view))
+}
+
+// The chart as text, or None once it's written to a PNG
+fn plot_file(options: &PlotOptions) -> Result<Option<String>, Box<dyn error::Error>> {
+    let mut plot = if options.surface {
+        let z_grid = plot::parse_grid(&fs::read_to_string(&options.file_path)?)?;
+        let xs: Vec<f32> = (0..z_grid.first().map_or(0, Vec::len))
+            .map(|column| column as f32)
+            .collect();
+        let ys: Vec<f32> = (0..z_grid.len()).map(|row| row as f32).collect();
+        plot::surface(&xs, &ys, &z_grid, options.wireframe)?
+    } else {
+        let point_cloud = PointCloud::from_file(
+            &options.file_path,
+            &options.csv_options,
+            options.coordinate_system,
+        )?;
+        if point_cloud.is_empty() {
+            return Err("no points found".into());
+        }
+        // Charts stand on x and y with z as height, so the file's up axis
+        // becomes z
+        let points: Vec<(f32, f32, f32)> = point_cloud
+            .points()
+            .iter()
+            .map(|point| CoordinateSystem::ZUp.to_data(point.x, point.y, point.z))
+            .collect();
+        match options.bars {
+            Some(style) => {
+                let positions: Vec<(f32, f32)> = points.iter().map(|&(x, y, _)| (x, y)).collect();
+                let heights: Vec<f32> = points.iter().map(|&(_, _, z)| z).collect();
+                plot::bars3d(&positions, &heights, style)?
+            }
+            None if options.trajectory => plot::trajectory(&points),
+            None => plot::scatter(&points),
+        }
+    };
+
+    if let Some((start, end)) = options.gradient {
+        plot = plot.with_gradient(start, end);
+    }
+    if let Some(colormap) = options.colormap {
+        plot = plot.colormap(colormap);
+    }
+    if let Some(title) = &options.title {
+        plot = plot.title(title);
+    }
+    match &options.output {
+        Some(output) => {
+            plot.save_png(output)?;
+            Ok(None)
         }
+        None => Ok(Some(plot.to_ascii(options.columns, options.rows)?)),
+    }
+}
+
+// Turntable GIF around the points, framed like `render` frames them
+fn animate_file(options: &AnimateOptions) -> Result<(), Box<dyn error::Error>> {
+    let mut point_cloud = PointCloud::from_file(
+        &options.file_path,
+        &options.csv_options,
+        options.coordinate_system,
+    )?;
+    if point_cloud.is_empty() {
+        return Err("no points found".into());
+    }
+    let quantizer = quantize::from_string(&options.quantizer).ok_or("unknown quantizer")?;
+
+    let viewport = Viewport::new(options.image.width, options.image.height);
+    let (yaw, pitch) = Preset::Isometric.angles();
+    let view = ViewState::framing(&point_cloud, viewport, yaw, pitch);
+    let mut views = render::turntable(&view, options.orbit, options.frames);
+    if let Some(fov) = options.dolly_zoom {
+        views = render::dolly_zoom(&views, fov);
     }
+    let images = if options.compact {
+        // Only the compact copy is kept while the frames render
+        let mut axes = mem::take(&mut point_cloud.axes);
+        if !options.image.axes {
+            axes.clear();
+        }
+        let compact = CompactCloud::from_cloud(&point_cloud);
+        let full_size = point_cloud.len() * mem::size_of::<Point3D>();
+        drop(point_cloud);
+        println!(
+            "Compact storage: {} points in {:.1} MB instead of {:.1} MB",
+            compact.point_count(),
+            compact.memory_size() as f32 / 1e6,
+            full_size as f32 / 1e6
+        );
+        views
+            .iter()
+            .map(|view| render::render_compact(&compact, &axes, view, viewport))
+            .collect()
+    } else {
+        let mut renderer = match options.gpu {
+            true => cloud_renderer(&point_cloud, true)?,
+            false => Box::new(image_renderer(&point_cloud, &options.image)?),
+        };
+        render::render_animation(renderer.as_mut(), &views, viewport)
+    };
+    let frames: Vec<(image::RgbImage, u16)> = images
+        .into_iter()
+        .map(|frame| (frame, options.delay))
+        .collect();
+    Ok(fs::write(
+        &options.output,
+        image::encode_gif(&frames, quantizer.as_ref()),
+    )?)
 }
 
-fn run_application(file_paths: Vec<String>) {
-    terminal::enable_raw_mode().unwrap();
-    execute!(io::stdout(), cursor::Hide, event::EnableMouseCapture,).unwrap();
+fn run_application(options: RunOptions) {
+    // Restores the terminal when the viewer returns or panics
+    let _terminal = match TerminalGuard::enter(TerminalOptions::new()) {
+        Ok(guard) => guard,
+        Err(e) => error_close(&format!("Failed to set up the terminal: {}", e)),
+    };
+
+    // Start live sources before the (possibly slow) file loads
+    let mut streams = Vec::new();
+    if options.stdin {
+        streams.push(StreamingCloud::stdin());
+    }
+    if let Some(address) = &options.listen {
+        match StreamingCloud::listen_tcp(address) {
+            Ok(stream) => streams.push(stream),
+            Err(e) => error_close(&format!("Failed to listen on {}: {}", address, e)),
+        }
+    }
+    if let Some(address) = &options.listen_udp {
+        match StreamingCloud::listen_udp(address) {
+            Ok(stream) => streams.push(stream),
+            Err(e) => error_close(&format!("Failed to listen on {}: {}", address, e)),
+        }
+    }
 
-    // Load point cloud(s)
-    let mut point_cloud = match load_multiple_files(&file_paths) {
-        Ok(cloud) => cloud,
-        Err(error) => error_close(&error),
+    // Load point cloud(s); a stream-only session starts empty
+    let mut point_cloud = if options.file_paths.is_empty() {
+        PointCloud::new()
+    } else {
+        match load_multiple_files(
+            &options.file_paths,
+            &options.csv_options,
+            options.coordinate_system,
+        ) {
+            Ok(cloud) => cloud,
+            Err(error) => error_close(&error),
+        }
     };
 
-    if point_cloud.points.is_empty() {
+    if point_cloud.is_empty() && streams.is_empty() {
         error_close(&"No points found in any files");
     }
 
+    let mut watcher = options.watch.then(|| FileWatcher::new(&options.file_paths));
+
     // Get dimensions
-    let center = point_cloud.get_center();
-    let diagonal = point_cloud.get_diagonal().max(1.0); // Ensure we don't get zero diagonal
+    let (center, mut diagonal) = fit_box(&point_cloud, options.bounds_percentile);
 
     // Setup camera
-    let mut camera = Camera::new(center, 0., 0., 0., VIEWPORT_DISTANCE, VIEWPORT_FOV);
+    execute!(io::stdout(), cursor::MoveTo(0, 0), Clear(ClearType::All)).unwrap();
+    let mut camera = Camera::new(center, VIEWPORT_DISTANCE, options.fov);
+    camera.screen.background = options.background;
+    camera.screen.color = options.color;
+    camera.screen.ansi_256 = options.ansi_256;
+    let backend = backend::from_string(&options.backend).unwrap_or_else(|| {
+        error_close(&format!("Unknown backend: {}", options.backend));
+    });
+    camera.screen.cell_size = backend.cell_size();
 
-    let mut view_yaw: f32 = std::f32::consts::PI / 2.0;
-    let mut view_pitch: f32 = 0.0;
-    let mut distance_to_data = diagonal * INITIAL_DISTANCE_MULTIPLIER;
-    let mut pan_mode = false;
+    let mut viewer = Viewer {
+        camera,
+        backend,
+        view: initial_view(&point_cloud, options.bounds_percentile),
+        crosshair: None,
+        layout: Layout::Single,
+        panes: Vec::new(),
+        panes_linked: true,
+        stride: 1,
+        last_motion: time::Instant::now(),
+        inertia: Inertia::new(),
+    };
 
     // Setup events
-    let mut mouse_speed: (f32, f32) = (0., 0.);
-    let mut last_mouse_position = Point2D::new(0, 0);
-    let mut center_point = center;
+    let mut input_mapper = InputMapper::new(options.key_bindings);
 
     // Setup command state
-    let mut command_state = CommandState::new();
+    let mut command_state =
+        CommandState::new(options.csv_options, options.coordinate_system, options.seed);
+    command_state.mouse = options.mouse;
+    command_state.bounds_percentile = options.bounds_percentile;
+    // The view and axes were fitted to every frame, so playback stays in view
+    command_state.sequence = options
+        .sequence
+        .and_then(|fps| CloudSequence::from_sources(&point_cloud, fps));
+
+    // Simulated time still to be stepped through, carried over between frames
+    let mut accumulator = Duration::ZERO;
+    let mut previous_start = time::Instant::now();
 
     // Start main loop
     loop {
         let start = time::Instant::now();
-        let mut start_mouse_position = last_mouse_position;
+        let frame_time = start - previous_start;
+        previous_start = start;
+
+        // Hot-reload watched files, keeping the camera where it is. Only the
+        // changed file's points are replaced; a file that fails to parse
+        // (e.g. mid-write) leaves its points untouched.
+        if let Some(watcher) = &mut watcher {
+            let mut reloaded = false;
+            for path in watcher.changed() {
+                // Files dropped with /remove stay unloaded
+                let Some(index) = point_cloud
+                    .sources
+                    .iter()
+                    .position(|source| *source == path)
+                else {
+                    continue;
+                };
+                reloaded |= point_cloud
+                    .reload_source(
+                        index,
+                        &command_state.csv_options,
+                        command_state.coordinate_system,
+                    )
+                    .is_ok();
+            }
+            if reloaded {
+                diagonal = fit_box(&point_cloud, command_state.bounds_percentile).1;
+            }
+        }
+
+        // Pick up streamed points
+        if !streams.is_empty() {
+            let previous_count = point_cloud.len();
+            let added: usize = streams
+                .iter()
+                .map(|stream| stream.drain_into(&mut point_cloud, command_state.coordinate_system))
+                .sum();
+            if let Some(heat) = &mut command_state.heat {
+                heat.add(&point_cloud.points()[previous_count..]);
+            }
+            if added > 0 {
+                point_cloud.axes = PointCloud::generate_axes_public(point_cloud.points());
+
+                // Frame the data once the first points of an empty session arrive
+                if previous_count == 0 {
+                    let center;
+                    (center, diagonal) = fit_box(&point_cloud, command_state.bounds_percentile);
+                    viewer.view.center = center;
+                    viewer.view.distance = diagonal * INITIAL_DISTANCE_MULTIPLIER;
+                }
+            }
+        }
 
         // Look through the queue while there is an available event
-        let mut event_count = 0;
         while event::poll(Duration::from_secs(0)).unwrap() {
-            if let Ok(event) = event::read() {
-                match event {
-                    event::Event::Key(key_event) => {
-                        if command_state.active {
-                            // Handle command mode input
-                            match key_event.code {
-                                event::KeyCode::Esc => {
-                                    command_state.exit_command_mode();
-                                }
-                                event::KeyCode::Enter => {
-                                    command_state.execute_command(&mut point_cloud);
-                                }
-                                event::KeyCode::Backspace => {
-                                    command_state.backspace();
-                                }
-                                event::KeyCode::Char(c) => {
-                                    command_state.add_char(c);
-                                }
-                                _ => {}
-                            }
-                        } else {
-                            // Handle normal mode input
-                            let is_ctrl_c = key_event.modifiers == event::KeyModifiers::CONTROL
-                                && key_event.code == event::KeyCode::Char('c');
-
-                            if is_ctrl_c {
-                                graceful_close()
-                            } else if key_event.code == event::KeyCode::Char('/') {
-                                command_state.enter_command_mode();
-                            }
-                        }
+            let Ok(event) = event::read() else {
+                continue;
+            };
+            let mode = if command_state.active {
+                InputMode::Command
+            } else if viewer.crosshair.is_some() {
+                InputMode::Pick
+            } else {
+                InputMode::Navigate
+            };
+            // Drags are measured in braille pixels whatever the backend, so
+            // they turn the view as far
+            let viewport = viewer.camera.screen.viewport;
+            let (cell_width, cell_height) = viewer.camera.screen.cell_size;
+            let viewport = Viewport::new(
+                viewport.width / cell_width * BRAILLE_CELL.0,
+                viewport.height / cell_height * BRAILLE_CELL.1,
+            );
+            let Some(input) = input_mapper.map(&event, mode, viewport, command_state.mouse) else {
+                continue;
+            };
+
+            match input {
+                InputEvent::Orbit { .. }
+                | InputEvent::Tumble(_)
+                | InputEvent::Pan { .. }
+                | InputEvent::Zoom(_) => {
+                    viewer.view.navigate(input, diagonal);
+                    viewer.inertia.push(input);
+                    viewer.last_motion = time::Instant::now();
+                }
+                InputEvent::Hold(held) => viewer.inertia.hold(held),
+                InputEvent::View(ViewChange::Reset) => {
+                    viewer.view = initial_view(&point_cloud, command_state.bounds_percentile);
+                    viewer.inertia.stop();
+                    viewer.last_motion = time::Instant::now();
+                }
+                InputEvent::View(ViewChange::Preset(preset)) => {
+                    let percentile = command_state.bounds_percentile;
+                    view_preset(&mut viewer.view, preset, &point_cloud, percentile);
+                    viewer.inertia.stop();
+                    viewer.last_motion = time::Instant::now();
+                }
+                InputEvent::Pick(PickInput::Toggle) => {
+                    // Start picking from the middle of the view
+                    let viewport = viewer.camera.screen.viewport;
+                    let (cell_width, cell_height) = viewer.camera.screen.cell_size;
+                    viewer.crosshair = match viewer.crosshair {
+                        Some(_) => None,
+                        None => Some(Point2D::new(
+                            (viewport.width / cell_width / 2) as i32,
+                            (viewport.height / cell_height / 2) as i32,
+                        )),
+                    };
+                }
+                InputEvent::Pick(PickInput::Point(cell)) => {
+                    if viewer.crosshair.is_some() {
+                        viewer.crosshair = Some(cell);
+                    }
+                }
+                InputEvent::Pick(PickInput::Step { x, y }) => {
+                    // Stop at the edges of the view
+                    let viewport = viewer.camera.screen.viewport;
+                    let (cell_width, cell_height) = viewer.camera.screen.cell_size;
+                    let last_column = (viewport.width / cell_width).max(1) as i32 - 1;
+                    let last_row = (viewport.height / cell_height).max(1) as i32 - 1;
+                    if let Some(cell) = &mut viewer.crosshair {
+                        cell.x = (cell.x + x).clamp(0, last_column);
+                        cell.y = (cell.y + y).clamp(0, last_row);
+                    }
+                }
+                InputEvent::Command(CommandInput::Open) => command_state.enter_command_mode(),
+                InputEvent::Command(CommandInput::Insert(c)) => command_state.add_char(c),
+                InputEvent::Command(CommandInput::Backspace) => command_state.backspace(),
+                InputEvent::Command(CommandInput::Submit) => {
+                    command_state.execute_command(&mut point_cloud, &mut viewer.view);
+                }
+                InputEvent::Command(CommandInput::Cancel) => command_state.exit_command_mode(),
+                InputEvent::Resize { columns, rows } => {
+                    let cell_size = viewer.camera.screen.cell_size;
+                    // Whatever the terminal reflowed is gone; draw everything again
+                    if viewer.layout == Layout::Single {
+                        viewer
+                            .camera
+                            .screen
+                            .resize(Viewport::from_terminal(columns, rows, cell_size));
                     }
+                    execute!(io::stdout(), Clear(ClearType::All)).unwrap();
+                    viewer.camera.screen.invalidate();
+                }
+                InputEvent::CycleLayout if options.backend != "braille" => {
+                    command_state.message =
+                        Some("Split-screen layouts need --backend braille".to_string());
+                }
+                InputEvent::CycleLayout => {
+                    viewer.layout = viewer.layout.next();
+                    viewer.panes = viewer
+                        .layout
+                        .presets()
+                        .iter()
+                        .map(|&preset| {
+                            let mut camera = Camera::new(center, VIEWPORT_DISTANCE, options.fov);
+                            camera.screen.background = options.background;
+                            camera.screen.color = options.color;
+                            camera.screen.ansi_256 = options.ansi_256;
+                            Pane::new(preset, camera, &viewer.view)
+                        })
+                        .collect();
 
-                    // Mouse controls (only when not in command mode)
-                    event::Event::Mouse(mouse_event) if !command_state.active => {
-                        let (x, y) = (mouse_event.column, mouse_event.row);
-                        match mouse_event.kind {
-                            event::MouseEventKind::Down(_) => {
-                                pan_mode = mouse_event.modifiers == event::KeyModifiers::CONTROL;
-                                last_mouse_position.x = x as i32;
-                                last_mouse_position.y = y as i32;
-                                start_mouse_position = last_mouse_position;
-                                event_count += 1;
-                            }
-
-                            event::MouseEventKind::Drag(_) => {
-                                pan_mode = mouse_event.modifiers == event::KeyModifiers::CONTROL;
-                                let delta_x = x as f32 - start_mouse_position.x as f32;
-                                let delta_y = start_mouse_position.y as f32 - y as f32;
-                                mouse_speed.0 =
-                                    delta_x / camera.screen.width as f32 * MOUSE_SPEED_MULTIPLIER;
-                                mouse_speed.1 =
-                                    delta_y / camera.screen.width as f32 * MOUSE_SPEED_MULTIPLIER;
-                                last_mouse_position.x = x as i32;
-                                last_mouse_position.y = y as i32;
-                                event_count += 1;
-                            }
-
-                            event::MouseEventKind::ScrollDown => {
-                                distance_to_data += diagonal * SCROLL_MULTIPLIER;
-                            }
-
-                            event::MouseEventKind::ScrollUp => {
-                                distance_to_data -= diagonal * SCROLL_MULTIPLIER;
-                                distance_to_data = distance_to_data.max(0.1);
-                            }
-                            _ => {}
+                    // Panes don't cover the same cells as the previous layout
+                    execute!(io::stdout(), Clear(ClearType::All)).unwrap();
+                    viewer.camera.screen.invalidate();
+                }
+                InputEvent::LinkPanes => viewer.panes_linked = !viewer.panes_linked,
+                InputEvent::ToggleAxes => command_state.axes = !command_state.axes,
+                InputEvent::Playback(playback) => {
+                    if let Some(sequence) = &mut command_state.sequence {
+                        match playback {
+                            PlaybackInput::Toggle => sequence.toggle(),
+                            PlaybackInput::Step(frames) => sequence.step(frames),
                         }
                     }
-                    _ => {}
                 }
+                InputEvent::Quit => return,
             }
         }
 
-        // If no event happened, reset the mouse
-        if event_count == 0 {
-            mouse_speed = (0., 0.);
-            pan_mode = false;
+        // Advance in fixed steps, so motion doesn't depend on how fast frames
+        // render. A long stall (e.g. a slow reload) is dropped, not caught up on.
+        accumulator += frame_time.min(MAX_FRAME_TIME);
+        while accumulator >= UPDATE_TIMESTEP {
+            viewer.update(UPDATE_TIMESTEP.as_secs_f32(), &mut command_state, diagonal);
+            accumulator -= UPDATE_TIMESTEP;
+        }
+        if let Some(sequence) = &mut command_state.sequence {
+            sequence.show(&mut point_cloud);
         }
 
-        // Update viewer params
-        if pan_mode {
-            // Handle horizontal pan
-            center_point.x -= mouse_speed.0 * camera.yaw.cos() * diagonal * PAN_MULTIPLIER;
-            center_point.z += mouse_speed.0 * camera.yaw.sin() * diagonal * PAN_MULTIPLIER;
+        viewer.draw(&point_cloud, &mut command_state, frame_time);
 
-            // Handle vertical pan
-            center_point.y -= mouse_speed.1 * camera.pitch.cos() * diagonal * PAN_MULTIPLIER;
-            center_point.x +=
-                mouse_speed.1 * camera.yaw.sin() * camera.pitch.sin() * diagonal * PAN_MULTIPLIER;
-            center_point.z +=
-                mouse_speed.1 * camera.yaw.cos() * camera.pitch.sin() * diagonal * PAN_MULTIPLIER;
-        } else {
-            view_yaw -= mouse_speed.0;
-            view_pitch -= mouse_speed.1;
+        // Add buffer time to hit 60 fps
+        if let Some(time) = TARGET_DURATION_PER_FRAME.checked_sub(start.elapsed()) {
+            thread::sleep(time);
+        }
+    }
+}
+
+// What the interactive session shows and from where
+struct Viewer {
+    camera: Camera,
+    backend: Box<dyn Backend>, // Prints the main view's frames
+    view: ViewState,
+    crosshair: Option<Point2D>, // Terminal cell under the picking crosshair, while picking points
+
+    // Split-screen views from fixed directions next to the interactive one
+    layout: Layout,
+    panes: Vec<Pane>,
+    panes_linked: bool,
+
+    // Adaptive drawing: every `stride`th point is drawn while the view moves
+    stride: usize,
+    last_motion: time::Instant, // Last time input moved the camera
+
+    inertia: Inertia, // How the view glides on after input, with --inertia
+}
+
+impl Viewer {
+    // Move time-driven state on by `dt` seconds
+    fn update(&mut self, dt: f32, command_state: &mut CommandState, diagonal: f32) {
+        if let Some(speed) = command_state.spin {
+            self.view.orbit(speed * dt, 0.0);
+        }
+        if let Some(friction) = command_state.mouse.friction
+            && self.inertia.update(&mut self.view, dt, friction, diagonal)
+        {
+            self.last_motion = time::Instant::now();
         }
+        if let Some(heat) = &mut command_state.heat {
+            heat.decay(dt);
+        }
+        if let Some(sequence) = &mut command_state.sequence {
+            sequence.advance(dt);
+        }
+    }
+
+    // Whether the camera has stopped long enough to be worth drawing in full
+    fn is_idle(&self, command_state: &CommandState) -> bool {
+        command_state.spin.is_none() && self.last_motion.elapsed() > IDLE_DELAY
+    }
 
-        // Update camera position
-        camera.coordinates.z =
-            -view_yaw.cos() * view_pitch.cos() * distance_to_data + center_point.z;
-        camera.coordinates.x =
-            view_yaw.sin() * view_pitch.cos() * distance_to_data + center_point.x;
-        camera.coordinates.y = view_pitch.sin() * distance_to_data + center_point.y;
-        camera.yaw = -view_yaw;
-        camera.pitch = -view_pitch;
+    // Thin the points out while drawing them takes longer than a frame, and
+    // fill them back in once there's time to spare. Drawing time is roughly
+    // proportional to the number of points drawn.
+    fn adapt_stride(&mut self, draw_time: Duration) {
+        let budget = TARGET_DURATION_PER_FRAME;
+        if draw_time > budget || (draw_time < budget / 2 && self.stride > 1) {
+            let ratio = draw_time.as_secs_f32() / (budget.as_secs_f32() * STRIDE_TARGET);
+            self.stride = ((self.stride as f32 * ratio).ceil() as usize).clamp(1, MAX_STRIDE);
+        }
+    }
 
-        // Render
-        camera.screen.fit_to_terminal();
-        camera.screen.clear();
+    // Render the views and the status line. `frame_time` is how long the
+    // previous frame took, for the fps readout.
+    fn draw(
+        &mut self,
+        point_cloud: &PointCloud,
+        command_state: &mut CommandState,
+        frame_time: Duration,
+    ) {
+        let adapting = command_state.adaptive && !self.is_idle(command_state);
+        let stride = if adapting { self.stride } else { 1 };
 
-        // Render axes with arrowheads and labels
-        for axis in &point_cloud.axes {
-            // Draw main axis line
-            camera.plot_line(&axis.axis_line.0, &axis.axis_line.1);
+        let camera = &mut self.camera;
+        self.view.apply(camera);
+        let draw_start = time::Instant::now();
 
-            // Draw arrowhead lines
-            for (start, end) in &axis.arrowhead_lines {
-                camera.plot_line(start, end);
+        // Printing doesn't depend on the number of points, so only drawing is timed
+        let draw_time;
+        if self.layout == Layout::Single {
+            camera.screen.fit_to_terminal();
+            draw_frame(camera, point_cloud, command_state, stride);
+            if let Some(cell) = self.crosshair {
+                camera.screen.draw_crosshair(
+                    &crosshair_pixel(cell, camera.screen.cell_size),
+                    CROSSHAIR_COLOR,
+                );
             }
-        }
+            draw_time = draw_start.elapsed();
+            self.backend.present(&mut camera.screen);
+        } else {
+            let (columns, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+            let pane_viewport = self.layout.pane_viewport(columns, rows);
+
+            camera.screen.resize(pane_viewport);
+            draw_frame(camera, point_cloud, command_state, stride);
+            if let Some(cell) = self.crosshair {
+                camera.screen.draw_crosshair(
+                    &crosshair_pixel(cell, camera.screen.cell_size),
+                    CROSSHAIR_COLOR,
+                );
+            }
+            for pane in &mut self.panes {
+                if self.panes_linked {
+                    pane.follow(&self.view);
+                }
+                pane.view.apply(&mut pane.camera);
+                pane.camera.screen.resize(pane_viewport);
+                draw_frame(&mut pane.camera, point_cloud, command_state, stride);
+            }
+
+            draw_time = draw_start.elapsed();
 
-        // Render points as vertices
-        for point in &point_cloud.points {
-            camera.plot_point(point);
+            let screens: Vec<&Screen> = iter::once(&camera.screen)
+                .chain(self.panes.iter().map(|pane| &pane.camera.screen))
+                .collect();
+            layout::render_panes(&screens, self.layout.columns());
         }
 
-        camera.screen.render();
+        if let Some(recorder) = &mut command_state.recording {
+            recorder.capture(&camera.screen);
+        }
 
-        // Add buffer time to hit 60 fps
-        if let Some(time) = TARGET_DURATION_PER_FRAME.checked_sub(start.elapsed()) {
-            thread::sleep(time);
+        if let Some(path) = command_state.pending_export.take() {
+            let page = html::frame_to_html(&camera.screen.frame(), camera.screen.background);
+            if let Err(e) = fs::write(&path, page) {
+                command_state.enter_command_mode();
+                command_state.error_message = Some(format!("Failed to export: {}", e));
+            }
         }
 
         // Status message
         let final_msg = if command_state.active || command_state.error_message.is_some() {
             command_state.get_display_text()
+        } else if let Some(message) = &command_state.message {
+            message.clone()
+        } else if let Some(cell) = self.crosshair {
+            // Readout of the point under the crosshair, in data coordinates
+            match camera.pick(point_cloud, &crosshair_pixel(cell, camera.screen.cell_size)) {
+                Some(index) => {
+                    let point = &point_cloud.points()[index];
+                    let (x, y, z) = command_state
+                        .coordinate_system
+                        .to_data(point.x, point.y, point.z);
+                    let source = point_cloud
+                        .source(index)
+                        .map_or("-", |source| point_cloud.sources[source].as_str());
+                    format!(
+                        "point {} at ({:.3}, {:.3}, {:.3}) from {} | 'p' to stop picking",
+                        index, x, y, z, source
+                    )
+                }
+                None => "no point under the crosshair | 'p' to stop picking".to_string(),
+            }
         } else {
-            let fps_msg = format!("fps: {:3.0}", 1. / start.elapsed().as_secs_f32());
+            let fps_msg = format!("fps: {:3.0}", 1. / frame_time.as_secs_f32().max(1e-3));
             let resolution_msg = format!(
                 "resolution: {} x {}",
-                camera.screen.width, camera.screen.height,
+                camera.screen.viewport.width, camera.screen.viewport.height,
             );
-            let points_msg = format!("points: {}", point_cloud.points.len());
+            let hidden = point_cloud.len() - point_cloud.visible_count();
+            let points_msg = match hidden {
+                0 => format!("points: {}", point_cloud.len()),
+                _ => format!(
+                    "points: {} ({} hidden)",
+                    point_cloud.visible_count(),
+                    hidden
+                ),
+            };
+
+            let points_msg = match stride {
+                1 => points_msg,
+                _ => format!("{} (1 in {} drawn)", points_msg, stride),
+            };
+            let points_msg = match &command_state.recording {
+                Some(recorder) => format!("REC {} frames | {}", recorder.frame_count(), points_msg),
+                None => points_msg,
+            };
+            let points_msg = match &command_state.sequence {
+                Some(sequence) => format!(
+                    "frame {}/{}{} | {}",
+                    sequence.current() + 1,
+                    sequence.len(),
+                    if sequence.playing { "" } else { " (paused)" },
+                    points_msg
+                ),
+                None => points_msg,
+            };
 
             let full_msg = format!(
                 "{} | {} | {} | Press '/' for commands",
@@ -359,7 +2283,7 @@ fn run_application(file_paths: Vec<String>) {
             );
             let short_msg = format!("{} | {} | '/' for commands", points_msg, fps_msg);
 
-            match terminal::size().unwrap().0 as usize {
+            match crossterm::terminal::size().unwrap().0 as usize {
                 width if width > full_msg.len() => full_msg,
                 width if width > short_msg.len() => short_msg,
                 _ => format!("{} | '/'", points_msg),
@@ -368,9 +2292,13 @@ fn run_application(file_paths: Vec<String>) {
 
         execute!(
             io::stdout(),
-            terminal::Clear(terminal::ClearType::CurrentLine),
+            Clear(ClearType::CurrentLine),
             style::Print(final_msg),
         )
         .unwrap();
+
+        if adapting {
+            self.adapt_stride(draw_time);
+        }
     }
 }
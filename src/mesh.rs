@@ -0,0 +1,125 @@
+use std::*;
+
+use crate::graphics::{Color, Point3D};
+use crate::rng::Rng;
+
+// Triangle mesh, e.g. a CAD reference model to compare scans against
+pub struct Mesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<[usize; 3]>, // Indices into `vertices`
+}
+
+impl Mesh {
+    // Read the vertices ("v x y z") and faces ("f a b c ...") of a Wavefront
+    // OBJ file. Faces with more than three corners are split into fans;
+    // texture/normal references ("f 1/1/1 ...") and other records are ignored.
+    pub fn from_obj(path: &str) -> Result<Mesh, Box<dyn error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for (line_num, line) in content.lines().enumerate() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coordinates: Result<Vec<f32>, _> = parts.take(3).map(str::parse).collect();
+                    match coordinates.as_deref() {
+                        Ok(&[x, y, z]) => vertices.push([x, y, z]),
+                        _ => {
+                            return Err(format!(
+                                "Invalid vertex on line {}: {}",
+                                line_num + 1,
+                                line
+                            )
+                            .into());
+                        }
+                    }
+                }
+                Some("f") => {
+                    // Indices are 1-based; negative ones count back from the
+                    // latest vertex
+                    let corners: Option<Vec<usize>> = parts
+                        .map(|corner| {
+                            let index: i64 = corner.split('/').next()?.parse().ok()?;
+                            let index = match index {
+                                1.. => index - 1,
+                                ..0 => vertices.len() as i64 + index,
+                                0 => return None,
+                            };
+                            (0..vertices.len() as i64)
+                                .contains(&index)
+                                .then_some(index as usize)
+                        })
+                        .collect();
+                    match corners {
+                        Some(corners) if corners.len() >= 3 => {
+                            for i in 1..corners.len() - 1 {
+                                triangles.push([corners[0], corners[i], corners[i + 1]]);
+                            }
+                        }
+                        _ => {
+                            return Err(
+                                format!("Invalid face on line {}: {}", line_num + 1, line).into()
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if triangles.is_empty() {
+            return Err("No faces found in mesh".into());
+        }
+        Ok(Mesh {
+            vertices,
+            triangles,
+        })
+    }
+
+    // `count` points spread uniformly over the surface: triangles are chosen
+    // in proportion to their area, then a uniform point inside each
+    pub fn sample_surface(&self, count: usize, color: Color, rng: &mut Rng) -> Vec<Point3D> {
+        // Running total of the triangle areas, for picking by area
+        let mut cumulative = Vec::with_capacity(self.triangles.len());
+        let mut total = 0.0;
+        for triangle in &self.triangles {
+            total += self.area(triangle);
+            cumulative.push(total);
+        }
+        if total <= 0.0 {
+            return Vec::new();
+        }
+
+        (0..count)
+            .map(|_| {
+                let target = rng.unit() * total;
+                let index = cumulative
+                    .partition_point(|&area| area <= target)
+                    .min(self.triangles.len() - 1);
+                let [a, b, c] = self.triangles[index].map(|vertex| self.vertices[vertex]);
+
+                // Folding the unit square onto the triangle keeps the density uniform
+                let (mut u, mut v) = (rng.unit(), rng.unit());
+                if u + v > 1.0 {
+                    (u, v) = (1.0 - u, 1.0 - v);
+                }
+                let [x, y, z] = [0, 1, 2]
+                    .map(|axis| a[axis] + u * (b[axis] - a[axis]) + v * (c[axis] - a[axis]));
+                Point3D::new_with_color(x, y, z, color)
+            })
+            .collect()
+    }
+
+    fn area(&self, triangle: &[usize; 3]) -> f32 {
+        let [a, b, c] = triangle.map(|vertex| self.vertices[vertex]);
+        let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let cross = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+        (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt() / 2.0
+    }
+}
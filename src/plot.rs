@@ -0,0 +1,344 @@
+use std::*;
+
+use crate::ascii::AsciiRenderer;
+use crate::graphics::{Color, CoordinateSystem, Point3D, PointCloud, Viewport};
+use crate::image::RgbImage;
+use crate::layout::Preset;
+use crate::render::{ImageRenderer, Renderer};
+use crate::view::ViewState;
+
+const TITLE_SCALE: usize = 4;
+const TITLE_PADDING: usize = 16;
+const TITLE_COLOR: Color = Color::Rgb(200, 200, 200);
+const WIREFRAME_SAMPLES: usize = 512; // Points along a whole row or column of a wireframe
+const TRAJECTORY_SAMPLES: usize = 4096; // Points along a whole trajectory's lines
+const BAR_SAMPLES: usize = 32; // Points across a bar's width
+const BAR_WIDTH: f32 = 0.8; // Of the spacing between neighboring bars
+const VIRIDIS: [Color; 5] = [
+    Color::Rgb(68, 1, 84),
+    Color::Rgb(59, 82, 139),
+    Color::Rgb(33, 145, 140),
+    Color::Rgb(94, 201, 98),
+    Color::Rgb(253, 231, 37),
+];
+
+// How a plot colors its points by height
+#[derive(Copy, Clone, PartialEq)]
+pub enum Colormap {
+    Ramp,    // Blue to red, as in summaries
+    Viridis, // Dark purple to yellow, evenly brighter, so it reads in grayscale
+}
+
+impl Colormap {
+    pub fn from_string(s: &str) -> Option<Colormap> {
+        match s {
+            "ramp" => Some(Colormap::Ramp),
+            "viridis" => Some(Colormap::Viridis),
+            _ => None,
+        }
+    }
+
+    // Color of a normalized value (0..1)
+    pub fn color(self, t: f32) -> Color {
+        match self {
+            Colormap::Ramp => Color::ramp(t),
+            Colormap::Viridis => {
+                let position = t.clamp(0.0, 1.0) * (VIRIDIS.len() - 1) as f32;
+                let index = (position.floor() as usize).min(VIRIDIS.len() - 2);
+                Color::blend(VIRIDIS[index], VIRIDIS[index + 1], position - index as f32)
+            }
+        }
+    }
+}
+
+// A chart with nothing to set up: the points are framed from the isometric
+// direction with axes around them and colored by height, e.g.
+//
+//   plot::scatter(&points).colormap(Colormap::Viridis).title("run 42").save_png("out.png")
+pub struct Plot {
+    point_cloud: PointCloud,
+    title: Option<String>,
+}
+
+// Points given as z-up (x, y, z) data
+pub fn scatter(points: &[(f32, f32, f32)]) -> Plot {
+    let points = points
+        .iter()
+        .map(|&(x, y, z)| {
+            let (x, y, z) = CoordinateSystem::ZUp.to_viewer(x, y, z);
+            Point3D::new(x, y, z)
+        })
+        .collect();
+    Plot::new(points).colormap(Colormap::Ramp)
+}
+
+// Samples in order, e.g. positions over time, joined by lines and colored by
+// how far along they are, blue at the first sample to red at the last. Each
+// point's progress (0..1) is kept as its scalar.
+pub fn trajectory(points: &[(f32, f32, f32)]) -> Plot {
+    let last = points.len().saturating_sub(1).max(1);
+    let samples: Vec<Point3D> = points
+        .iter()
+        .map(|&(x, y, z)| {
+            let (x, y, z) = CoordinateSystem::ZUp.to_viewer(x, y, z);
+            Point3D::new(x, y, z)
+        })
+        .collect();
+
+    // Lines get points in proportion to their length
+    let distance = |a: &Point3D, b: &Point3D| {
+        ((b.x - a.x).powi(2) + (b.y - a.y).powi(2) + (b.z - a.z).powi(2)).sqrt()
+    };
+    let length: f32 = samples
+        .windows(2)
+        .map(|pair| distance(&pair[0], &pair[1]))
+        .sum();
+    let mut line = Vec::with_capacity(samples.len() + TRAJECTORY_SAMPLES);
+    let mut progress = Vec::with_capacity(line.capacity());
+    for (index, pair) in samples.windows(2).enumerate() {
+        let share = distance(&pair[0], &pair[1]) / length.max(f32::EPSILON);
+        let steps = (share * TRAJECTORY_SAMPLES as f32).ceil().max(1.0) as usize;
+        line.push(pair[0]);
+        line.extend(between(pair[0], pair[1], steps));
+        progress.extend(
+            (0..steps).map(|step| (index as f32 + step as f32 / steps as f32) / last as f32),
+        );
+    }
+    line.extend(samples.last());
+    progress.extend(
+        samples
+            .last()
+            .map(|_| (samples.len() - 1) as f32 / last as f32),
+    );
+
+    let mut plot = Plot::new(line);
+    plot.point_cloud.set_scalars(Some(progress));
+    plot.with_gradient(Color::Blue, Color::Red)
+}
+
+// Height field z = f(x, y) on a grid: `z_grid[row][column]` is the height at
+// (xs[column], ys[row]). With `wireframe`, grid neighbors are also joined by
+// lines, as gnuplot's splot draws them.
+pub fn surface(
+    xs: &[f32],
+    ys: &[f32],
+    z_grid: &[Vec<f32>],
+    wireframe: bool,
+) -> Result<Plot, String> {
+    if xs.is_empty() || ys.is_empty() {
+        return Err("a surface needs at least one x and one y".to_string());
+    }
+    if z_grid.len() != ys.len() || z_grid.iter().any(|row| row.len() != xs.len()) {
+        return Err(format!(
+            "the height grid must have {} rows of {} heights, one per y and x",
+            ys.len(),
+            xs.len()
+        ));
+    }
+
+    let at = |column: usize, row: usize| {
+        let (x, y, z) = CoordinateSystem::ZUp.to_viewer(xs[column], ys[row], z_grid[row][column]);
+        Point3D::new(x, y, z)
+    };
+    let mut points = Vec::with_capacity(xs.len() * ys.len());
+    for row in 0..ys.len() {
+        for column in 0..xs.len() {
+            points.push(at(column, row));
+        }
+    }
+    if wireframe {
+        let samples = (WIREFRAME_SAMPLES / xs.len().max(ys.len())).max(2);
+        for row in 0..ys.len() {
+            for column in 0..xs.len() {
+                if column + 1 < xs.len() {
+                    points.extend(between(at(column, row), at(column + 1, row), samples));
+                }
+                if row + 1 < ys.len() {
+                    points.extend(between(at(column, row), at(column, row + 1), samples));
+                }
+            }
+        }
+    }
+    Ok(Plot::new(points).colormap(Colormap::Ramp))
+}
+
+// How `bars3d` draws each bar
+#[derive(Copy, Clone, PartialEq)]
+pub enum BarStyle {
+    Frame, // The twelve edges
+    Solid, // The top and sides filled with points
+}
+
+// 3D histogram: a bar standing on the ground at each (x, y) position, up (or
+// down) to its height. Bars are square, 80% as wide as the closest spacing
+// between positions along x or y, so bars on a grid leave gaps between them.
+pub fn bars3d(positions: &[(f32, f32)], heights: &[f32], style: BarStyle) -> Result<Plot, String> {
+    if positions.len() != heights.len() {
+        return Err(format!(
+            "{} bar positions but {} heights",
+            positions.len(),
+            heights.len()
+        ));
+    }
+    let spacing = |coordinate: fn(&(f32, f32)) -> f32| {
+        let mut values: Vec<f32> = positions.iter().map(coordinate).collect();
+        values.sort_by(f32::total_cmp);
+        values
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .filter(|gap| *gap > f32::EPSILON)
+            .fold(f32::INFINITY, f32::min)
+    };
+    let spacing = spacing(|position| position.0).min(spacing(|position| position.1));
+    let width = BAR_WIDTH * if spacing.is_finite() { spacing } else { 1.0 };
+
+    let mut points = Vec::new();
+    for (&(x, y), &height) in positions.iter().zip(heights) {
+        let at = |dx: f32, dy: f32, z: f32| {
+            let (x, y, z) = CoordinateSystem::ZUp.to_viewer(x + dx * width, y + dy * width, z);
+            Point3D::new(x, y, z)
+        };
+        let rows = ((height.abs() / width * BAR_SAMPLES as f32).ceil() as usize).max(1);
+        let corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+        for (index, &(dx, dy)) in corners.iter().enumerate() {
+            let (next_x, next_y) = corners[(index + 1) % corners.len()];
+            match style {
+                BarStyle::Frame => {
+                    points.push(at(dx, dy, 0.0));
+                    points.extend(between(at(dx, dy, 0.0), at(dx, dy, height), rows));
+                    for z in [0.0, height] {
+                        points.push(at(dx, dy, z));
+                        points.extend(between(at(dx, dy, z), at(next_x, next_y, z), BAR_SAMPLES));
+                    }
+                }
+                // Each side from its corner up to the next one's
+                BarStyle::Solid => {
+                    for row in 0..=rows {
+                        let z = height * row as f32 / rows as f32;
+                        points.push(at(dx, dy, z));
+                        points.extend(between(at(dx, dy, z), at(next_x, next_y, z), BAR_SAMPLES));
+                    }
+                }
+            }
+        }
+        if style == BarStyle::Solid {
+            for row in 1..BAR_SAMPLES {
+                let dy = row as f32 / BAR_SAMPLES as f32 - 0.5;
+                points.extend(between(
+                    at(-0.5, dy, height),
+                    at(0.5, dy, height),
+                    BAR_SAMPLES,
+                ));
+            }
+        }
+    }
+    Ok(Plot::new(points).colormap(Colormap::Ramp))
+}
+
+// Heights for `surface` as text: one row of numbers per line, blank lines
+// and `#` comments skipped
+pub fn parse_grid(content: &str) -> Result<Vec<Vec<f32>>, String> {
+    let mut grid = Vec::new();
+    for (line_num, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row = line
+            .split_whitespace()
+            .map(|value| {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("line {}: invalid height {}", line_num + 1, value))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        grid.push(row);
+    }
+    Ok(grid)
+}
+
+// Points evenly spaced strictly between two others, `samples` steps apart
+fn between(start: Point3D, end: Point3D, samples: usize) -> impl Iterator<Item = Point3D> {
+    (1..samples).map(move |step| {
+        let t = step as f32 / samples as f32;
+        Point3D::new(
+            start.x + (end.x - start.x) * t,
+            start.y + (end.y - start.y) * t,
+            start.z + (end.z - start.z) * t,
+        )
+    })
+}
+
+impl Plot {
+    fn new(points: Vec<Point3D>) -> Plot {
+        Plot {
+            point_cloud: points.into_iter().collect(),
+            title: None,
+        }
+    }
+
+    pub fn colormap(mut self, colormap: Colormap) -> Plot {
+        let (min, max) = self.point_cloud.get_bounds();
+        let height_range = (max.y - min.y).max(f32::EPSILON);
+        for point in self.point_cloud.points_mut() {
+            point.color = colormap.color((point.y - min.y) / height_range);
+        }
+        self
+    }
+
+    // Color a trajectory by progress, from `start` at its first sample to
+    // `end` at its last; points of other plots have no progress and take `start`
+    pub fn with_gradient(mut self, start: Color, end: Color) -> Plot {
+        for index in 0..self.point_cloud.len() {
+            let progress = self.point_cloud.scalar(index).unwrap_or(0.0);
+            self.point_cloud.points_mut()[index].color = Color::blend(start, end, progress);
+        }
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Plot {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    // At the renderer's default size, with the title in the top left
+    pub fn to_image(&self) -> RgbImage {
+        let mut renderer = ImageRenderer::new(&self.point_cloud);
+        let viewport = renderer.viewport();
+        let mut image = renderer.render(&self.view(viewport), viewport);
+        if let Some(title) = &self.title {
+            image.draw_text(
+                TITLE_PADDING,
+                TITLE_PADDING,
+                title,
+                TITLE_SCALE,
+                TITLE_COLOR,
+            );
+        }
+        image
+    }
+
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        self.to_image().save_png(path)
+    }
+
+    // Colored braille text `columns` characters wide and `rows` lines high,
+    // the title taking the first line if there is one
+    pub fn to_ascii(&self, columns: u16, rows: u16) -> Result<String, String> {
+        let rows = rows.saturating_sub(self.title.is_some() as u16);
+        let renderer = AsciiRenderer::builder(&self.point_cloud)
+            .size(columns, rows)
+            .color(true)
+            .build()?;
+        let text = renderer.render(&self.view(renderer.viewport()));
+        Ok(match &self.title {
+            Some(title) => format!("{}\n{}", title, text),
+            None => text,
+        })
+    }
+
+    fn view(&self, viewport: Viewport) -> ViewState {
+        let (yaw, pitch) = Preset::Isometric.angles();
+        ViewState::framing(&self.point_cloud, viewport, yaw, pitch)
+    }
+}
@@ -0,0 +1,298 @@
+use std::*;
+
+use numpy::{PyArray1, PyArray3, PyArrayMethods, PyReadonlyArray2, PyUntypedArrayMethods};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::ascii::{AsciiRenderer, Charset};
+use crate::csv::CsvOptions;
+use crate::graphics::{self, Color, CoordinateSystem, Point3D};
+use crate::layout::Preset;
+use crate::render::{self, Renderer};
+use crate::view::ViewState;
+
+// Python bindings, built as the `altostratus` extension module:
+//
+//     cloud = altostratus.PointCloud(xyz)  # (N, 3) float32 or float64
+//     camera = altostratus.Camera(yaw=30, pitch=20)
+//     png = altostratus.ImageRenderer(800, 600).png(cloud, camera)
+//     print(altostratus.text(cloud, camera, columns=100, rows=40))
+
+// Positions read straight out of the numpy buffer, whatever its strides,
+// without an intermediate copy; each becomes a point of the cloud
+#[derive(FromPyObject)]
+enum Positions<'py> {
+    Single(PyReadonlyArray2<'py, f32>),
+    Double(PyReadonlyArray2<'py, f64>),
+}
+
+impl Positions<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Positions::Single(array) => array.shape()[0],
+            Positions::Double(array) => array.shape()[0],
+        }
+    }
+
+    fn columns(&self) -> usize {
+        match self {
+            Positions::Single(array) => array.shape()[1],
+            Positions::Double(array) => array.shape()[1],
+        }
+    }
+
+    fn get(&self, index: usize) -> [f32; 3] {
+        match self {
+            Positions::Single(array) => {
+                let array = array.as_array();
+                [0, 1, 2].map(|column| array[[index, column]])
+            }
+            Positions::Double(array) => {
+                let array = array.as_array();
+                [0, 1, 2].map(|column| array[[index, column]] as f32)
+            }
+        }
+    }
+}
+
+fn coordinate_system(up: &str) -> PyResult<CoordinateSystem> {
+    CoordinateSystem::from_string(up).ok_or_else(|| {
+        PyValueError::new_err(format!("Unknown coordinate system: {} (z, y or left)", up))
+    })
+}
+
+// The points to draw, in the given coordinate system (z up by default)
+#[pyclass(name = "PointCloud")]
+struct PyPointCloud {
+    cloud: graphics::PointCloud,
+}
+
+#[pymethods]
+impl PyPointCloud {
+    // `points` is an (N, 3) float array; `colors`, if given, an (N, 3) uint8
+    // array of RGB
+    #[new]
+    #[pyo3(signature = (points, colors = None, up = "z"))]
+    fn new(
+        points: Positions<'_>,
+        colors: Option<PyReadonlyArray2<'_, u8>>,
+        up: &str,
+    ) -> PyResult<PyPointCloud> {
+        let coordinate_system = coordinate_system(up)?;
+        if points.columns() != 3 {
+            return Err(PyValueError::new_err("points must be an (N, 3) array"));
+        }
+        let colors = colors.as_ref().map(|colors| colors.as_array());
+        if let Some(colors) = &colors
+            && colors.shape() != [points.len(), 3]
+        {
+            return Err(PyValueError::new_err(
+                "colors must be an (N, 3) uint8 array, one row per point",
+            ));
+        }
+
+        let cloud = (0..points.len())
+            .map(|index| {
+                let [x, y, z] = points.get(index);
+                let (x, y, z) = coordinate_system.to_viewer(x, y, z);
+                match &colors {
+                    Some(colors) => {
+                        let [r, g, b] = [0, 1, 2].map(|channel| colors[[index, channel]]);
+                        Point3D::new_with_color(x, y, z, Color::Rgb(r, g, b))
+                    }
+                    None => Point3D::new(x, y, z),
+                }
+            })
+            .collect();
+        Ok(PyPointCloud { cloud })
+    }
+
+    // Any file the viewer opens
+    #[staticmethod]
+    #[pyo3(signature = (path, up = "z"))]
+    fn load(path: &str, up: &str) -> PyResult<PyPointCloud> {
+        let cloud =
+            graphics::PointCloud::from_file(path, &CsvOptions::new(), coordinate_system(up)?)
+                .map_err(|e| PyIOError::new_err(format!("{}: {}", path, e)))?;
+        Ok(PyPointCloud { cloud })
+    }
+
+    fn __len__(&self) -> usize {
+        self.cloud.len()
+    }
+}
+
+// Where the picture is taken from. Angles are in degrees; with no distance
+// the camera is as close as every point fits the picture.
+#[pyclass(name = "Camera")]
+#[derive(Clone)]
+struct PyCamera {
+    #[pyo3(get, set)]
+    yaw: f32,
+    #[pyo3(get, set)]
+    pitch: f32,
+    #[pyo3(get, set)]
+    roll: f32,
+    #[pyo3(get, set)]
+    distance: Option<f32>,
+    #[pyo3(get, set)]
+    fov: Option<f32>,
+}
+
+#[pymethods]
+impl PyCamera {
+    // Isometric, as the viewer starts
+    #[new]
+    #[pyo3(signature = (yaw = None, pitch = None, roll = 0.0, distance = None, fov = None))]
+    fn new(
+        yaw: Option<f32>,
+        pitch: Option<f32>,
+        roll: f32,
+        distance: Option<f32>,
+        fov: Option<f32>,
+    ) -> PyCamera {
+        let (default_yaw, default_pitch) = Preset::Isometric.angles();
+        PyCamera {
+            yaw: yaw.unwrap_or(default_yaw.to_degrees()),
+            pitch: pitch.unwrap_or(default_pitch.to_degrees()),
+            roll,
+            distance,
+            fov,
+        }
+    }
+}
+
+impl PyCamera {
+    // A view from this camera's angles, framed by `frame`, then turned, zoomed
+    // and moved back as set
+    fn view(&self, frame: impl FnOnce(f32, f32) -> ViewState) -> ViewState {
+        let mut view = frame(self.yaw.to_radians(), self.pitch.to_radians());
+        view.roll_by(self.roll.to_radians());
+        if let Some(fov) = self.fov {
+            view = view.dolly_zoom(fov.to_radians());
+        }
+        if let Some(distance) = self.distance {
+            view.distance = distance;
+        }
+        view
+    }
+}
+
+// Image settings, kept until a cloud is drawn with them
+#[pyclass(name = "ImageRenderer")]
+struct PyImageRenderer {
+    width: u16,
+    height: u16,
+    background: Color,
+    point_size: Option<f32>,
+    axes: bool,
+    supersampling: usize,
+}
+
+#[pymethods]
+impl PyImageRenderer {
+    #[new]
+    #[pyo3(signature = (width = 1920, height = 1080, background = "black", point_size = None, axes = true, supersampling = 1))]
+    fn new(
+        width: u16,
+        height: u16,
+        background: &str,
+        point_size: Option<f32>,
+        axes: bool,
+        supersampling: usize,
+    ) -> PyResult<PyImageRenderer> {
+        let background = Color::from_string(background)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown color: {}", background)))?;
+        Ok(PyImageRenderer {
+            width,
+            height,
+            background,
+            point_size,
+            axes,
+            supersampling,
+        })
+    }
+
+    // The picture as a (height, width, 3) uint8 array
+    #[pyo3(signature = (cloud, camera = None))]
+    fn render<'py>(
+        &self,
+        py: Python<'py>,
+        cloud: &PyPointCloud,
+        camera: Option<PyCamera>,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let image = self.image(cloud, camera)?;
+        let pixels = image.pixels().as_flattened().to_vec();
+        PyArray1::from_vec(py, pixels).reshape([image.height, image.width, 3])
+    }
+
+    // The picture as PNG file contents, which notebooks show as they are
+    #[pyo3(signature = (cloud, camera = None))]
+    fn png<'py>(
+        &self,
+        py: Python<'py>,
+        cloud: &PyPointCloud,
+        camera: Option<PyCamera>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let png = self.image(cloud, camera)?.to_png();
+        Ok(PyBytes::new(py, &png))
+    }
+}
+
+impl PyImageRenderer {
+    fn image(
+        &self,
+        cloud: &PyPointCloud,
+        camera: Option<PyCamera>,
+    ) -> PyResult<crate::image::RgbImage> {
+        let mut builder = render::ImageRenderer::builder(&cloud.cloud)
+            .size(self.width, self.height)
+            .background(self.background)
+            .axes(self.axes)
+            .supersampling(self.supersampling);
+        if let Some(size) = self.point_size {
+            builder = builder.point_size(size);
+        }
+        let mut renderer = builder.build().map_err(PyValueError::new_err)?;
+        let camera = camera.unwrap_or_else(|| PyCamera::new(None, None, 0.0, None, None));
+        let view = camera.view(|yaw, pitch| renderer.auto_frame(yaw, pitch));
+        Ok(renderer.render(&view, renderer.viewport()))
+    }
+}
+
+// The picture as terminal text, as `altostratus ascii` prints it
+#[pyfunction]
+#[pyo3(signature = (cloud, camera = None, columns = 80, rows = 24, color = false, charset = "braille", axes = true))]
+fn text(
+    cloud: &PyPointCloud,
+    camera: Option<PyCamera>,
+    columns: u16,
+    rows: u16,
+    color: bool,
+    charset: &str,
+    axes: bool,
+) -> PyResult<String> {
+    let charset = Charset::from_string(charset)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown charset: {}", charset)))?;
+    let renderer = AsciiRenderer::builder(&cloud.cloud)
+        .size(columns, rows)
+        .charset(charset)
+        .color(color)
+        .axes(axes)
+        .build()
+        .map_err(PyValueError::new_err)?;
+    let camera = camera.unwrap_or_else(|| PyCamera::new(None, None, 0.0, None, None));
+    let view =
+        camera.view(|yaw, pitch| ViewState::framing(&cloud.cloud, renderer.viewport(), yaw, pitch));
+    Ok(renderer.render(&view))
+}
+
+#[pymodule]
+fn altostratus(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyPointCloud>()?;
+    module.add_class::<PyCamera>()?;
+    module.add_class::<PyImageRenderer>()?;
+    module.add_function(wrap_pyfunction!(text, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use std::*;
+
+use collections::HashMap;
+
+use crate::image::RgbImage;
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255]; // xterm color cube steps
+const OCTREE_DEPTH: usize = 8; // One level per bit of each channel
+
+// A few colors to draw an image with
+pub struct Palette {
+    pub colors: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    // Index of the closest color by squared RGB distance
+    pub fn nearest(&self, rgb: [u8; 3]) -> u8 {
+        (0..self.colors.len())
+            .min_by_key(|&i| distance(self.colors[i], rgb))
+            .unwrap_or(0) as u8
+    }
+}
+
+// Chooses the palette for images limited to a few colors, like GIFs and
+// 256-color terminals. Trades quality for speed differently per implementation.
+pub trait Quantizer {
+    // At most `max_colors` colors for a histogram of distinct colors and
+    // their pixel counts
+    fn palette(&self, histogram: &[([u8; 3], u32)], max_colors: usize) -> Palette;
+
+    // Palette entry to draw a color with
+    fn index(&self, palette: &Palette, rgb: [u8; 3]) -> u8 {
+        palette.nearest(rgb)
+    }
+}
+
+pub fn from_string(s: &str) -> Option<Box<dyn Quantizer>> {
+    match s {
+        "xterm" => Some(Box::new(XtermPalette)),
+        "median-cut" => Some(Box::new(MedianCut)),
+        "octree" => Some(Box::new(Octree)),
+        _ => None,
+    }
+}
+
+// Distinct colors of all the images and how many pixels have each, so one
+// palette can be shared by every frame of an animation
+pub fn histogram<'a>(images: impl IntoIterator<Item = &'a RgbImage>) -> Vec<([u8; 3], u32)> {
+    let mut counts: HashMap<[u8; 3], u32> = HashMap::new();
+    for image in images {
+        for &rgb in image.pixels() {
+            *counts.entry(rgb).or_insert(0) += 1;
+        }
+    }
+    counts.into_iter().collect()
+}
+
+// The fixed xterm palette from index 16: a 6x6x6 color cube and 24 grays.
+// Ignores the image, so it's the fastest and matches what a 256-color
+// terminal can show.
+pub struct XtermPalette;
+
+impl Quantizer for XtermPalette {
+    fn palette(&self, _histogram: &[([u8; 3], u32)], _max_colors: usize) -> Palette {
+        let mut colors = Vec::with_capacity(240);
+        for r in CUBE_LEVELS {
+            for g in CUBE_LEVELS {
+                for b in CUBE_LEVELS {
+                    colors.push([r, g, b]);
+                }
+            }
+        }
+        colors.extend((0..24).map(|i| [8 + i * 10; 3]));
+        Palette { colors }
+    }
+
+    fn index(&self, _palette: &Palette, rgb: [u8; 3]) -> u8 {
+        xterm_index(rgb)
+    }
+}
+
+// Position of a color in the xterm palette after the 16 system colors: the
+// closer of the nearest cube color and the nearest gray
+pub fn xterm_index(rgb: [u8; 3]) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs())
+            .unwrap()
+    };
+    let [ri, gi, bi] = rgb.map(level);
+    let cube = [CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]];
+
+    let mean = rgb.iter().map(|&c| c as u32).sum::<u32>() / 3;
+    let gray_index = (mean.saturating_sub(3) / 10).min(23);
+    let gray = [8 + gray_index as u8 * 10; 3];
+
+    if distance(gray, rgb) < distance(cube, rgb) {
+        216 + gray_index as u8
+    } else {
+        (ri * 36 + gi * 6 + bi) as u8
+    }
+}
+
+// Colors of the xterm palette by terminal index (16 to 255)
+pub fn xterm_color(index: u8) -> Option<[u8; 3]> {
+    let index = index.checked_sub(16)?;
+    Some(XtermPalette.palette(&[], 0).colors[index as usize])
+}
+
+// Repeatedly splits the box of colors with the widest channel range at its
+// weighted median. Best quality, slowest.
+pub struct MedianCut;
+
+impl Quantizer for MedianCut {
+    fn palette(&self, histogram: &[([u8; 3], u32)], max_colors: usize) -> Palette {
+        let mut boxes = vec![histogram.to_vec()];
+        while boxes.len() < max_colors {
+            let widest = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, colors)| colors.len() > 1)
+                .map(|(index, colors)| {
+                    let (channel, range) = (0..3)
+                        .map(|channel| {
+                            let values = colors.iter().map(|(rgb, _)| rgb[channel]);
+                            let range = values.clone().max().unwrap() - values.min().unwrap();
+                            (channel, range)
+                        })
+                        .max_by_key(|&(_, range)| range)
+                        .unwrap();
+                    (index, channel, range)
+                })
+                .max_by_key(|&(_, _, range)| range);
+            let Some((index, channel, _)) = widest else {
+                break;
+            };
+
+            let mut colors = boxes.swap_remove(index);
+            colors.sort_by_key(|(rgb, _)| rgb[channel]);
+            let total: u64 = colors.iter().map(|&(_, count)| count as u64).sum();
+            let mut seen = 0;
+            let median = colors
+                .iter()
+                .position(|&(_, count)| {
+                    seen += count as u64;
+                    seen * 2 >= total
+                })
+                .unwrap_or(0);
+            let upper = colors.split_off((median + 1).clamp(1, colors.len() - 1));
+            boxes.push(colors);
+            boxes.push(upper);
+        }
+
+        let colors = boxes
+            .iter()
+            .filter(|colors| !colors.is_empty())
+            .map(|colors| {
+                let mut sum = [0u64; 3];
+                let mut total = 0u64;
+                for &(rgb, count) in colors {
+                    add_weighted(&mut sum, rgb, count);
+                    total += count as u64;
+                }
+                sum.map(|channel| (channel / total.max(1)) as u8)
+            })
+            .collect();
+        with_fallback(colors)
+    }
+}
+
+// Sorts colors into an octree by their bits and merges the least used
+// branches until few enough leaves remain. Faster than median cut, with
+// slightly coarser colors.
+pub struct Octree;
+
+#[derive(Default)]
+struct OctreeNode {
+    children: [Option<usize>; 8],
+    sum: [u64; 3], // Of every color below the node
+    count: u64,
+}
+
+impl Quantizer for Octree {
+    fn palette(&self, histogram: &[([u8; 3], u32)], max_colors: usize) -> Palette {
+        let mut nodes = vec![OctreeNode::default()];
+        let mut parents: Vec<Vec<usize>> = vec![Vec::new(); OCTREE_DEPTH]; // Inner nodes by depth
+        for &(rgb, count) in histogram {
+            let mut node = 0;
+            for (depth, depth_parents) in parents.iter_mut().enumerate() {
+                nodes[node].count += count as u64;
+                add_weighted(&mut nodes[node].sum, rgb, count);
+
+                let bit = 7 - depth;
+                let branch = rgb.iter().fold(0, |branch, &channel| {
+                    branch << 1 | (channel >> bit) as usize & 1
+                });
+                node = match nodes[node].children[branch] {
+                    Some(child) => child,
+                    None => {
+                        if nodes[node].children.iter().all(Option::is_none) {
+                            depth_parents.push(node);
+                        }
+                        nodes.push(OctreeNode::default());
+                        nodes[node].children[branch] = Some(nodes.len() - 1);
+                        nodes.len() - 1
+                    }
+                };
+            }
+            nodes[node].count += count as u64;
+            add_weighted(&mut nodes[node].sum, rgb, count);
+        }
+
+        let is_leaf = |node: &OctreeNode| node.children.iter().all(Option::is_none);
+        let mut leaf_count = nodes.iter().filter(|node| is_leaf(node)).count();
+
+        // Fold the deepest branches back into their parents, rarest first;
+        // a parent whose children are folded becomes a leaf itself
+        for depth in (0..OCTREE_DEPTH).rev() {
+            parents[depth].sort_by_key(|&node| cmp::Reverse(nodes[node].count));
+            while leaf_count > max_colors.max(1) {
+                let Some(node) = parents[depth].pop() else {
+                    break;
+                };
+                let children = nodes[node].children.iter().flatten().count();
+                nodes[node].children = [None; 8];
+                leaf_count -= children - 1;
+            }
+        }
+
+        let mut colors = Vec::new();
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &nodes[node];
+            if is_leaf(node) {
+                if node.count > 0 {
+                    colors.push(node.sum.map(|channel| (channel / node.count) as u8));
+                }
+            } else {
+                stack.extend(node.children.iter().flatten());
+            }
+        }
+        with_fallback(colors)
+    }
+}
+
+// Images without pixels still need a color to point at
+fn with_fallback(colors: Vec<[u8; 3]>) -> Palette {
+    match colors.is_empty() {
+        true => Palette {
+            colors: vec![[0, 0, 0]],
+        },
+        false => Palette { colors },
+    }
+}
+
+fn add_weighted(sum: &mut [u64; 3], rgb: [u8; 3], count: u32) {
+    for (total, channel) in sum.iter_mut().zip(rgb) {
+        *total += channel as u64 * count as u64;
+    }
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> i32 {
+    (0..3).map(|i| (a[i] as i32 - b[i] as i32).pow(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graphics::Color;
+
+    fn gradient() -> RgbImage {
+        let mut image = RgbImage::new(64, 64, Color::Black);
+        for y in 0..64 {
+            for x in 0..64 {
+                image.put(x, y, Color::Rgb(x as u8 * 4, y as u8 * 4, 128));
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn palettes_fit_and_keep_exact_colors() {
+        let histogram = histogram([&gradient()]);
+        let few = [([255, 0, 0], 10), ([0, 0, 255], 3), ([10, 200, 10], 1)];
+        for quantizer in [&MedianCut as &dyn Quantizer, &Octree] {
+            let palette = quantizer.palette(&histogram, 256);
+            assert!(!palette.colors.is_empty() && palette.colors.len() <= 256);
+
+            let palette = quantizer.palette(&few, 16);
+            for (rgb, _) in few {
+                assert_eq!(palette.colors[quantizer.index(&palette, rgb) as usize], rgb);
+            }
+        }
+    }
+
+    #[test]
+    fn xterm_indices_match_the_palette() {
+        for rgb in [
+            [0, 95, 255],
+            [215, 135, 0],
+            [128, 128, 128],
+            [238, 238, 238],
+        ] {
+            let index = xterm_index(rgb);
+            let color = xterm_color(index + 16).unwrap();
+            assert_eq!(XtermPalette.palette(&[], 0).colors[index as usize], color);
+            assert!(
+                distance(color, rgb) <= 3 * 10 * 10,
+                "{:?} for {:?}",
+                color,
+                rgb
+            );
+        }
+        assert_eq!(xterm_color(15), None);
+    }
+}
@@ -0,0 +1,154 @@
+use std::*;
+
+use crate::segmentation::{cross, dot, normalize};
+
+// A rotation as a unit quaternion: turning by angle a about unit axis k is
+// w = cos(a/2), xyz = k sin(a/2)
+#[derive(Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    pub w: f32,
+    pub xyz: [f32; 3],
+}
+
+impl Quaternion {
+    pub const IDENTITY: Quaternion = Quaternion {
+        w: 1.0,
+        xyz: [0.0; 3],
+    };
+
+    // Turning by `angle` radians about the unit vector `axis`
+    pub fn about(axis: [f32; 3], angle: f32) -> Quaternion {
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Quaternion {
+            w: cos,
+            xyz: axis.map(|v| v * sin),
+        }
+    }
+
+    // The shortest rotation taking direction `from` to direction `to`
+    pub fn from_arc(from: [f32; 3], to: [f32; 3]) -> Quaternion {
+        let (from, to) = (normalize(from), normalize(to));
+        // Half the angle comes from normalizing (1 + cos a, k sin a)
+        Quaternion {
+            w: 1.0 + dot(from, to),
+            xyz: cross(from, to),
+        }
+        .normalized()
+    }
+
+    fn normalized(self) -> Quaternion {
+        let [x, y, z] = self.xyz;
+        let length = (self.w * self.w + x * x + y * y + z * z).sqrt();
+        if length <= f32::EPSILON {
+            // Opposite directions: no single shortest rotation, so none at all
+            return Quaternion::IDENTITY;
+        }
+        Quaternion {
+            w: self.w / length,
+            xyz: self.xyz.map(|v| v / length),
+        }
+    }
+
+    // The rotation that turns by the length of `v`, in radians, about `v`
+    pub fn from_rotation_vector(v: [f32; 3]) -> Quaternion {
+        let angle = dot(v, v).sqrt();
+        match angle > f32::EPSILON {
+            true => Quaternion::about(v.map(|v| v / angle), angle),
+            false => Quaternion::IDENTITY,
+        }
+    }
+
+    // The axis scaled by the angle in radians, which unlike the quaternion
+    // itself can be scaled and added up, as for speeds of turning
+    pub fn to_rotation_vector(self) -> [f32; 3] {
+        let sin = dot(self.xyz, self.xyz).sqrt();
+        if sin <= f32::EPSILON {
+            return [0.0; 3];
+        }
+        let angle = 2.0 * sin.atan2(self.w);
+        self.xyz.map(|v| v / sin * angle)
+    }
+
+    // Rows of the same rotation as a matrix
+    pub fn to_matrix(self) -> [[f32; 3]; 3] {
+        let (w, [x, y, z]) = (self.w, self.xyz);
+        [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ]
+    }
+
+    pub fn rotate(self, v: [f32; 3]) -> [f32; 3] {
+        let t = cross(self.xyz, v).map(|t| 2.0 * t);
+        let u = cross(self.xyz, t);
+        [0, 1, 2].map(|i| v[i] + self.w * t[i] + u[i])
+    }
+}
+
+// `a * b` turns by `b`, then by `a`. Renormalized, so long chains of small
+// turns don't drift away from a rotation.
+impl ops::Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Quaternion) -> Quaternion {
+        let (a, b) = (self.xyz, other.xyz);
+        let turn = cross(a, b);
+        Quaternion {
+            w: self.w * other.w - dot(a, b),
+            xyz: [0, 1, 2].map(|i| self.w * b[i] + other.w * a[i] + turn[i]),
+        }
+        .normalized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!(
+            (0..3).all(|i| (a[i] - b[i]).abs() < 1e-5),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn rotation_vectors_round_trip() {
+        for v in [[0.3, -0.2, 0.9], [0.0, 2.5, 0.0], [-1.0, 1.0, 1.0]] {
+            assert_close(Quaternion::from_rotation_vector(v).to_rotation_vector(), v);
+        }
+        assert_close(Quaternion::IDENTITY.to_rotation_vector(), [0.0; 3]);
+    }
+
+    #[test]
+    fn matrix_rotates_like_the_quaternion() {
+        let q = Quaternion::about(normalize([1.0, 2.0, -0.5]), 1.2)
+            * Quaternion::from_arc([1.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        let v = [0.4, -1.0, 2.0];
+        let rows = q.to_matrix();
+        assert_close(q.rotate(v), rows.map(|row| dot(row, v)));
+
+        // A quarter turn about z takes x to y
+        let quarter = Quaternion::about([0.0, 0.0, 1.0], f32::consts::FRAC_PI_2);
+        assert_close(quarter.rotate([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]);
+        assert_close(
+            Quaternion::from_arc([1.0, 0.0, 0.0], [0.0, 1.0, 0.0]).to_rotation_vector(),
+            quarter.to_rotation_vector(),
+        );
+    }
+}
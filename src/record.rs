@@ -0,0 +1,71 @@
+use std::*;
+
+use crate::graphics::Screen;
+use crate::image::{self, RgbImage};
+use crate::quantize::Quantizer;
+
+const FRAME_INTERVAL: time::Duration = time::Duration::from_millis(50); // At most 20 frames per second
+const MIN_DELAY: u16 = 2; // Hundredths of a second; viewers slow down shorter frames
+
+// Frames of an interactive session, kept in memory and written out as an
+// animated GIF when the recording stops
+pub struct Recorder {
+    pub path: String,
+    frames: Vec<(RgbImage, time::Instant)>, // Each frame and when it appeared
+    quantizer: Box<dyn Quantizer>,          // Picks the GIF's colors
+}
+
+impl Recorder {
+    pub fn new(path: &str, quantizer: Box<dyn Quantizer>) -> Recorder {
+        Recorder {
+            path: path.to_string(),
+            frames: Vec::new(),
+            quantizer,
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Add the screen's current frame. Frames that come too soon after the
+    // last one, or don't change anything, only extend how long it is shown.
+    pub fn capture(&mut self, screen: &Screen) {
+        if let Some((_, captured)) = self.frames.last()
+            && captured.elapsed() < FRAME_INTERVAL
+        {
+            return;
+        }
+
+        let frame = screen.to_image();
+        if self.frames.last().is_none_or(|(last, _)| *last != frame) {
+            self.frames.push((frame, time::Instant::now()));
+        }
+    }
+
+    // Write the GIF, timing each frame as it was shown; the last one lasts
+    // until now
+    pub fn finish(self) -> io::Result<()> {
+        let ends: Vec<time::Instant> = self
+            .frames
+            .iter()
+            .skip(1)
+            .map(|(_, captured)| *captured)
+            .chain(iter::once(time::Instant::now()))
+            .collect();
+
+        let frames: Vec<(RgbImage, u16)> = self
+            .frames
+            .into_iter()
+            .zip(ends)
+            .map(|((frame, captured), end)| {
+                let hundredths = ((end - captured).as_millis() / 10).min(u16::MAX as u128);
+                (frame, (hundredths as u16).max(MIN_DELAY))
+            })
+            .collect();
+        fs::write(
+            &self.path,
+            image::encode_gif(&frames, self.quantizer.as_ref()),
+        )
+    }
+}
@@ -0,0 +1,545 @@
+use std::*;
+
+use crate::arrays::PointArrays;
+use crate::chunks::PointChunks;
+use crate::compact::CompactCloud;
+use crate::graphics::{
+    AxisDecoration, Camera, Color, Point3D, PointCloud, PointStyle, Screen, Viewport,
+};
+use crate::image::{ImageFormat, RgbImage};
+use crate::rng::Rng;
+use crate::view::VIEWPORT_DISTANCE;
+use crate::view::{Intrinsics, ViewState};
+
+const DEFAULT_IMAGE_SIZE: (u16, u16) = (1920, 1080); // Pixels, as `altostratus render` defaults to
+const FIRST_PASS_FRACTION: usize = 16; // A progressive view starts with 1 in this many points
+const AUTO_FRAME_MARGIN: f32 = 1.05; // Image size over the framed cloud's, across and down
+const SQUARE_PIXEL_TOLERANCE: f32 = 0.01; // Relative difference in focal lengths taken as square pixels
+
+// Camera path going once around the center by `degrees`, in `frames` even
+// steps; a full turn loops seamlessly since the last frame stops a step short
+pub fn turntable(view: &ViewState, degrees: f32, frames: usize) -> Vec<ViewState> {
+    (0..frames)
+        .map(|frame| {
+            let mut view = *view;
+            view.orbit((degrees * frame as f32 / frames as f32).to_radians(), 0.0);
+            view
+        })
+        .collect()
+}
+
+// The camera path `views` dolly-zoomed from their field of view to `fov` (see
+// `ViewState::dolly_zoom`), evenly from the first frame to the last
+pub fn dolly_zoom(views: &[ViewState], fov: f32) -> Vec<ViewState> {
+    let steps = views.len().saturating_sub(1).max(1) as f32;
+    views
+        .iter()
+        .enumerate()
+        .map(|(frame, view)| {
+            let t = frame as f32 / steps;
+            view.dolly_zoom(view.fov + (fov - view.fov) * t)
+        })
+        .collect()
+}
+
+// Draws one cloud, given when the renderer is made, from any number of views.
+// Implementations may keep the cloud in whatever form draws it fastest.
+pub trait Renderer {
+    fn render(&mut self, view: &ViewState, viewport: Viewport) -> RgbImage;
+}
+
+// The CPU rasterizer, drawing the points as the viewer does. The visible
+// points are copied into separate arrays when it's made, which draw faster
+// than the cloud's own points (see benches/render.rs), and one-pixel points
+// are drawn in bands of the image across all cores.
+pub struct ImageRenderer<'a> {
+    point_cloud: &'a PointCloud, // For the axes
+    arrays: PointArrays,
+    viewport: Viewport, // Size the builder was given
+    background: Color,
+    point_style: PointStyle,
+    axes: bool,
+    supersampling: usize, // Pixels drawn per image pixel, across and down
+}
+
+impl<'a> ImageRenderer<'a> {
+    // Defaults: 1920x1080 one-pixel points over black, with the axes
+    pub fn new(point_cloud: &'a PointCloud) -> ImageRenderer<'a> {
+        ImageRenderer {
+            point_cloud,
+            arrays: PointArrays::from_cloud(point_cloud),
+            viewport: Viewport::new(DEFAULT_IMAGE_SIZE.0, DEFAULT_IMAGE_SIZE.1),
+            background: Color::Black,
+            point_style: PointStyle::Dot,
+            axes: true,
+            supersampling: 1,
+        }
+    }
+
+    pub fn builder(point_cloud: &'a PointCloud) -> ImageRendererBuilder<'a> {
+        ImageRendererBuilder {
+            renderer: ImageRenderer::new(point_cloud),
+            size: DEFAULT_IMAGE_SIZE,
+            point_size: None,
+        }
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    // Looking at the cloud from the given angles, as close as every point
+    // fits the image at this renderer's size and point size
+    pub fn auto_frame(&self, yaw: f32, pitch: f32) -> ViewState {
+        let reach = self.point_style.reach();
+        ViewState::frame_cloud(
+            self.point_cloud,
+            self.viewport,
+            (yaw, pitch),
+            reach,
+            AUTO_FRAME_MARGIN,
+        )
+    }
+
+    // Render from `view` at the builder's size straight to a file, with the
+    // view, the point count and the version that drew it as metadata
+    pub fn render_to_file(
+        &mut self,
+        view: &ViewState,
+        path: &str,
+        format: ImageFormat,
+    ) -> io::Result<()> {
+        let image = self.render(view, self.viewport);
+        image.save(path, format, &metadata(self.point_cloud, view))
+    }
+
+    // Like `render`, then draws the file's points into the same depth buffer
+    // one chunk at a time, so files bigger than memory can be drawn. Made on
+    // the cloud from `PointChunks::bounds`, the renderer frames the view and
+    // fits the axes to the whole file.
+    pub fn render_chunks(
+        &mut self,
+        view: &ViewState,
+        viewport: Viewport,
+        chunks: &PointChunks,
+    ) -> Result<RgbImage, Box<dyn error::Error>> {
+        let mut camera = blank_camera(view, self.drawn_viewport(viewport));
+        camera.point_style = self.point_style;
+        camera.screen.smooth_lines = true;
+        if self.axes {
+            camera.draw_axes(&self.point_cloud.axes);
+        }
+        camera.draw_arrays(&self.arrays, 0..self.arrays.len());
+        for chunk in chunks.iter()? {
+            let arrays = PointArrays::from_points(&chunk?);
+            camera.draw_arrays(&arrays, 0..arrays.len());
+        }
+        camera.screen.background = Some(self.background);
+        Ok(self.finish(&camera.screen))
+    }
+
+    // The cloud drawn over a photo, at its size, as the camera that took it
+    // would have seen the points: from `view`, usually made from the photo's
+    // pose with `ViewState::from_pose`, through the lens the intrinsics
+    // describe (the view's own field of view is ignored). Where the points
+    // and the photo line up shows how well the pose and the scan agree.
+    pub fn render_overlay(
+        &mut self,
+        view: &ViewState,
+        intrinsics: &Intrinsics,
+        photo: &RgbImage,
+    ) -> Result<RgbImage, String> {
+        let Intrinsics { fx, fy, cx, cy } = *intrinsics;
+        if !(fx > 0.0 && fy > 0.0 && [fx, fy, cx, cy].iter().all(|v| v.is_finite())) {
+            return Err("focal lengths must be positive numbers".to_string());
+        }
+        // Points are projected onto square pixels
+        if (fx - fy).abs() > fx * SQUARE_PIXEL_TOLERANCE {
+            return Err(format!(
+                "pixels must be square, but the focal lengths are {} and {}",
+                fx, fy
+            ));
+        }
+
+        // Drawn with the principal point in the middle, on a screen big enough
+        // that the photo's frame around it can be cut out afterwards
+        let (width, height) = (photo.width as f32, photo.height as f32);
+        let half_width = cx.max(width - cx).ceil().max(1.0);
+        let half_height = cy.max(height - cy).ceil().max(1.0);
+        let (left, top) = (
+            (half_width - cx).round() as usize,
+            (half_height - cy).round() as usize,
+        );
+        let padded = (2.0 * half_width, 2.0 * half_height);
+        if padded.0.max(padded.1) * self.supersampling as f32 > u16::MAX as f32 {
+            return Err("the principal point is too far outside the photo".to_string());
+        }
+        let viewport = Viewport::new(padded.0 as u16, padded.1 as u16);
+        let view = ViewState {
+            fov: 2.0 * (half_width / fx).atan(),
+            ..*view
+        };
+
+        let drawn = self.drawn_viewport(viewport);
+        let mut camera = blank_camera(&view, drawn);
+        camera.point_style = self.point_style;
+        camera.screen.smooth_lines = true;
+        if self.axes {
+            camera.draw_axes(&self.point_cloud.axes);
+        }
+        camera.draw_arrays(&self.arrays, 0..self.arrays.len());
+
+        // The photo, scaled up as much as the screen is, under the points
+        let factor = self.supersampling;
+        let mut image = RgbImage::new(drawn.width as usize, drawn.height as usize, self.background);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let pixel = (x / factor)
+                    .checked_sub(left)
+                    .zip((y / factor).checked_sub(top));
+                if let Some(color) = pixel.and_then(|(x, y)| photo.get(x, y)) {
+                    image.put(x, y, color);
+                }
+            }
+        }
+        camera.screen.draw_onto(&mut image);
+        if factor > 1 {
+            image = image.downsample(factor);
+        }
+        Ok(image.crop(left, top, photo.width, photo.height))
+    }
+
+    // Screen size drawn at for an image of `viewport`
+    fn drawn_viewport(&self, viewport: Viewport) -> Viewport {
+        let scale = |size: u16| size.saturating_mul(self.supersampling as u16);
+        Viewport::new(scale(viewport.width), scale(viewport.height))
+    }
+
+    // The drawn screen brought down to image size
+    fn finish(&self, screen: &Screen) -> RgbImage {
+        match self.supersampling {
+            1 => screen.to_image(),
+            factor => screen.to_image().downsample(factor),
+        }
+    }
+}
+
+impl Renderer for ImageRenderer<'_> {
+    fn render(&mut self, view: &ViewState, viewport: Viewport) -> RgbImage {
+        let mut camera = blank_camera(view, self.drawn_viewport(viewport));
+        camera.point_style = self.point_style;
+        camera.screen.smooth_lines = true;
+        if self.axes {
+            camera.draw_axes(&self.point_cloud.axes);
+        }
+        camera.draw_arrays(&self.arrays, 0..self.arrays.len());
+        camera.screen.background = Some(self.background);
+        self.finish(&camera.screen)
+    }
+}
+
+// An `ImageRenderer` for clouds too big to draw at interactive rates. The
+// first frame of a view is a random sample of the points; every further frame
+// of the same view and size adds as many points again to the same screen and
+// depth buffer, until all are drawn. Any other view starts over.
+pub struct ProgressiveRenderer<'a> {
+    renderer: ImageRenderer<'a>, // With its points shuffled
+    camera: Camera,
+    shown: Option<(ViewState, Viewport)>, // What the camera has drawn so far
+    drawn: usize,                         // Points drawn of the shown view
+}
+
+impl<'a> ProgressiveRenderer<'a> {
+    pub fn new(mut renderer: ImageRenderer<'a>) -> ProgressiveRenderer<'a> {
+        renderer.arrays.shuffle(&mut Rng::new(0));
+        ProgressiveRenderer {
+            renderer,
+            camera: Camera::new(Point3D::new(0., 0., 0.), 0., 0.),
+            shown: None,
+            drawn: 0,
+        }
+    }
+}
+
+impl Renderer for ProgressiveRenderer<'_> {
+    fn render(&mut self, view: &ViewState, viewport: Viewport) -> RgbImage {
+        let same_view = self.shown.is_some_and(|(shown, shown_viewport)| {
+            shown_viewport == viewport
+                && (shown.orientation, shown.distance, shown.fov)
+                    == (view.orientation, view.distance, view.fov)
+                && (shown.center.x, shown.center.y, shown.center.z)
+                    == (view.center.x, view.center.y, view.center.z)
+        });
+        let renderer = &self.renderer;
+        if !same_view {
+            self.camera = blank_camera(view, renderer.drawn_viewport(viewport));
+            self.camera.point_style = renderer.point_style;
+            self.camera.screen.smooth_lines = true;
+            self.camera.screen.background = Some(renderer.background);
+            if renderer.axes {
+                self.camera.draw_axes(&renderer.point_cloud.axes);
+            }
+            self.shown = Some((*view, viewport));
+            self.drawn = 0;
+        }
+
+        let total = renderer.arrays.len();
+        let first_pass = total.div_ceil(FIRST_PASS_FRACTION);
+        let end = (self.drawn + self.drawn.max(first_pass)).min(total);
+        self.camera.draw_arrays(&renderer.arrays, self.drawn..end);
+        self.drawn = end;
+        renderer.finish(&self.camera.screen)
+    }
+}
+
+// What a render of the cloud from `view` shows, as text to keep with the
+// image. The camera is in viewer coordinates (y up), as views are.
+pub fn metadata(point_cloud: &PointCloud, view: &ViewState) -> Vec<(&'static str, String)> {
+    let (yaw, pitch, roll) = view.angles();
+    let points = point_cloud.visible_points();
+    vec![
+        (
+            "Software",
+            format!("altostratus {}", env!("CARGO_PKG_VERSION")),
+        ),
+        ("Source", point_cloud.sources.join(", ")),
+        ("Points", points.count().to_string()),
+        (
+            "Camera",
+            format!(
+                "yaw {:.2} pitch {:.2} roll {:.2} fov {:.2} (degrees), distance {}, looking at {},{},{}",
+                yaw.to_degrees(),
+                pitch.to_degrees(),
+                roll.to_degrees(),
+                view.fov.to_degrees(),
+                view.distance,
+                view.center.x,
+                view.center.y,
+                view.center.z
+            ),
+        ),
+    ]
+}
+
+// Settings for an `ImageRenderer`, checked all at once by `build`
+pub struct ImageRendererBuilder<'a> {
+    renderer: ImageRenderer<'a>,
+    size: (u16, u16),
+    point_size: Option<f32>,
+}
+
+impl<'a> ImageRendererBuilder<'a> {
+    // Image width and height in pixels
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn background(mut self, color: Color) -> Self {
+        self.renderer.background = color;
+        self
+    }
+
+    // Width of the square drawn for every point, in world units (one pixel
+    // if not given)
+    pub fn point_size(mut self, size: f32) -> Self {
+        self.point_size = Some(size);
+        self
+    }
+
+    // Whether the cloud's axes are drawn
+    pub fn axes(mut self, axes: bool) -> Self {
+        self.renderer.axes = axes;
+        self
+    }
+
+    // Draw at 2 or 4 times the image size across and down, then average
+    // each block of pixels down to one, for smooth edges (1 draws at size)
+    pub fn supersampling(mut self, factor: usize) -> Self {
+        self.renderer.supersampling = factor;
+        self
+    }
+
+    pub fn build(self) -> Result<ImageRenderer<'a>, String> {
+        let mut renderer = self.renderer;
+        let (width, height) = self.size;
+        if width == 0 || height == 0 {
+            return Err("image width and height must be at least 1".to_string());
+        }
+        renderer.viewport = Viewport::new(width, height);
+        if ![1, 2, 4].contains(&renderer.supersampling) {
+            return Err(format!(
+                "supersampling must be 1, 2 or 4, not {}",
+                renderer.supersampling
+            ));
+        }
+        if width.max(height) as usize * renderer.supersampling > u16::MAX as usize {
+            return Err(format!(
+                "{}x{} is too large to draw at {} times the size",
+                width, height, renderer.supersampling
+            ));
+        }
+        if let Some(size) = self.point_size {
+            if !(size.is_finite() && size > 0.0) {
+                return Err(format!(
+                    "point size must be a positive number, not {}",
+                    size
+                ));
+            }
+            renderer.point_style = PointStyle::Square { size };
+        }
+        Ok(renderer)
+    }
+}
+
+// Frames for web pages: RGBA bytes row by row from the top left, the layout
+// of an HTML canvas's `ImageData`. The buffer is reused between frames.
+pub struct CanvasRenderer<'a> {
+    renderer: Box<dyn Renderer + 'a>,
+    frame: Vec<u8>,
+}
+
+impl<'a> CanvasRenderer<'a> {
+    pub fn new(renderer: Box<dyn Renderer + 'a>) -> CanvasRenderer<'a> {
+        CanvasRenderer {
+            renderer,
+            frame: Vec::new(),
+        }
+    }
+
+    pub fn render(&mut self, view: &ViewState, viewport: Viewport) -> &[u8] {
+        let image = self.image(view, viewport);
+        self.frame.clear();
+        self.frame.extend(
+            image
+                .pixels()
+                .iter()
+                .flat_map(|&[r, g, b]| [r, g, b, u8::MAX]),
+        );
+        &self.frame
+    }
+
+    // The same frame as an image, e.g. to send as a PNG
+    pub fn image(&mut self, view: &ViewState, viewport: Viewport) -> RgbImage {
+        self.renderer.render(view, viewport)
+    }
+}
+
+// One frame per view along a camera path
+pub fn render_animation(
+    renderer: &mut dyn Renderer,
+    views: &[ViewState],
+    viewport: Viewport,
+) -> Vec<RgbImage> {
+    views
+        .iter()
+        .map(|view| renderer.render(view, viewport))
+        .collect()
+}
+
+// The cloud and its axes as the viewer would show them from `view`
+pub fn render_screen(point_cloud: &PointCloud, view: &ViewState, viewport: Viewport) -> Screen {
+    draw_screen(point_cloud, view, viewport, PointStyle::Dot, true)
+}
+
+// Like `render_screen`, with every point drawn in `point_style` and the axes
+// left out unless `axes` is set
+pub fn draw_screen(
+    point_cloud: &PointCloud,
+    view: &ViewState,
+    viewport: Viewport,
+    point_style: PointStyle,
+    axes: bool,
+) -> Screen {
+    let mut camera = blank_camera(view, viewport);
+    camera.point_style = point_style;
+    if axes {
+        camera.draw_axes(&point_cloud.axes);
+    }
+    camera.draw_points(point_cloud, 1);
+    camera.screen
+}
+
+// Like `render_image`, for points in compact storage
+pub fn render_compact(
+    points: &CompactCloud,
+    axes: &[AxisDecoration],
+    view: &ViewState,
+    viewport: Viewport,
+) -> RgbImage {
+    let mut camera = blank_camera(view, viewport);
+    camera.draw_axes(axes);
+    camera.plot_points(points.iter());
+    camera.screen.to_image()
+}
+
+fn blank_camera(view: &ViewState, viewport: Viewport) -> Camera {
+    let mut camera = Camera::new(view.center, VIEWPORT_DISTANCE, view.fov);
+    view.apply(&mut camera);
+    camera.screen.resize(viewport);
+    camera.screen.clear();
+    camera
+}
+
+// Like `render_screen`, with one image pixel per braille dot
+pub fn render_image(point_cloud: &PointCloud, view: &ViewState, viewport: Viewport) -> RgbImage {
+    render_screen(point_cloud, view, viewport).to_image()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::view::CameraPose;
+
+    #[test]
+    fn overlay_puts_points_where_the_photos_camera_sees_them() {
+        // Straight ahead lands on the principal point, off to the side as far
+        // as the focal length says, with the principal point off the middle
+        let mut cloud = PointCloud::new();
+        cloud.push(Point3D::new_with_color(
+            0.0,
+            0.0,
+            5.0,
+            Color::Rgb(255, 0, 0),
+        ));
+        cloud.push(Point3D::new_with_color(
+            1.0,
+            -1.0,
+            5.0,
+            Color::Rgb(0, 255, 0),
+        ));
+        let pose = CameraPose {
+            eye: [0.0; 3],
+            forward: [0.0, 0.0, 1.0],
+            up: [0.0, 1.0, 0.0],
+        };
+        let intrinsics = Intrinsics {
+            fx: 100.0,
+            fy: 100.0,
+            cx: 30.0,
+            cy: 20.0,
+        };
+        let photo = RgbImage::new(100, 60, Color::Rgb(50, 50, 50));
+
+        let mut renderer = ImageRenderer::builder(&cloud).axes(false).build().unwrap();
+        let view = ViewState::from_pose(&pose, 5.0);
+        let image = renderer.render_overlay(&view, &intrinsics, &photo).unwrap();
+
+        assert_eq!((image.width, image.height), (100, 60));
+        let drawn: Vec<_> = (0..60)
+            .flat_map(|y| (0..100).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let color = image.get(x, y).unwrap();
+                (color != Color::Rgb(50, 50, 50)).then_some((x, y, color))
+            })
+            .collect();
+        assert_eq!(
+            drawn,
+            [
+                (30, 20, Color::Rgb(255, 0, 0)),
+                (50, 40, Color::Rgb(0, 255, 0))
+            ]
+        );
+    }
+}
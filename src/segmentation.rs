@@ -0,0 +1,347 @@
+use std::array;
+use std::collections::VecDeque;
+
+use crate::graphics::{Color, Point3D};
+use crate::rng::Rng;
+use crate::spatial::SpatialGrid;
+
+// Colors cycled through when painting segments (black and default are skipped
+// so segments stay distinguishable from the background and unlabeled data)
+const SEGMENT_COLORS: [Color; 7] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+// Estimate a unit normal per point from the covariance of its neighborhood.
+// Points with fewer than three neighbors get an upward-facing normal.
+pub fn estimate_normals(points: &[Point3D], radius: f32) -> Vec<[f32; 3]> {
+    let grid = SpatialGrid::new(points, radius);
+
+    points
+        .iter()
+        .map(|point| {
+            let neighbors = grid.neighbors_within(point, radius);
+            if neighbors.len() < 3 {
+                return [0.0, 1.0, 0.0];
+            }
+
+            let count = neighbors.len() as f32;
+            let mut mean = [0.0f32; 3];
+            for &index in &neighbors {
+                let neighbor = &points[index];
+                mean[0] += neighbor.x / count;
+                mean[1] += neighbor.y / count;
+                mean[2] += neighbor.z / count;
+            }
+
+            let mut covariance = [[0.0f32; 3]; 3];
+            for &index in &neighbors {
+                let neighbor = &points[index];
+                let delta = [
+                    neighbor.x - mean[0],
+                    neighbor.y - mean[1],
+                    neighbor.z - mean[2],
+                ];
+                for row in 0..3 {
+                    for col in 0..3 {
+                        covariance[row][col] += delta[row] * delta[col];
+                    }
+                }
+            }
+
+            // The normal is the direction of least variance
+            smallest_eigenvector(covariance)
+        })
+        .collect()
+}
+
+// Jacobi eigenvalue iteration for a symmetric 3x3 matrix
+pub fn smallest_eigenvector(mut matrix: [[f32; 3]; 3]) -> [f32; 3] {
+    let mut vectors = [[1.0f32, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    for _ in 0..32 {
+        // Pick the largest off-diagonal element
+        let (p, q) = [(0, 1), (0, 2), (1, 2)]
+            .into_iter()
+            .max_by(|a, b| matrix[a.0][a.1].abs().total_cmp(&matrix[b.0][b.1].abs()))
+            .unwrap();
+
+        if matrix[p][q].abs() < 1e-9 {
+            break;
+        }
+
+        let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+        let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+        let c = 1.0 / (t * t + 1.0).sqrt();
+        let s = t * c;
+
+        for row in &mut matrix {
+            let (mkp, mkq) = (row[p], row[q]);
+            row[p] = c * mkp - s * mkq;
+            row[q] = s * mkp + c * mkq;
+        }
+        let (row_p, row_q) = (matrix[p], matrix[q]);
+        matrix[p] = array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+        matrix[q] = array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+        for row in &mut vectors {
+            let (vp, vq) = (row[p], row[q]);
+            row[p] = c * vp - s * vq;
+            row[q] = s * vp + c * vq;
+        }
+    }
+
+    let smallest = (0..3)
+        .min_by(|&a, &b| matrix[a][a].total_cmp(&matrix[b][b]))
+        .unwrap();
+    let normal = [
+        vectors[0][smallest],
+        vectors[1][smallest],
+        vectors[2][smallest],
+    ];
+
+    let length = (normal[0].powi(2) + normal[1].powi(2) + normal[2].powi(2)).sqrt();
+    if length > 0.0 {
+        [normal[0] / length, normal[1] / length, normal[2] / length]
+    } else {
+        [0.0, 1.0, 0.0]
+    }
+}
+
+// Label smooth connected regions: neighbors closer than `distance_threshold`
+// whose normals differ by less than `angle_threshold` (radians) share a segment.
+// Returns one segment label per point, numbered from zero.
+pub fn region_growing(
+    points: &[Point3D],
+    normals: &[[f32; 3]],
+    angle_threshold: f32,
+    distance_threshold: f32,
+) -> Vec<usize> {
+    let grid = SpatialGrid::new(points, distance_threshold);
+    let min_cos = angle_threshold.cos();
+
+    let mut labels: Vec<Option<usize>> = vec![None; points.len()];
+    let mut next_label = 0;
+    let mut queue = VecDeque::new();
+
+    for seed in 0..points.len() {
+        if labels[seed].is_some() {
+            continue;
+        }
+
+        labels[seed] = Some(next_label);
+        queue.push_back(seed);
+
+        while let Some(current) = queue.pop_front() {
+            for neighbor in grid.neighbors_within(&points[current], distance_threshold) {
+                if labels[neighbor].is_some() {
+                    continue;
+                }
+
+                // Normals are unoriented, so compare the absolute cosine
+                let cos = dot(normals[current], normals[neighbor]).abs();
+                if cos >= min_cos {
+                    labels[neighbor] = Some(next_label);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        next_label += 1;
+    }
+
+    labels.into_iter().map(|label| label.unwrap()).collect()
+}
+
+// Paint each point with a color picked from its segment label
+pub fn color_by_labels(points: &mut [Point3D], labels: &[usize]) {
+    for (point, &label) in points.iter_mut().zip(labels) {
+        point.color = label_color(label);
+    }
+}
+
+// Paint the inliers of each plane with its own color
+pub fn color_planes(points: &mut [Point3D], planes: &[PlaneModel]) {
+    for (plane_index, plane) in planes.iter().enumerate() {
+        for &index in &plane.inliers {
+            points[index].color = label_color(plane_index);
+        }
+    }
+}
+
+// Color for a segment, plane or cluster label
+pub fn label_color(label: usize) -> Color {
+    SEGMENT_COLORS[label % SEGMENT_COLORS.len()]
+}
+
+// Plane n . p + d = 0 fitted by `detect_planes`, with the indices it explains
+pub struct PlaneModel {
+    pub normal: [f32; 3],
+    pub offset: f32,
+    pub inliers: Vec<usize>,
+}
+
+impl PlaneModel {
+    // Corners of the rectangle spanned by the inliers within the plane
+    pub fn outline(&self, points: &[Point3D]) -> [Point3D; 4] {
+        let normal = self.normal;
+        let helper = if normal[0].abs() < 0.9 {
+            [1.0, 0.0, 0.0]
+        } else {
+            [0.0, 1.0, 0.0]
+        };
+        let u = normalize(cross(normal, helper));
+        let v = cross(normal, u);
+
+        let (mut min_u, mut max_u) = (f32::INFINITY, f32::NEG_INFINITY);
+        let (mut min_v, mut max_v) = (f32::INFINITY, f32::NEG_INFINITY);
+        for &index in &self.inliers {
+            let p = to_array(&points[index]);
+            min_u = min_u.min(dot(p, u));
+            max_u = max_u.max(dot(p, u));
+            min_v = min_v.min(dot(p, v));
+            max_v = max_v.max(dot(p, v));
+        }
+
+        // Point on the plane closest to the origin, then offset along u and v
+        let base = [
+            -self.offset * normal[0],
+            -self.offset * normal[1],
+            -self.offset * normal[2],
+        ];
+        let corner = |a: f32, b: f32| {
+            Point3D::new(
+                base[0] + a * u[0] + b * v[0],
+                base[1] + a * u[1] + b * v[1],
+                base[2] + a * u[2] + b * v[2],
+            )
+        };
+
+        [
+            corner(min_u, min_v),
+            corner(max_u, min_v),
+            corner(max_u, max_v),
+            corner(min_u, max_v),
+        ]
+    }
+}
+
+const RANSAC_ITERATIONS: usize = 200;
+
+// Find up to `count` planes with RANSAC. Each plane's inliers (points within
+// `threshold` of it) are removed before searching for the next one.
+pub fn detect_planes(
+    points: &[Point3D],
+    count: usize,
+    threshold: f32,
+    rng: &mut Rng,
+) -> Vec<PlaneModel> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut planes = Vec::new();
+
+    while planes.len() < count && remaining.len() >= 3 {
+        let mut best: Option<PlaneModel> = None;
+
+        for _ in 0..RANSAC_ITERATIONS {
+            let mut sample = || remaining[rng.below(remaining.len())];
+            let (a, b, c) = (
+                to_array(&points[sample()]),
+                to_array(&points[sample()]),
+                to_array(&points[sample()]),
+            );
+
+            let normal = cross(sub(b, a), sub(c, a));
+            if dot(normal, normal) < f32::EPSILON {
+                continue; // Degenerate (collinear or repeated) sample
+            }
+            let normal = normalize(normal);
+            let offset = -dot(normal, a);
+
+            let inliers: Vec<usize> = remaining
+                .iter()
+                .copied()
+                .filter(|&index| {
+                    (dot(normal, to_array(&points[index])) + offset).abs() <= threshold
+                })
+                .collect();
+
+            if best
+                .as_ref()
+                .is_none_or(|plane| inliers.len() > plane.inliers.len())
+            {
+                best = Some(PlaneModel {
+                    normal,
+                    offset,
+                    inliers,
+                });
+            }
+        }
+
+        let Some(plane) = best.filter(|plane| plane.inliers.len() >= 3) else {
+            break;
+        };
+
+        remaining.retain(|index| plane.inliers.binary_search(index).is_err());
+        planes.push(plane);
+    }
+
+    planes
+}
+
+fn to_array(point: &Point3D) -> [f32; 3] {
+    [point.x, point.y, point.z]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    [a[0] / length, a[1] / length, a[2] / length]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ransac_finds_a_plane_among_clutter() {
+        let mut rng = Rng::new(11);
+        // The plane z = 0.5x + 2 on a grid, then points scattered above it
+        let mut points: Vec<Point3D> = (0..100)
+            .map(|i| {
+                let (x, y) = ((i % 10) as f32, (i / 10) as f32);
+                Point3D::new(x, y, 0.5 * x + 2.0)
+            })
+            .collect();
+        points.extend((0..30).map(|_| {
+            let (x, y) = (rng.unit() * 10.0, rng.unit() * 10.0);
+            Point3D::new(x, y, 0.5 * x + 3.0 + rng.unit() * 5.0)
+        }));
+
+        let planes = detect_planes(&points, 1, 0.01, &mut rng);
+        assert_eq!(planes.len(), 1);
+        let plane = &planes[0];
+        assert_eq!(plane.inliers, (0..100).collect::<Vec<_>>());
+        // Normal along (-0.5, 0, 1), either way round
+        let expected = normalize([-0.5, 0.0, 1.0]);
+        assert!((dot(plane.normal, expected).abs() - 1.0).abs() < 1e-4);
+    }
+}
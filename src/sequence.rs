@@ -0,0 +1,87 @@
+use crate::graphics::PointCloud;
+
+// A point cloud that changes over time, one set of points per frame, as a
+// simulation or tracker writes them out. Playback loops back to the first
+// frame; stepping by hand pauses it.
+pub struct CloudSequence {
+    frames: Vec<PointCloud>, // At least one, none of them empty
+    current: usize,
+    shown: Option<usize>, // Frame whose points the viewed cloud holds
+    pub fps: f32,
+    pub playing: bool,
+    elapsed: f32, // Seconds spent on the current frame
+}
+
+impl CloudSequence {
+    // One frame per file the cloud was loaded from, in the order given, or
+    // None if none of them had points
+    pub fn from_sources(point_cloud: &PointCloud, fps: f32) -> Option<CloudSequence> {
+        let mut indices = vec![Vec::new(); point_cloud.sources.len()];
+        for (source, indices) in indices.iter_mut().enumerate() {
+            indices.extend(point_cloud.source_points(source));
+        }
+        let frames: Vec<PointCloud> = indices
+            .iter()
+            .filter(|indices| !indices.is_empty())
+            .map(|indices| point_cloud.select(indices))
+            .collect();
+        if frames.is_empty() {
+            return None;
+        }
+        Some(CloudSequence {
+            frames,
+            current: 0,
+            shown: None,
+            fps,
+            playing: true,
+            elapsed: 0.0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn toggle(&mut self) {
+        self.playing = !self.playing;
+        self.elapsed = 0.0;
+    }
+
+    // Pause and move `frames` frames on (or back, if negative), wrapping around
+    pub fn step(&mut self, frames: i32) {
+        self.playing = false;
+        self.elapsed = 0.0;
+        self.current = (self.current as i64 + frames as i64).rem_euclid(self.len() as i64) as usize;
+    }
+
+    // Move playback on by `dt` seconds
+    pub fn advance(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.elapsed += dt;
+        let period = 1.0 / self.fps;
+        while self.elapsed >= period {
+            self.elapsed -= period;
+            self.current = (self.current + 1) % self.len();
+        }
+    }
+
+    // Put the current frame's points into `point_cloud` if it doesn't hold
+    // them already. The axes and sources stay as they are, fitted to every
+    // frame, so they don't jump around during playback.
+    pub fn show(&mut self, point_cloud: &mut PointCloud) {
+        if self.shown != Some(self.current) {
+            point_cloud.replace_points(&self.frames[self.current]);
+            self.shown = Some(self.current);
+        }
+    }
+}
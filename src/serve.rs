@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::*;
+
+use crate::graphics::{BRAILLE_CELL, Camera, PointCloud, Viewport};
+use crate::html;
+use crate::render::{CanvasRenderer, ImageRenderer, ProgressiveRenderer, Renderer};
+use crate::view::ViewState;
+
+const DEFAULT_COLUMNS: u16 = 100;
+const DEFAULT_ROWS: u16 = 40;
+const MAX_DIMENSION: u16 = 1000; // Terminal cells per side a request may ask for
+const CLIENT_TIMEOUT: time::Duration = time::Duration::from_secs(5); // For a stalled client
+
+// Answer HTTP GET requests with rendered frames of the cloud. The camera is
+// set from the query string, e.g. /?yaw=45&pitch=20&cols=120&rows=40&format=html
+//   yaw, pitch  - orbit angles in degrees (default 90 and 0, as in the viewer)
+//   distance    - distance from the center (default 1.5 x the bounding diagonal)
+//   roll        - degrees the picture is turned clockwise (default 0)
+//   cols, rows  - frame size in terminal cells
+//   format      - "ansi" (default), "html", "png", or "rgba" for raw RGBA
+//                 bytes to draw on a canvas; images are cols x 2 by rows x 4
+//                 pixels
+// With `progressive`, rgba and png frames start as a sample of the points and
+// fill in as the same view is asked for again. Requests are answered one at a
+// time, so a client that stops sending or reading is given up on after
+// `CLIENT_TIMEOUT`.
+pub fn run_server(
+    point_cloud: &PointCloud,
+    (address, port): (&str, u16),
+    progressive: bool,
+    viewport_fov: f32,
+    viewport_distance: f32,
+) -> io::Result<()> {
+    let listener = TcpListener::bind((address, port))?;
+    println!("Serving frames on http://{}:{}/", address, port);
+
+    let mut camera = Camera::new(point_cloud.get_center(), viewport_distance, viewport_fov);
+    let renderer: Box<dyn Renderer> = match progressive {
+        true => Box::new(ProgressiveRenderer::new(ImageRenderer::new(point_cloud))),
+        false => Box::new(ImageRenderer::new(point_cloud)),
+    };
+    let mut canvas = CanvasRenderer::new(renderer);
+
+    for stream in listener.incoming() {
+        // A misbehaving client shouldn't take the server down
+        let result =
+            stream.and_then(|stream| handle_request(stream, point_cloud, &mut camera, &mut canvas));
+        if let Err(e) = result {
+            eprintln!("Request failed: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_request(
+    mut stream: TcpStream,
+    point_cloud: &PointCloud,
+    camera: &mut Camera,
+    canvas: &mut CanvasRenderer,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain the headers so the client isn't reset while still sending them
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    // "GET /path?query HTTP/1.1"
+    let mut parts = request_line.split_whitespace();
+    let (Some("GET"), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"GET only\n",
+        );
+    };
+
+    match render_frame(point_cloud, camera, canvas, &parse_query(target)) {
+        Ok((content_type, body)) => respond(&mut stream, "200 OK", content_type, &body),
+        Err(message) => respond(
+            &mut stream,
+            "400 Bad Request",
+            "text/plain",
+            format!("{}\n", message).as_bytes(),
+        ),
+    }
+}
+
+fn parse_query(target: &str) -> HashMap<&str, &str> {
+    let query = target.split_once('?').map_or("", |(_, query)| query);
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect()
+}
+
+fn render_frame(
+    point_cloud: &PointCloud,
+    camera: &mut Camera,
+    canvas: &mut CanvasRenderer,
+    query: &HashMap<&str, &str>,
+) -> Result<(&'static str, Vec<u8>), String> {
+    let number = |name: &str, default: f32| match query.get(name) {
+        Some(value) => value
+            .parse::<f32>()
+            .map_err(|_| format!("Invalid {}: {}", name, value)),
+        None => Ok(default),
+    };
+    let cells = |name: &str, default: u16| match query.get(name) {
+        Some(value) => match value.parse::<u16>() {
+            Ok(cells) if (1..=MAX_DIMENSION).contains(&cells) => Ok(cells),
+            _ => Err(format!("{} must be between 1 and {}", name, MAX_DIMENSION)),
+        },
+        None => Ok(default),
+    };
+
+    let view = ViewState {
+        orientation: ViewState::orientation_at(
+            number("yaw", 90.0)?.to_radians(),
+            number("pitch", 0.0)?.to_radians(),
+            number("roll", 0.0)?.to_radians(),
+        ),
+        distance: number("distance", point_cloud.get_diagonal().max(1.0) * 1.5)?,
+        center: point_cloud.get_center(),
+        fov: camera.viewport_fov,
+    };
+    // The status line row is left out of the frame
+    let viewport = Viewport::from_terminal(
+        cells("cols", DEFAULT_COLUMNS)?,
+        cells("rows", DEFAULT_ROWS)? + 1,
+        BRAILLE_CELL,
+    );
+
+    let format = query.get("format").copied().unwrap_or("ansi");
+    match format {
+        "rgba" => {
+            let frame = canvas.render(&view, viewport);
+            return Ok(("application/octet-stream", frame.to_vec()));
+        }
+        "png" => return Ok(("image/png", canvas.image(&view, viewport).to_png())),
+        _ => {}
+    }
+
+    view.apply(camera);
+    camera.screen.resize(viewport);
+    camera.screen.clear();
+    camera.draw_cloud(point_cloud);
+    let frame = camera.screen.frame();
+
+    match format {
+        "ansi" => Ok(("text/plain; charset=utf-8", frame.into_bytes())),
+        "html" => Ok((
+            "text/html; charset=utf-8",
+            html::frame_to_html(&frame, camera.screen.background).into_bytes(),
+        )),
+        other => Err(format!(
+            "Unknown format: {} (expected ansi, html, png or rgba)",
+            other
+        )),
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len(),
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
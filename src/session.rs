@@ -0,0 +1,144 @@
+use std::io::Write;
+use std::*;
+
+use crate::graphics::{CoordinateSystem, Point3D, PointCloud};
+use crate::view::VIEWPORT_FOV;
+use crate::view::ViewState;
+
+// Sessions are point files with extra lines holding the camera and datasets:
+//   view yaw pitch distance center_x center_y center_z roll fov
+//   source <path>        one per dataset, in order
+//   dataset <index>|none the dataset of the points that follow
+//   hidden on|off        whether the points that follow are hidden
+// Points are always stored z-up, whatever system the data was loaded with.
+// Sessions written before datasets were saved have only visible points
+// without a dataset, and those written before roll and the field of view
+// were saved have a view without them.
+pub fn save_session(
+    path: &str,
+    point_cloud: &PointCloud,
+    view: &ViewState,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+
+    writeln!(file, "# altostratus session")?;
+    let (yaw, pitch, roll) = view.angles();
+    writeln!(
+        file,
+        "view {} {} {} {} {} {} {} {}",
+        yaw, pitch, view.distance, view.center.x, view.center.y, view.center.z, roll, view.fov
+    )?;
+
+    for source in &point_cloud.sources {
+        writeln!(file, "source {}", source)?;
+    }
+
+    // Datasets and hidden flags only change between runs of points
+    let (mut source, mut hidden) = (None, false);
+    for (index, point) in point_cloud.points().iter().enumerate() {
+        if point_cloud.source(index) != source {
+            source = point_cloud.source(index);
+            match source {
+                Some(index) => writeln!(file, "dataset {}", index)?,
+                None => writeln!(file, "dataset none")?,
+            }
+        }
+        if point_cloud.is_hidden(index) != hidden {
+            hidden = point_cloud.is_hidden(index);
+            writeln!(file, "hidden {}", if hidden { "on" } else { "off" })?;
+        }
+
+        // Viewer to z-up: viewer_y is file_z
+        writeln!(
+            file,
+            "pc {} {} {} {}",
+            point.x, point.z, point.y, point.color
+        )?;
+    }
+
+    file.flush()?;
+    Ok(())
+}
+
+pub fn load_session(path: &str) -> Result<(PointCloud, ViewState), Box<dyn error::Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut points = Vec::new();
+    let mut point_states = Vec::new(); // Dataset and hidden flag of each point
+    let mut sources = Vec::new();
+    let mut view = None;
+    let (mut source, mut hidden) = (None, false);
+
+    for (line_num, line) in content.lines().enumerate() {
+        let invalid = |what: &str| format!("Invalid {} on line {}: {}", what, line_num + 1, line);
+        if let Some(path) = line.trim().strip_prefix("source ") {
+            sources.push(path.to_string());
+            continue;
+        }
+        if let Some(index) = line.trim().strip_prefix("dataset ") {
+            source = match index.trim() {
+                "none" => None,
+                index => match index.parse() {
+                    Ok(index) if index < sources.len() => Some(index),
+                    _ => return Err(invalid("dataset").into()),
+                },
+            };
+            continue;
+        }
+        if let Some(flag) = line.trim().strip_prefix("hidden ") {
+            hidden = match flag.trim() {
+                "on" => true,
+                "off" => false,
+                _ => return Err(invalid("hidden flag").into()),
+            };
+            continue;
+        }
+        if let Some(values) = line.trim().strip_prefix("view ") {
+            let values: Vec<f32> = values
+                .split_whitespace()
+                .map(|value| value.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| format!("Invalid view on line {}: {}", line_num + 1, line))?;
+
+            let expected = || {
+                format!(
+                    "Invalid view on line {}: {}. Expected 'view yaw pitch distance x y z roll fov'",
+                    line_num + 1,
+                    line
+                )
+            };
+            let [yaw, pitch, distance, x, y, z, ref rest @ ..] = values[..] else {
+                return Err(expected().into());
+            };
+            // Older sessions leave roll and the field of view off
+            let (roll, fov) = match rest {
+                [] => (0.0, VIEWPORT_FOV),
+                &[roll] => (roll, VIEWPORT_FOV),
+                &[roll, fov] if fov > 0.0 && fov < f32::consts::PI => (roll, fov),
+                _ => return Err(expected().into()),
+            };
+
+            view = Some(ViewState {
+                orientation: ViewState::orientation_at(yaw, pitch, roll),
+                distance,
+                center: Point3D::new(x, y, z),
+                fov,
+            });
+            continue;
+        }
+
+        for point in PointCloud::parse_line(line, line_num)? {
+            points.push(point);
+            point_states.push((source, hidden));
+        }
+    }
+
+    CoordinateSystem::ZUp.convert(&mut points);
+    let view = view.ok_or("No view found in session file")?;
+    let mut point_cloud: PointCloud = points.into_iter().collect();
+    point_cloud.sources = sources;
+    for (index, (source, hidden)) in point_states.into_iter().enumerate() {
+        point_cloud.set_source(index, source);
+        point_cloud.set_hidden(index, hidden);
+    }
+    Ok((point_cloud, view))
+}
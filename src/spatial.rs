@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::graphics::Point3D;
+
+// Uniform grid over a point set for fixed-radius neighbor queries.
+// Cells are as wide as the query radius, so a query only has to visit
+// the 27 cells around the query point.
+pub struct SpatialGrid<'a> {
+    points: &'a [Point3D],
+    cell_size: f32,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+}
+
+impl<'a> SpatialGrid<'a> {
+    pub fn new(points: &'a [Point3D], cell_size: f32) -> SpatialGrid<'a> {
+        let cell_size = cell_size.max(f32::EPSILON);
+        let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+
+        for (index, point) in points.iter().enumerate() {
+            cells
+                .entry(Self::cell_of(point, cell_size))
+                .or_default()
+                .push(index);
+        }
+
+        SpatialGrid {
+            points,
+            cell_size,
+            cells,
+        }
+    }
+
+    fn cell_of(point: &Point3D, cell_size: f32) -> (i32, i32, i32) {
+        (
+            (point.x / cell_size).floor() as i32,
+            (point.y / cell_size).floor() as i32,
+            (point.z / cell_size).floor() as i32,
+        )
+    }
+
+    // Indices of all points within `radius` of `center` (radius <= cell size)
+    pub fn neighbors_within(&self, center: &Point3D, radius: f32) -> Vec<usize> {
+        let (cx, cy, cz) = Self::cell_of(center, self.cell_size);
+        let radius_squared = radius * radius;
+        let mut neighbors = Vec::new();
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(cell) = self.cells.get(&(cx + dx, cy + dy, cz + dz)) else {
+                        continue;
+                    };
+
+                    for &index in cell {
+                        let point = &self.points[index];
+                        let distance_squared = (point.x - center.x).powi(2)
+                            + (point.y - center.y).powi(2)
+                            + (point.z - center.z).powi(2);
+                        if distance_squared <= radius_squared {
+                            neighbors.push(index);
+                        }
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::BufRead;
+use std::net::{TcpListener, UdpSocket};
+use std::sync::mpsc;
+use std::*;
+
+use crate::graphics::{CoordinateSystem, Point3D, PointCloud};
+
+const MAX_DATAGRAM_SIZE: usize = 65_536;
+
+// Points arriving incrementally from a line-oriented source (one point file
+// line per record). Reading happens on background threads so the viewer can
+// keep rendering; parsed points are collected with `drain_into`.
+pub struct StreamingCloud {
+    receiver: mpsc::Receiver<Vec<Point3D>>,
+}
+
+impl StreamingCloud {
+    pub fn from_reader<R: BufRead + Send + 'static>(reader: R) -> StreamingCloud {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || forward_lines(reader, &sender));
+        StreamingCloud { receiver }
+    }
+
+    pub fn stdin() -> StreamingCloud {
+        Self::from_reader(io::BufReader::new(io::stdin()))
+    }
+
+    // Accept any number of TCP clients, each sending newline-delimited records
+    pub fn listen_tcp(address: &str) -> io::Result<StreamingCloud> {
+        let listener = TcpListener::bind(address)?;
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for connection in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || forward_lines(io::BufReader::new(connection), &sender));
+            }
+        });
+
+        Ok(StreamingCloud { receiver })
+    }
+
+    // Receive UDP datagrams, each holding one or more newline-delimited records
+    pub fn listen_udp(address: &str) -> io::Result<StreamingCloud> {
+        let socket = UdpSocket::bind(address)?;
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut buffer = vec![0u8; MAX_DATAGRAM_SIZE];
+            while let Ok((size, _)) = socket.recv_from(&mut buffer) {
+                let text = String::from_utf8_lossy(&buffer[..size]);
+                let points: Vec<Point3D> = text
+                    .lines()
+                    .filter_map(|line| PointCloud::parse_line(line, 0).ok())
+                    .flatten()
+                    .collect();
+
+                if !points.is_empty() && sender.send(points).is_err() {
+                    break; // Viewer has gone away
+                }
+            }
+        });
+
+        Ok(StreamingCloud { receiver })
+    }
+
+    // Move every point received so far into `point_cloud` without blocking,
+    // converting them from `coordinate_system`. Returns the number of points
+    // added; the cloud's axes are left for the caller to refit.
+    pub fn drain_into(
+        &self,
+        point_cloud: &mut PointCloud,
+        coordinate_system: CoordinateSystem,
+    ) -> usize {
+        let mut added = 0;
+        for mut batch in self.receiver.try_iter() {
+            coordinate_system.convert(&mut batch);
+            added += batch.len();
+            for point in batch {
+                point_cloud.push(point);
+            }
+        }
+        added
+    }
+}
+
+fn forward_lines<R: BufRead>(reader: R, sender: &mpsc::Sender<Vec<Point3D>>) {
+    for (line_num, line) in reader.lines().enumerate() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        // Malformed records are skipped so one bad line doesn't end the stream
+        if let Ok(points) = PointCloud::parse_line(&line, line_num)
+            && !points.is_empty()
+            && sender.send(points).is_err()
+        {
+            break; // Viewer has gone away
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::*;
+
+use crate::graphics::{Color, CoordinateSystem, PointCloud, Viewport};
+use crate::image::{self, RgbImage};
+use crate::layout::Preset;
+use crate::render::{self, ImageRenderer};
+use crate::view::ViewState;
+
+const SUMMARY_WIDTH: u16 = 640; // Size of the rendered area, without the footer
+const SUMMARY_HEIGHT: u16 = 480;
+const TEXT_SCALE: usize = 2;
+const FOOTER_PADDING: usize = 8;
+const RAMP_WIDTH: usize = 100; // Width of the height legend's color bar
+const FOOTER_BACKGROUND: Color = Color::Rgb(32, 32, 32);
+const FOOTER_TEXT: Color = Color::Rgb(200, 200, 200);
+
+// Thumbnail of a cloud with no settings to choose: an isometric view framing
+// every point, colored by height, with the axes and a footer giving the point
+// count, the size of the bounding box (as z-up data) and the height legend.
+pub fn render_summary(point_cloud: &PointCloud) -> RgbImage {
+    let (min, max) = point_cloud.get_bounds();
+    let height_range = (max.y - min.y).max(f32::EPSILON);
+    let colored: PointCloud = point_cloud
+        .points()
+        .iter()
+        .map(|point| {
+            let mut point = *point;
+            point.color = Color::ramp((point.y - min.y) / height_range);
+            point
+        })
+        .collect();
+
+    let viewport = Viewport::new(SUMMARY_WIDTH, SUMMARY_HEIGHT);
+    let (yaw, pitch) = Preset::Isometric.angles();
+    let view = ViewState::framing(point_cloud, viewport, yaw, pitch);
+    let frame = render::render_image(&colored, &view, viewport);
+
+    let footer_height = image::text_height(TEXT_SCALE) + 2 * FOOTER_PADDING;
+    let (width, height) = (SUMMARY_WIDTH as usize, SUMMARY_HEIGHT as usize);
+    let mut image = RgbImage::new(width, height + footer_height, Color::Black);
+    image.paste(&frame);
+
+    // Footer: stats on the left, height legend on the right
+    image.fill_rect(0, height, width, footer_height, FOOTER_BACKGROUND);
+    let text_y = height + FOOTER_PADDING;
+    let (size_x, size_y, size_z) =
+        CoordinateSystem::ZUp.to_data(max.x - min.x, max.y - min.y, max.z - min.z);
+    let stats = format!(
+        "{} points  size {} x {} x {}",
+        point_cloud.visible_count(),
+        format_number(size_x),
+        format_number(size_y),
+        format_number(size_z)
+    );
+    image.draw_text(FOOTER_PADDING, text_y, &stats, TEXT_SCALE, FOOTER_TEXT);
+
+    let low = format_number(min.y);
+    let high = format_number(max.y);
+    let advance = image::text_advance(TEXT_SCALE);
+    let legend_width = (low.len() + high.len() + 2) * advance + RAMP_WIDTH;
+    let mut x = width.saturating_sub(legend_width + FOOTER_PADDING);
+    image.draw_text(x, text_y, &low, TEXT_SCALE, FOOTER_TEXT);
+    x += (low.len() + 1) * advance;
+    for offset in 0..RAMP_WIDTH {
+        let color = Color::ramp(offset as f32 / (RAMP_WIDTH - 1) as f32);
+        image.fill_rect(x + offset, text_y, 1, image::text_height(TEXT_SCALE), color);
+    }
+    x += RAMP_WIDTH + advance;
+    image.draw_text(x, text_y, &high, TEXT_SCALE, FOOTER_TEXT);
+
+    image
+}
+
+// Views of the cloud from `count` directions spread evenly around it, each
+// framing every point, so catalogs can show all sides without choosing
+// cameras. The directions form a Fibonacci lattice on the sphere: even steps
+// in height from top to bottom, turning by the golden angle between them.
+pub fn render_orbit_thumbnails(point_cloud: &PointCloud, count: usize) -> Vec<RgbImage> {
+    let viewport = Viewport::new(SUMMARY_WIDTH, SUMMARY_HEIGHT);
+    let golden_angle = f32::consts::PI * (3.0 - 5f32.sqrt());
+    let views: Vec<ViewState> = (0..count)
+        .map(|index| {
+            let height = 1.0 - (index as f32 + 0.5) * 2.0 / count as f32;
+            let yaw = index as f32 * golden_angle;
+            ViewState::framing(point_cloud, viewport, yaw, height.asin())
+        })
+        .collect();
+    render::render_animation(&mut ImageRenderer::new(point_cloud), &views, viewport)
+}
+
+// Short label for a coordinate: two decimals for small values, fewer for large
+fn format_number(value: f32) -> String {
+    match value.abs() {
+        magnitude if magnitude >= 1000.0 => format!("{:.0}", value),
+        magnitude if magnitude >= 100.0 => format!("{:.1}", value),
+        _ => format!("{:.2}", value),
+    }
+}
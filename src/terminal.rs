@@ -0,0 +1,70 @@
+use std::*;
+
+use crossterm::{cursor, event, execute};
+
+// Whether the terminal currently needs restoring
+static ACTIVE: sync::atomic::AtomicBool = sync::atomic::AtomicBool::new(false);
+static PANIC_HOOK: sync::Once = sync::Once::new();
+
+// What to change about the terminal on top of raw mode
+#[derive(Copy, Clone)]
+pub struct TerminalOptions {
+    pub mouse_capture: bool,
+    pub hide_cursor: bool,
+}
+
+impl TerminalOptions {
+    pub fn new() -> TerminalOptions {
+        TerminalOptions {
+            mouse_capture: true,
+            hide_cursor: true,
+        }
+    }
+}
+
+// Raw mode (plus mouse capture and a hidden cursor, if asked for) for as long
+// as the guard lives. The terminal is put back when the guard is dropped and
+// also before a panic message is printed, so a crash leaves a usable shell
+// and a readable backtrace.
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn enter(options: TerminalOptions) -> io::Result<TerminalGuard> {
+        PANIC_HOOK.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                previous(info);
+            }));
+        });
+
+        crossterm::terminal::enable_raw_mode()?;
+        ACTIVE.store(true, sync::atomic::Ordering::SeqCst);
+        if options.hide_cursor {
+            execute!(io::stdout(), cursor::Hide)?;
+        }
+        if options.mouse_capture {
+            execute!(io::stdout(), event::EnableMouseCapture)?;
+        }
+        Ok(TerminalGuard { _private: () })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// Undo everything a guard may have changed. Does nothing unless a guard is
+// active, so it's safe to call on any exit path; needed before
+// `process::exit`, which skips destructors.
+pub fn restore() {
+    if ACTIVE.swap(false, sync::atomic::Ordering::SeqCst) {
+        // Best effort: there's nowhere left to report failures
+        let _ = execute!(io::stdout(), cursor::Show, event::DisableMouseCapture);
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
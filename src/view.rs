@@ -0,0 +1,460 @@
+use std::*;
+
+use crate::graphics::{Camera, Point3D, PointCloud, Viewport};
+use crate::input::InputEvent;
+use crate::quaternion::Quaternion;
+use crate::segmentation::{cross, dot, normalize};
+
+pub const VIEWPORT_FOV: f32 = 1.7; // Default field of view, radians
+pub const VIEWPORT_DISTANCE: f32 = 0.1; // Near plane
+const MIN_DISTANCE: f32 = 0.1; // Closest the camera zooms to the center
+const FRAMING_MARGIN: f32 = 1.1; // Camera distance over the closest that fits the cloud
+const POLE_EPSILON: f32 = 1e-6; // Horizontal view direction below which the camera looks straight up or down
+const GLIDE_DELAY: f32 = 0.06; // Seconds without input before the view glides on, longer than gaps in a drag
+const MIN_INPUT_GAP: f32 = 1.0 / 240.0; // Seconds; closer inputs are taken as this far apart
+const MIN_GLIDE_SPEED: f32 = 1e-3; // Radians or diagonals per second below which gliding stops
+
+// Interactive camera parameters: which way the camera faces, its zoom
+// distance and the point it orbits around (moved by panning), plus how wide
+// it sees. Orbiting and tumbling turn the orientation itself, so turns add up
+// exactly, even over the poles; yaw, pitch and roll are worked out from it
+// for display and files.
+#[derive(Copy, Clone)]
+pub struct ViewState {
+    pub orientation: Quaternion, // Turn from viewer to camera space
+    pub distance: f32,
+    pub center: Point3D,
+    pub fov: f32, // Radians across the picture, for cameras made for the view
+}
+
+// Where a camera is and which way it faces, the parts of a pose matrix from
+// SLAM or photogrammetry tools that place a view
+#[derive(Copy, Clone)]
+pub struct CameraPose {
+    pub eye: [f32; 3],
+    pub forward: [f32; 3], // View direction
+    pub up: [f32; 3],      // Up in the camera's picture
+}
+
+impl CameraPose {
+    // From a camera-to-world matrix, row by row, in the OpenGL convention:
+    // the camera looks down its -z axis, with y up
+    pub fn from_matrix(matrix: [[f32; 4]; 4]) -> CameraPose {
+        let column = |j: usize| [matrix[0][j], matrix[1][j], matrix[2][j]];
+        CameraPose {
+            eye: column(3),
+            forward: column(2).map(|v| -v),
+            up: column(1),
+        }
+    }
+
+    // From extrinsics in the OpenCV and COLMAP convention, a world-to-camera
+    // rotation and translation (x_camera = R x_world + t): the camera looks
+    // down its +z axis, with y down
+    pub fn from_extrinsics(rotation: [[f32; 3]; 3], translation: [f32; 3]) -> CameraPose {
+        // The rows of R are the camera's axes, and it sits where R x + t = 0
+        let eye = array::from_fn(|j| -(0..3).map(|i| rotation[i][j] * translation[i]).sum::<f32>());
+        CameraPose {
+            eye,
+            forward: rotation[2],
+            up: rotation[1].map(|v| -v),
+        }
+    }
+
+    // Camera-to-world matrix, row by row, in the OpenGL convention
+    pub fn to_matrix(self) -> [[f32; 4]; 4] {
+        let back = normalize(self.forward.map(|v| -v));
+        let right = normalize(cross(self.up, back));
+        let columns = [right, cross(back, right), back, self.eye];
+        array::from_fn(|i| match i {
+            3 => [0.0, 0.0, 0.0, 1.0],
+            _ => columns.map(|column| column[i]),
+        })
+    }
+
+    // The same pose with its position and directions moved by `map`, e.g.
+    // between data and viewer coordinates. Only right for maps that are also
+    // linear, like swapping or flipping axes.
+    pub fn map(&self, map: impl Fn([f32; 3]) -> [f32; 3]) -> CameraPose {
+        CameraPose {
+            eye: map(self.eye),
+            forward: map(self.forward),
+            up: map(self.up),
+        }
+    }
+}
+
+// The pinhole camera a photo was taken with, as OpenCV and COLMAP give it:
+// focal lengths and the principal point, in pixels of the photo
+#[derive(Copy, Clone)]
+pub struct Intrinsics {
+    pub fx: f32,
+    pub fy: f32,
+    pub cx: f32, // From the center of the top left pixel
+    pub cy: f32,
+}
+
+impl ViewState {
+    // The orbit that puts the camera at `eye`, looking at `target`
+    pub fn looking_at(eye: &Point3D, target: &Point3D) -> ViewState {
+        let (x, y, z) = (eye.x - target.x, eye.y - target.y, eye.z - target.z);
+        let distance = (x * x + y * y + z * z).sqrt().max(MIN_DISTANCE);
+        let pitch = (y / distance).clamp(-1.0, 1.0).asin();
+        ViewState {
+            orientation: ViewState::orientation_at(x.atan2(-z), pitch, 0.0),
+            distance,
+            center: *target,
+            fov: VIEWPORT_FOV,
+        }
+    }
+
+    // The orientation of a camera orbited by `yaw` about the vertical and
+    // `pitch` up towards the top, then turned by `roll` about its view
+    // direction, positive turning the picture clockwise; all in radians
+    pub fn orientation_at(yaw: f32, pitch: f32, roll: f32) -> Quaternion {
+        Quaternion::about([0., 0., 1.], -roll)
+            * Quaternion::about([1., 0., 0.], -pitch)
+            * Quaternion::about([0., 1., 0.], yaw)
+    }
+
+    // Yaw, pitch and roll of the orientation, with the pitch between straight
+    // down and straight up: past a pole the yaw and roll turn around instead
+    pub fn angles(&self) -> (f32, f32, f32) {
+        // The rotation is roll * pitch * yaw, whose last row (the view
+        // direction) leaves out the roll
+        let rows = self.orientation.to_matrix();
+        let [forward_x, forward_y, forward_z] = rows[2];
+        let pitch = -forward_y.clamp(-1.0, 1.0).asin();
+        if forward_x.hypot(forward_z) > POLE_EPSILON {
+            let roll = rows[0][1].atan2(rows[1][1]);
+            ((-forward_x).atan2(forward_z), pitch, roll)
+        } else {
+            // Straight up or down, where any yaw will do with the right roll
+            (0.0, pitch, -rows[1][0].atan2(rows[0][0]))
+        }
+    }
+
+    // The same view, rolled so that the direction `up` points straight up the
+    // screen (left as it is if `up` is along the view direction)
+    pub fn with_up(&self, up: &Point3D) -> ViewState {
+        let [x, y, _] = self.orientation.rotate([up.x, up.y, up.z]);
+        if x.hypot(y) <= f32::EPSILON {
+            return *self;
+        }
+        ViewState {
+            orientation: Quaternion::about([0., 0., 1.], x.atan2(y)) * self.orientation,
+            ..*self
+        }
+    }
+
+    // Turn the picture clockwise by `roll` radians
+    pub fn roll_by(&mut self, roll: f32) {
+        self.orientation = Quaternion::about([0., 0., 1.], -roll) * self.orientation;
+    }
+
+    // Looking from the pose's eye along its view direction, with its up
+    // pointing up the screen, around a center `distance` ahead
+    pub fn from_pose(pose: &CameraPose, distance: f32) -> ViewState {
+        let ([x, y, z], [dx, dy, dz]) = (pose.eye, normalize(pose.forward));
+        let distance = distance.max(MIN_DISTANCE);
+        let target = Point3D::new(x + dx * distance, y + dy * distance, z + dz * distance);
+        let [up_x, up_y, up_z] = pose.up;
+        ViewState::looking_at(&Point3D::new(x, y, z), &target)
+            .with_up(&Point3D::new(up_x, up_y, up_z))
+    }
+
+    // The camera's pose, in viewer coordinates
+    pub fn pose(&self) -> CameraPose {
+        let eye = self.camera().coordinates;
+        let [_, up, forward] = self.orientation.to_matrix();
+        CameraPose {
+            eye: [eye.x, eye.y, eye.z],
+            forward,
+            up,
+        }
+    }
+
+    // Turn the scene about the center by `rotation`, given in camera space
+    // (x right, y up, z into the screen), by turning the camera the other way
+    pub fn tumble(&mut self, rotation: Quaternion) {
+        self.orientation = rotation * self.orientation;
+    }
+
+    // Orbit by `yaw` about the vertical, then by `pitch` about the screen's
+    // horizontal, however the view is turned, so orbiting carries on over
+    // the poles
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        self.orientation = Quaternion::about([1., 0., 0.], -pitch)
+            * self.orientation
+            * Quaternion::about([0., 1., 0.], yaw);
+    }
+
+    // Looking at the cloud's center from the given angles, backed off until
+    // its bounding sphere fits the narrower field of view of `viewport`
+    pub fn framing(
+        point_cloud: &PointCloud,
+        viewport: Viewport,
+        yaw: f32,
+        pitch: f32,
+    ) -> ViewState {
+        let half_width = (VIEWPORT_FOV / 2.0).tan();
+        let half_angle = (half_width * viewport.aspect_ratio().min(1.0)).atan();
+        let radius = point_cloud.get_diagonal().max(1.0) / 2.0;
+        ViewState {
+            orientation: ViewState::orientation_at(yaw, pitch, 0.0),
+            distance: radius / half_angle.sin() * FRAMING_MARGIN,
+            center: point_cloud.get_center(),
+            fov: VIEWPORT_FOV,
+        }
+    }
+
+    // Looking at the center of the cloud's bounding box from the given angles
+    // (yaw, pitch), as close as the camera gets with the whole box inside
+    // `viewport`, across and down, once it's grown by `reach` for points drawn
+    // larger than a pixel. `margin` is how many times larger than the box the
+    // image is. Boxes under 1 across, down to single points, are grown to that.
+    pub fn frame_cloud(
+        point_cloud: &PointCloud,
+        viewport: Viewport,
+        (yaw, pitch): (f32, f32),
+        reach: f32,
+        margin: f32,
+    ) -> ViewState {
+        let (min, max) = point_cloud.get_bounds();
+        let center = point_cloud.get_center();
+        let grow = reach + (1.0 - point_cloud.get_diagonal()).max(0.0) / 2.0;
+        let mut view = ViewState {
+            orientation: ViewState::orientation_at(yaw, pitch, 0.0),
+            distance: 0.0,
+            center,
+            fov: VIEWPORT_FOV,
+        };
+
+        // Corners relative to the center, turned into camera space, where the
+        // camera sits `distance` back along z
+        let projection = view.camera().projection();
+        let origin = projection.to_camera(&center);
+        let half_width = (view.fov / 2.0).tan() / margin;
+        let half_height = half_width * viewport.aspect_ratio();
+        let mut distance = MIN_DISTANCE;
+        for corner in 0..8 {
+            let pick = |bit: usize, min: f32, max: f32| match corner >> bit & 1 {
+                0 => min - grow,
+                _ => max + grow,
+            };
+            let corner = Point3D::new(
+                pick(0, min.x, max.x),
+                pick(1, min.y, max.y),
+                pick(2, min.z, max.z),
+            );
+            let point = projection.to_camera(&corner);
+            let (x, y, z) = (point.x - origin.x, point.y - origin.y, point.z - origin.z);
+            distance = distance
+                .max(x.abs() / half_width - z)
+                .max(y.abs() / half_height - z)
+                .max(VIEWPORT_DISTANCE - z); // In front of the near plane
+        }
+        view.distance = distance;
+        view
+    }
+
+    // The same view at field of view `fov`, with the camera moved along its
+    // view direction so the plane through the center shows the same width:
+    // what's at the center keeps its size while everything around it swells
+    // or shrinks, as in a dolly zoom
+    pub fn dolly_zoom(&self, fov: f32) -> ViewState {
+        let half_width = self.distance * (self.fov / 2.0).tan();
+        ViewState {
+            distance: (half_width / (fov / 2.0).tan()).max(MIN_DISTANCE),
+            fov,
+            ..*self
+        }
+    }
+
+    // A camera placed for this view
+    fn camera(&self) -> Camera {
+        let mut camera = Camera::new(self.center, VIEWPORT_DISTANCE, self.fov);
+        self.apply(&mut camera);
+        camera
+    }
+
+    // Place the camera on its orbit around the center, looking inwards
+    pub fn apply(&self, camera: &mut Camera) {
+        let [_, _, [forward_x, forward_y, forward_z]] = self.orientation.to_matrix();
+        camera.coordinates.x = self.center.x - forward_x * self.distance;
+        camera.coordinates.y = self.center.y - forward_y * self.distance;
+        camera.coordinates.z = self.center.z - forward_z * self.distance;
+        camera.orientation = self.orientation;
+    }
+
+    // Drag the scene across the screen by `right` and `up` world units (the
+    // center moves the opposite way)
+    pub fn pan(&mut self, right: f32, up: f32) {
+        // The rows are the screen's right, up and into it in viewer space
+        let [screen_right, screen_up, _] = self.orientation.to_matrix();
+        self.center.x -= right * screen_right[0] + up * screen_up[0];
+        self.center.y -= right * screen_right[1] + up * screen_up[1];
+        self.center.z -= right * screen_right[2] + up * screen_up[2];
+    }
+
+    // Apply an orbit, pan or zoom, with distances given as fractions of the
+    // scene's diagonal; other events are ignored
+    pub fn navigate(&mut self, event: InputEvent, diagonal: f32) {
+        match event {
+            InputEvent::Orbit { yaw, pitch } => self.orbit(yaw, pitch),
+            InputEvent::Tumble(rotation) => self.tumble(rotation),
+            InputEvent::Pan { right, up } => self.pan(right * diagonal, up * diagonal),
+            InputEvent::Zoom(amount) => {
+                self.distance = (self.distance - amount * diagonal).max(MIN_DISTANCE);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Keeps the view moving after navigation input stops, as if it had weight:
+// a flick of the mouse spins it on, and a burst of scrolling or held keys
+// carries on a little, slowing to a stop. Speeds come from how far recent
+// inputs moved the view over the time between them. Nothing glides while a
+// mouse button is held, so letting go of a still drag leaves the view still.
+pub struct Inertia {
+    orbit: (f32, f32), // Yaw and pitch, radians per second
+    tumble: [f32; 3],  // Camera-space rotation vector, radians per second
+    pan: (f32, f32),   // Diagonals per second
+    zoom: f32,         // Diagonals per second
+    last_input: Option<time::Instant>,
+    held: bool,
+}
+
+impl Inertia {
+    pub fn new() -> Inertia {
+        Inertia {
+            orbit: (0.0, 0.0),
+            tumble: [0.0; 3],
+            pan: (0.0, 0.0),
+            zoom: 0.0,
+            last_input: None,
+            held: false,
+        }
+    }
+
+    pub fn stop(&mut self) {
+        *self = Inertia {
+            held: self.held,
+            ..Inertia::new()
+        };
+    }
+
+    // A mouse button went down, which catches the view, or up
+    pub fn hold(&mut self, held: bool) {
+        self.held = held;
+        if held {
+            self.stop();
+        }
+    }
+
+    // Take the speed of a navigation event that just moved the view, averaged
+    // with the last one's to even out the steps of terminal cells
+    pub fn push(&mut self, event: InputEvent) {
+        let now = time::Instant::now();
+        let gap = match self.last_input {
+            Some(last) => now.duration_since(last).as_secs_f32(),
+            None => f32::INFINITY,
+        };
+        if gap > GLIDE_DELAY {
+            // The start of a new movement
+            self.stop();
+        }
+        let gap = gap.clamp(MIN_INPUT_GAP, GLIDE_DELAY);
+        let blend = |speed: f32, amount: f32| (speed + amount / gap) / 2.0;
+        let previous = mem::take(self);
+        self.held = previous.held;
+        match event {
+            InputEvent::Orbit { yaw, pitch } => {
+                self.orbit = (blend(previous.orbit.0, yaw), blend(previous.orbit.1, pitch));
+            }
+            InputEvent::Tumble(rotation) => {
+                let turn = rotation.to_rotation_vector();
+                self.tumble = array::from_fn(|i| blend(previous.tumble[i], turn[i]));
+            }
+            InputEvent::Pan { right, up } => {
+                self.pan = (blend(previous.pan.0, right), blend(previous.pan.1, up));
+            }
+            InputEvent::Zoom(amount) => self.zoom = blend(previous.zoom, amount),
+            _ => {}
+        }
+        self.last_input = Some(now);
+    }
+
+    // Glide `view` on by `dt` seconds once input has stopped, losing speed at
+    // `friction` per second. Returns whether it moved.
+    pub fn update(&mut self, view: &mut ViewState, dt: f32, friction: f32, diagonal: f32) -> bool {
+        let resting = self
+            .last_input
+            .is_some_and(|last| last.elapsed().as_secs_f32() < GLIDE_DELAY);
+        let speed = [
+            self.orbit.0,
+            self.orbit.1,
+            self.pan.0,
+            self.pan.1,
+            self.zoom,
+            dot(self.tumble, self.tumble).sqrt(),
+        ]
+        .into_iter()
+        .fold(0.0, |fastest: f32, speed| fastest.max(speed.abs()));
+        if self.held || resting || speed < MIN_GLIDE_SPEED {
+            return false;
+        }
+
+        // Only one of these is moving, as each input replaces the others
+        if self.orbit != (0.0, 0.0) {
+            let (yaw, pitch) = (self.orbit.0 * dt, self.orbit.1 * dt);
+            view.navigate(InputEvent::Orbit { yaw, pitch }, diagonal);
+        }
+        if self.tumble != [0.0; 3] {
+            view.tumble(Quaternion::from_rotation_vector(
+                self.tumble.map(|v| v * dt),
+            ));
+        }
+        let (right, up) = (self.pan.0 * dt, self.pan.1 * dt);
+        view.navigate(InputEvent::Pan { right, up }, diagonal);
+        view.navigate(InputEvent::Zoom(self.zoom * dt), diagonal);
+
+        let decay = (-friction * dt).exp();
+        self.orbit = (self.orbit.0 * decay, self.orbit.1 * decay);
+        self.tumble = self.tumble.map(|v| v * decay);
+        self.pan = (self.pan.0 * decay, self.pan.1 * decay);
+        self.zoom *= decay;
+        true
+    }
+}
+
+impl Default for Inertia {
+    fn default() -> Inertia {
+        Inertia::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn angles_round_trip_through_the_orientation() {
+        for (yaw, pitch, roll) in [(0.7, 0.3, 0.0), (-2.0, -1.1, 0.4), (3.0, 1.5, -0.2)] {
+            let view = ViewState {
+                orientation: ViewState::orientation_at(yaw, pitch, roll),
+                distance: 1.0,
+                center: Point3D::new(0.0, 0.0, 0.0),
+                fov: 1.0,
+            };
+            let (y, p, r) = view.angles();
+            assert!(
+                (y - yaw).abs() < 1e-4 && (p - pitch).abs() < 1e-4 && (r - roll).abs() < 1e-4,
+                "{:?} from {:?}",
+                (y, p, r),
+                (yaw, pitch, roll)
+            );
+        }
+    }
+}
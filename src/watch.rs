@@ -0,0 +1,45 @@
+use std::time::{Instant, SystemTime};
+use std::*;
+
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(500);
+
+// Polls file modification times so loaded files can be hot-reloaded.
+// Checks are rate limited, so `changed` is cheap to call every frame.
+pub struct FileWatcher {
+    files: Vec<(String, Option<SystemTime>)>,
+    last_poll: Instant,
+}
+
+impl FileWatcher {
+    pub fn new(paths: &[String]) -> FileWatcher {
+        FileWatcher {
+            files: paths
+                .iter()
+                .map(|path| (path.clone(), modified_time(path)))
+                .collect(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    // Watched files modified since the previous check
+    pub fn changed(&mut self) -> Vec<String> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut changed = Vec::new();
+        for (path, last_modified) in &mut self.files {
+            let modified = modified_time(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,262 @@
+use std::*;
+
+use num::NonZeroU32;
+use rc::Rc;
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use softbuffer::{Context, Surface};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{Key, NamedKey};
+use winit::window::{Window, WindowId};
+
+use crate::graphics::{BRAILLE_CELL, PointCloud, Viewport};
+use crate::input::{InputEvent, InputMapper, InputMode, MouseSettings, ViewChange};
+use crate::render::Renderer;
+use crate::view::ViewState;
+use crate::{fit_box, initial_view, view_preset};
+
+const WINDOW_SIZE: (f64, f64) = (1280.0, 720.0); // Logical pixels the window opens at
+
+type WindowSurface = Surface<Rc<Window>, Rc<Window>>;
+
+// The viewer in a native window, one image pixel per window pixel. Input is
+// turned into the terminal's events and mapped the same way, so keys, drags
+// and scrolling move the camera as they do in the terminal.
+struct WindowViewer<'a> {
+    point_cloud: &'a PointCloud,
+    renderer: Box<dyn Renderer + 'a>,
+    input_mapper: InputMapper,
+    mouse: MouseSettings,
+    bounds_percentile: Option<f32>,
+    view: ViewState,
+    diagonal: f32, // Of the framed box, which input distances are fractions of
+    window: Option<(Rc<Window>, WindowSurface)>, // Once the app resumes
+    modifiers: KeyModifiers,
+    cursor: (u16, u16), // Last position in window pixels
+    held_button: Option<MouseButton>,
+    error: Option<Box<dyn error::Error>>, // What ended the event loop early
+}
+
+pub fn run(
+    point_cloud: &PointCloud,
+    renderer: Box<dyn Renderer + '_>,
+    input_mapper: InputMapper,
+    mouse: MouseSettings,
+    bounds_percentile: Option<f32>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut viewer = WindowViewer {
+        point_cloud,
+        renderer,
+        input_mapper,
+        mouse,
+        bounds_percentile,
+        view: initial_view(point_cloud, bounds_percentile),
+        diagonal: fit_box(point_cloud, bounds_percentile).1,
+        window: None,
+        modifiers: KeyModifiers::NONE,
+        cursor: (0, 0),
+        held_button: None,
+        error: None,
+    };
+    EventLoop::new()?.run_app(&mut viewer)?;
+    viewer.error.map_or(Ok(()), Err)
+}
+
+impl WindowViewer<'_> {
+    fn open(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn error::Error>> {
+        let attributes = Window::default_attributes()
+            .with_title(format!(
+                "altostratus - {}",
+                self.point_cloud.sources.join(", ")
+            ))
+            .with_inner_size(LogicalSize::new(WINDOW_SIZE.0, WINDOW_SIZE.1));
+        let window = Rc::new(event_loop.create_window(attributes)?);
+        let context = Context::new(window.clone())?;
+        let surface = Surface::new(&context, window.clone())?;
+        self.window = Some((window, surface));
+        Ok(())
+    }
+
+    fn draw(&mut self) -> Result<(), Box<dyn error::Error>> {
+        let Some((window, surface)) = &mut self.window else {
+            return Ok(());
+        };
+        let size = window.inner_size();
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            return Ok(()); // Minimized
+        };
+        surface.resize(width, height)?;
+
+        let viewport = Viewport::new(
+            size.width.min(u16::MAX as u32) as u16,
+            size.height.min(u16::MAX as u32) as u16,
+        );
+        let image = self.renderer.render(&self.view, viewport);
+        let mut buffer = surface.buffer_mut()?;
+        buffer.fill(0);
+        for (row, image_row) in buffer
+            .chunks_mut(size.width as usize)
+            .zip(image.pixels().chunks(image.width))
+        {
+            for (pixel, &[r, g, b]) in row.iter_mut().zip(image_row) {
+                *pixel = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+            }
+        }
+        buffer.present()?;
+        Ok(())
+    }
+
+    // The terminal event a window event stands for, if any
+    fn terminal_event(&mut self, event: &WindowEvent) -> Option<Event> {
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                let state = modifiers.state();
+                self.modifiers = KeyModifiers::NONE;
+                self.modifiers.set(KeyModifiers::SHIFT, state.shift_key());
+                self.modifiers
+                    .set(KeyModifiers::CONTROL, state.control_key());
+                self.modifiers.set(KeyModifiers::ALT, state.alt_key());
+                None
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                let code = match &event.logical_key {
+                    Key::Character(text) => KeyCode::Char(text.chars().next()?),
+                    Key::Named(NamedKey::Space) => KeyCode::Char(' '),
+                    Key::Named(NamedKey::ArrowLeft) => KeyCode::Left,
+                    Key::Named(NamedKey::ArrowRight) => KeyCode::Right,
+                    Key::Named(NamedKey::ArrowUp) => KeyCode::Up,
+                    Key::Named(NamedKey::ArrowDown) => KeyCode::Down,
+                    Key::Named(NamedKey::PageUp) => KeyCode::PageUp,
+                    Key::Named(NamedKey::PageDown) => KeyCode::PageDown,
+                    Key::Named(NamedKey::Home) => KeyCode::Home,
+                    Key::Named(NamedKey::End) => KeyCode::End,
+                    _ => return None,
+                };
+                Some(Event::Key(KeyEvent::new(code, self.modifiers)))
+            }
+            // Window pixels stand in for terminal cells
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = (position.x as u16, position.y as u16);
+                Some(self.mouse_event(match self.held_button {
+                    Some(button) => MouseEventKind::Drag(button),
+                    None => MouseEventKind::Moved,
+                }))
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    winit::event::MouseButton::Left => MouseButton::Left,
+                    winit::event::MouseButton::Right => MouseButton::Right,
+                    winit::event::MouseButton::Middle => MouseButton::Middle,
+                    _ => return None,
+                };
+                let kind = match state {
+                    ElementState::Pressed => {
+                        self.held_button = Some(button);
+                        MouseEventKind::Down(button)
+                    }
+                    ElementState::Released => {
+                        self.held_button = None;
+                        MouseEventKind::Up(button)
+                    }
+                };
+                Some(self.mouse_event(kind))
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let up = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y as f64,
+                    MouseScrollDelta::PixelDelta(position) => position.y,
+                };
+                match up.partial_cmp(&0.0)? {
+                    cmp::Ordering::Greater => Some(self.mouse_event(MouseEventKind::ScrollUp)),
+                    cmp::Ordering::Less => Some(self.mouse_event(MouseEventKind::ScrollDown)),
+                    cmp::Ordering::Equal => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // At the cursor, with the modifiers held
+    fn mouse_event(&self, kind: MouseEventKind) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column: self.cursor.0,
+            row: self.cursor.1,
+            modifiers: self.modifiers,
+        })
+    }
+
+    fn request_redraw(&self) {
+        if let Some((window, _)) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
+
+impl ApplicationHandler for WindowViewer<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_none()
+            && let Err(e) = self.open(event_loop)
+        {
+            self.error = Some(e);
+            event_loop.exit();
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested => {
+                if let Err(e) = self.draw() {
+                    self.error = Some(e);
+                    event_loop.exit();
+                }
+            }
+            WindowEvent::Resized(_) => self.request_redraw(),
+            event => {
+                let Some(event) = self.terminal_event(&event) else {
+                    return;
+                };
+                // Drags are measured against a viewport of braille dots, as
+                // in the terminal, where each cell is two dots wide
+                let size = self
+                    .window
+                    .as_ref()
+                    .map_or_else(Default::default, |(window, _)| window.inner_size());
+                let viewport = Viewport::new(
+                    (size.width as u16).saturating_mul(BRAILLE_CELL.0),
+                    (size.height as u16).saturating_mul(BRAILLE_CELL.1),
+                );
+                let input =
+                    self.input_mapper
+                        .map(&event, InputMode::Navigate, viewport, self.mouse);
+                match input {
+                    Some(
+                        input @ (InputEvent::Orbit { .. }
+                        | InputEvent::Tumble(_)
+                        | InputEvent::Pan { .. }
+                        | InputEvent::Zoom(_)),
+                    ) => self.view.navigate(input, self.diagonal),
+                    Some(InputEvent::View(ViewChange::Reset)) => {
+                        self.view = initial_view(self.point_cloud, self.bounds_percentile);
+                    }
+                    Some(InputEvent::View(ViewChange::Preset(preset))) => {
+                        let percentile = self.bounds_percentile;
+                        view_preset(&mut self.view, preset, self.point_cloud, percentile);
+                    }
+                    Some(InputEvent::Quit) => event_loop.exit(),
+                    // Picking, commands and layouts are terminal-only
+                    _ => return,
+                }
+                self.request_redraw();
+            }
+        }
+    }
+}